@@ -1,4 +1,7 @@
+use crate::config::HostKind;
+use crate::core::GroupBy;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "todo")]
@@ -12,7 +15,29 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Initialize guidebook-todo for first-time use
-    Init,
+    Init {
+        #[arg(long, conflicts_with_all = ["github", "host", "remote"])]
+        /// Set up a local-only data repository, without prompting
+        local: bool,
+        #[arg(long, conflicts_with_all = ["host", "remote"])]
+        /// Create or link the guidebook-data repository on GitHub, without prompting
+        github: bool,
+        #[arg(long, value_enum, conflicts_with = "remote")]
+        /// Create or link the guidebook-data repository on this host (github, gitlab, gitea or forgejo), without prompting
+        host: Option<HostKind>,
+        #[arg(long)]
+        /// Clone the data repository from this git URL (or push a new one to it if empty)
+        remote: Option<String>,
+        #[arg(long)]
+        /// Keep the data repository here instead of ~/.local/share/guidebook
+        data_dir: Option<PathBuf>,
+        #[arg(long)]
+        /// Replace an existing data directory (it is moved aside, not deleted)
+        force: bool,
+        #[arg(long)]
+        /// Import the TODO file in the current directory into the global list
+        migrate_local: bool,
+    },
 
     /// Add a new TODO
     Add {
@@ -21,10 +46,24 @@ pub enum Commands {
         #[arg(long)]
         /// Create TODO with defaults, bypass TUI
         quick: bool,
+        #[arg(long)]
+        /// Due date (YYYY-MM-DD [HH:MM], today, tomorrow, weekday, +3d, +2w)
+        due: Option<String>,
+        #[arg(long)]
+        /// Repeat rule (daily, weekly[:mon,thu], monthly[:15], after:3d)
+        repeat: Option<String>,
+        #[arg(long)]
+        /// Create as a subtask of this TODO ID
+        parent: Option<u32>,
+        #[arg(long)]
+        /// Comma-separated IDs of TODOs that must be finished first
+        blocked_by: Option<String>,
     },
 
     /// List TODOs
     List {
+        /// Search query, e.g. "#bug @work p0..p1 (!todo OR !inprogress) NOT title:flaky"
+        query: Vec<String>,
         #[arg(long)]
         /// Filter by status
         status: Option<String>,
@@ -40,6 +79,24 @@ pub enum Commands {
         #[arg(long)]
         /// Show all TODOs regardless of status
         all: bool,
+        #[arg(long)]
+        /// Only show active TODOs that are not blocked
+        ready: bool,
+        #[arg(long)]
+        /// Only TODOs created after this date (YYYY-MM-DD, yesterday, last-week, 7d, ...)
+        created_after: Option<String>,
+        #[arg(long)]
+        /// Only TODOs finished on or since this date (YYYY-MM-DD, today, this-week, 14d, ...)
+        done_since: Option<String>,
+        #[arg(long)]
+        /// Run a saved view; a query given as well narrows it further
+        view: Option<String>,
+        #[arg(long, allow_hyphen_values = true)]
+        /// Sort order, e.g. priority,-created,title ('-' reverses a field)
+        sort: Option<String>,
+        #[arg(long, value_enum)]
+        /// Split the list into sections
+        group_by: Option<GroupBy>,
     },
 
     /// Update a TODO
@@ -64,6 +121,21 @@ pub enum Commands {
         #[arg(long)]
         /// Update notes
         notes: Option<String>,
+        #[arg(long)]
+        /// New due date, or 'none' to clear it
+        due: Option<String>,
+        #[arg(long)]
+        /// New repeat rule, or 'none' to stop repeating
+        repeat: Option<String>,
+        #[arg(long)]
+        /// Move under this parent TODO ID, or 'none' to make top-level
+        parent: Option<String>,
+        #[arg(long)]
+        /// Open subtasks when completing/archiving a parent (complete, leave)
+        children: Option<String>,
+        #[arg(long, allow_hyphen_values = true)]
+        /// Blocking TODO IDs (3,4 to replace, +5 to add, -3 to remove, 'none' to clear)
+        blocked_by: Option<String>,
     },
 
     /// Delete TODOs
@@ -96,9 +168,65 @@ pub enum Commands {
         id: u32,
     },
 
+    /// Show how a TODO changed over time, from the data repository's git log
+    History {
+        /// TODO ID
+        id: u32,
+    },
+
+    /// Bring back a TODO, or the whole list, as it was at an earlier commit or date
+    Restore {
+        /// TODO ID (omit with --all)
+        id: Option<u32>,
+        #[arg(long)]
+        /// Commit (hash, HEAD~3) or date (YYYY-MM-DD, yesterday, 7d) to restore from
+        at: String,
+        #[arg(long)]
+        /// Restore the whole list instead of one TODO
+        all: bool,
+        #[arg(long)]
+        /// Skip the confirmation prompt
+        yes: bool,
+    },
+
+    /// Show TODO changes between commits, or uncommitted changes since the last commit
+    Diff {
+        /// Older commit or date (default: HEAD)
+        from: Option<String>,
+        /// Newer commit or date (default: the working file)
+        to: Option<String>,
+    },
+
     /// Show statistics
     Stats,
 
+    /// Start timing work on a TODO (stops any running timer)
+    Start {
+        /// TODO ID
+        id: u32,
+    },
+
+    /// Stop the running timer
+    Stop,
+
+    /// Summarize tracked data
+    Report {
+        #[command(subcommand)]
+        report: ReportCommands,
+    },
+
+    /// Save and manage named searches
+    View {
+        #[command(subcommand)]
+        view: ViewCommands,
+    },
+
+    /// Revert the last change to the TODO list
+    Undo,
+
+    /// Re-apply the last undone change
+    Redo,
+
     /// Push changes to GitHub repository
     Push {
         #[arg(long)]
@@ -107,8 +235,52 @@ pub enum Commands {
         #[arg(long)]
         /// Force push even if no changes detected
         force: bool,
+        #[arg(long)]
+        /// Remote to push to (default: the branch's upstream, the `remote` setting, or origin)
+        remote: Option<String>,
+        #[arg(long)]
+        /// Remote branch to push to (default: the branch's upstream, the `branch` setting, or the current branch)
+        branch: Option<String>,
+    },
+
+    /// Pull, merge by TODO ID, and push the data repository
+    Sync {
+        #[arg(long)]
+        /// Resolve every conflict this way instead of asking (local, remote)
+        prefer: Option<String>,
     },
 
     /// Open the TODO file in VS Code
     Code,
 }
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Time tracked, grouped by project and category
+    Time {
+        #[arg(long)]
+        /// Only count time from this date (YYYY-MM-DD, today, week, month, monday, 7d)
+        since: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ViewCommands {
+    /// Save a query under a name, replacing any view with that name
+    Save {
+        /// View name
+        name: String,
+        /// Search query, same syntax as `todo list`
+        query: Vec<String>,
+        #[arg(long, allow_hyphen_values = true)]
+        /// Sort order, e.g. priority,-created,title
+        sort: Option<String>,
+    },
+    /// List saved views
+    List,
+    /// Delete a saved view
+    Delete {
+        /// View name
+        name: String,
+    },
+}
@@ -1,38 +1,151 @@
-use crate::cli::args::{Cli, Commands};
-use crate::core::{default_display, find_todo_file, load_todos, TodoList};
-use crate::display::{format_detail, format_list};
+use crate::cli::args::{Cli, Commands, ReportCommands, ViewCommands};
+use crate::cli::init::{run_init, InitMode, InitOptions};
+use crate::config::HostKind;
+use crate::core::{
+    changes_commit_message, commit_all, default_display, diff_todo_lists, find_todo_file,
+    format_duration, get_data_dir, global_todo_file, group_todos, has_changes, load_todos,
+    open_repo, parse_child_action, parse_due, parse_recurrence, parse_since, parse_sort,
+    parse_status, push, rank_by_query, redo_next, remote_target, save_todos, sort_todos,
+    sync_data_repo, todo_history, uncommitted_changes, undo_last, BackendKind, ChildAction,
+    Comparison, ConflictKind, DateField, DateFilter, GroupBy, MergeConflict, PushError, Query,
+    Resolution, SavedView, SortKey, Status, Term, TodoList, TrackedFile, Views,
+};
+use crate::display::{
+    format_detail, format_grouped_list, format_history, format_list, format_time_report,
+    format_todo_diff,
+};
 use crate::tui::{run_add_todo, run_edit_todo, run_search_todo};
 use anyhow::{Context, Result};
-use dirs::home_dir;
-use std::path::PathBuf;
+use colored::Colorize;
+use std::io::{IsTerminal, Write};
 use std::process::Command;
 
+/// Changes shown in the history panel of `todo show`
+const SHOW_HISTORY_EVENTS: usize = 5;
+
 pub async fn run_command(cli: Cli) -> Result<()> {
     match cli.command {
         None => {
             // Default behavior: show overview
             default_display().await?;
         }
-        Some(Commands::Init) => {
-            crate::cli::init::run_init().await?;
+        Some(Commands::Init {
+            local,
+            github,
+            host,
+            remote,
+            data_dir,
+            force,
+            migrate_local,
+        }) => {
+            let mode = if local {
+                Some(InitMode::Local)
+            } else if github {
+                Some(InitMode::Hosted(HostKind::GitHub))
+            } else if let Some(host) = host {
+                Some(InitMode::Hosted(host))
+            } else {
+                remote.map(InitMode::Remote)
+            };
+            run_init(InitOptions {
+                mode,
+                data_dir,
+                force,
+                migrate_local,
+            })
+            .await?;
         }
-        Some(Commands::Add { title, quick }) => {
+        Some(Commands::Add {
+            title,
+            quick,
+            due,
+            repeat,
+            parent,
+            blocked_by,
+        }) => {
             if quick {
                 // Quick add with defaults
-                quick_add_todo(title).await?;
+                quick_add_todo(title, due, repeat, parent, blocked_by).await?;
             } else {
                 // Interactive TUI add
-                run_add_todo(title).await?;
+                if repeat.is_some() {
+                    anyhow::bail!(
+                        "--repeat requires --quick; use 'todo update --repeat' after adding"
+                    );
+                }
+                if blocked_by.is_some() {
+                    anyhow::bail!(
+                        "--blocked-by requires --quick; use 'todo update --blocked-by' after adding"
+                    );
+                }
+                run_add_todo(title, due, parent).await?;
             }
         }
         Some(Commands::List {
+            query,
             status,
             category,
             priority,
             tags,
             all,
+            ready,
+            created_after,
+            done_since,
+            view,
+            sort: sort_order,
+            group_by,
         }) => {
-            list_todos(status, category, priority, tags, all).await?;
+            let query = query.join(" ");
+            let mut queries =
+                vec![Query::parse(&query).with_context(|| format!("Invalid query: {}", query))?];
+            let mut sort = Vec::new();
+            if let Some(name) = view {
+                let views = Views::load()?;
+                let view = views.get(&name)?;
+                queries.push(Query::parse(&view.query).with_context(|| {
+                    format!("Invalid query in view '{}': {}", name, view.query)
+                })?);
+                if let Some(order) = &view.sort {
+                    sort = parse_sort(order).map_err(|error| {
+                        anyhow::anyhow!("Invalid sort order in view '{}': {}", name, error)
+                    })?;
+                }
+            }
+            // An explicit --sort wins over the view's
+            if let Some(order) = sort_order {
+                sort = parse_sort(&order)
+                    .map_err(|error| anyhow::anyhow!("Invalid --sort: {}", error))?;
+            }
+            if let Some(date) = created_after {
+                queries.push(date_query(
+                    "--created-after",
+                    DateField::Created,
+                    Comparison::After,
+                    &date,
+                )?);
+            }
+            if let Some(date) = done_since {
+                queries.push(date_query(
+                    "--done-since",
+                    DateField::Finished,
+                    Comparison::OnOrAfter,
+                    &date,
+                )?);
+            }
+            list_todos(
+                Query::And(queries),
+                ListOptions {
+                    status,
+                    category,
+                    priority,
+                    tags,
+                    all,
+                    ready,
+                    sort,
+                    group_by,
+                },
+            )
+            .await?;
         }
         Some(Commands::Update {
             id,
@@ -42,8 +155,29 @@ pub async fn run_command(cli: Cli) -> Result<()> {
             category,
             project,
             notes,
+            due,
+            repeat,
+            parent,
+            children,
+            blocked_by,
         }) => {
-            update_todo(id, status, priority, tags, category, project, notes).await?;
+            update_todo(
+                id,
+                TodoChanges {
+                    status,
+                    priority,
+                    tags,
+                    category,
+                    project,
+                    notes,
+                    due,
+                    repeat,
+                    parent,
+                    children,
+                    blocked_by,
+                },
+            )
+            .await?;
         }
         Some(Commands::Delete {
             id,
@@ -61,11 +195,56 @@ pub async fn run_command(cli: Cli) -> Result<()> {
         Some(Commands::Show { id }) => {
             show_todo(id).await?;
         }
+        Some(Commands::History { id }) => {
+            show_history(id).await?;
+        }
+        Some(Commands::Restore { id, at, all, yes }) => {
+            restore(id, at, all, yes).await?;
+        }
+        Some(Commands::Diff { from, to }) => {
+            show_diff(from, to).await?;
+        }
         Some(Commands::Stats) => {
             show_stats().await?;
         }
-        Some(Commands::Push { message, force }) => {
-            push_to_github(message, force).await?;
+        Some(Commands::Start { id }) => {
+            start_timer(id).await?;
+        }
+        Some(Commands::Stop) => {
+            stop_timer().await?;
+        }
+        Some(Commands::Report { report }) => match report {
+            ReportCommands::Time { since } => {
+                report_time(since).await?;
+            }
+        },
+        Some(Commands::View { view }) => match view {
+            ViewCommands::Save { name, query, sort } => {
+                save_view(&name, &query.join(" "), sort)?;
+            }
+            ViewCommands::List => {
+                list_views()?;
+            }
+            ViewCommands::Delete { name } => {
+                delete_view(&name)?;
+            }
+        },
+        Some(Commands::Undo) => {
+            undo().await?;
+        }
+        Some(Commands::Redo) => {
+            redo().await?;
+        }
+        Some(Commands::Push {
+            message,
+            force,
+            remote,
+            branch,
+        }) => {
+            push_to_github(message, force, remote, branch).await?;
+        }
+        Some(Commands::Sync { prefer }) => {
+            sync(prefer).await?;
         }
         Some(Commands::Code) => {
             open_in_vscode().await?;
@@ -74,7 +253,13 @@ pub async fn run_command(cli: Cli) -> Result<()> {
     Ok(())
 }
 
-async fn quick_add_todo(title: Option<String>) -> Result<()> {
+async fn quick_add_todo(
+    title: Option<String>,
+    due: Option<String>,
+    repeat: Option<String>,
+    parent: Option<u32>,
+    blocked_by: Option<String>,
+) -> Result<()> {
     let title = title.unwrap_or_else(|| {
         println!("Enter TODO title:");
         let mut input = String::new();
@@ -86,44 +271,263 @@ async fn quick_add_todo(title: Option<String>) -> Result<()> {
         anyhow::bail!("TODO title cannot be empty");
     }
 
+    let due = due.as_deref().map(parse_due).transpose()?;
+    let recurrence = repeat.as_deref().map(parse_recurrence).transpose()?;
+
     let mut todo_list = load_todos().await?;
-    let todo = todo_list.create_todo(title);
+    let mut todo = todo_list.create_todo(title);
+    if let Some((due_date, due_time)) = due {
+        todo.due_date = Some(due_date);
+        todo.due_time = due_time;
+    }
+    todo.recurrence = recurrence;
+    let id = todo.id;
     todo_list.add_todo(todo);
+    if parent.is_some() {
+        todo_list.set_parent(id, parent)?;
+    }
+    if let Some(blocked_by) = blocked_by {
+        todo_list.update_blockers(id, &blocked_by)?;
+    }
     todo_list.save().await?;
 
     println!("✓ TODO added successfully");
     Ok(())
 }
 
-async fn list_todos(
+/// `--created-after` and `--done-since` as the query terms they stand for
+fn date_query(flag: &str, field: DateField, comparison: Comparison, date: &str) -> Result<Query> {
+    let filter = DateFilter::compare(comparison, date)
+        .map_err(|error| anyhow::anyhow!("Invalid {}: {}", flag, error))?;
+    Ok(Query::Term(Term::Date(field, filter)))
+}
+
+/// Filters and ordering requested by `todo list`, besides the query
+struct ListOptions {
     status: Option<String>,
     category: Option<String>,
     priority: Option<String>,
     tags: Option<String>,
     all: bool,
-) -> Result<()> {
+    ready: bool,
+    sort: Vec<SortKey>,
+    group_by: Option<GroupBy>,
+}
+
+async fn list_todos(query: Query, options: ListOptions) -> Result<()> {
     let todo_list = load_todos().await?;
-    let filtered_todos = todo_list.filter_todos(status, category, priority, tags, all);
-    format_list(&filtered_todos);
+    // A query about status may be asking for archived TODOs
+    let all = options.all || query.mentions_status();
+    let mut filtered_todos = todo_list.filter_todos(
+        options.status,
+        options.category,
+        options.priority,
+        options.tags,
+        all,
+    );
+    filtered_todos.retain(|todo| query.matches(todo));
+    if options.ready {
+        filtered_todos.retain(|todo| todo_list.is_ready(todo.id));
+    }
+    // Best matches first; a sort order takes over, keeping that order for ties
+    rank_by_query(&mut filtered_todos, &query);
+    sort_todos(&mut filtered_todos, &options.sort);
+    match options.group_by {
+        Some(group_by) => {
+            let groups = group_todos(&filtered_todos, group_by);
+            format_grouped_list(&groups, filtered_todos.len(), &todo_list);
+        }
+        None => format_list(&filtered_todos, &todo_list),
+    }
     Ok(())
 }
 
-async fn update_todo(
-    id: u32,
+fn save_view(name: &str, query: &str, sort: Option<String>) -> Result<()> {
+    let mut views = Views::load()?;
+    let view = SavedView {
+        query: query.to_string(),
+        sort,
+    };
+    let replaced = views.insert(name, view)?;
+    views.save(&format!("Save view {}", name))?;
+    if replaced {
+        println!("✓ Updated view '{}'", name);
+    } else {
+        println!("✓ Saved view '{}'", name);
+    }
+    println!("  Run it with: todo list --view {}", name);
+    Ok(())
+}
+
+fn list_views() -> Result<()> {
+    let views = Views::load()?;
+    if views.views.is_empty() {
+        println!("No saved views. Save one with: todo view save <name> \"<query>\"");
+        return Ok(());
+    }
+    let width = views
+        .views
+        .keys()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (name, view) in &views.views {
+        let query = if view.query.is_empty() {
+            "(everything)"
+        } else {
+            view.query.as_str()
+        };
+        match &view.sort {
+            Some(sort) => println!("{:width$}  {}  sort: {}", name, query, sort, width = width),
+            None => println!("{:width$}  {}", name, query, width = width),
+        }
+    }
+    Ok(())
+}
+
+fn delete_view(name: &str) -> Result<()> {
+    let mut views = Views::load()?;
+    views.remove(name)?;
+    views.save(&format!("Delete view {}", name))?;
+    println!("✓ Deleted view '{}'", name);
+    Ok(())
+}
+
+/// Field changes requested by `todo update`, as given on the command line
+struct TodoChanges {
     status: Option<String>,
     priority: Option<String>,
     tags: Option<String>,
     category: Option<String>,
     project: Option<String>,
     notes: Option<String>,
-) -> Result<()> {
+    due: Option<String>,
+    repeat: Option<String>,
+    parent: Option<String>,
+    children: Option<String>,
+    blocked_by: Option<String>,
+}
+
+async fn update_todo(id: u32, changes: TodoChanges) -> Result<()> {
     let mut todo_list = load_todos().await?;
-    todo_list.update_todo(id, status, priority, tags, category, project, notes)?;
+    if todo_list.get_todo(id).is_none() {
+        anyhow::bail!("TODO with ID {} not found", id);
+    }
+
+    // Completing or archiving a parent decides what happens to its open subtasks
+    let new_status = changes.status.as_deref().map(parse_status).transpose()?;
+    let child_action = match new_status {
+        Some(Status::Done) | Some(Status::Archived) => {
+            let open = todo_list.open_descendants(id).len();
+            if open == 0 {
+                None
+            } else {
+                match choose_child_action(id, open, changes.children.as_deref())? {
+                    Some(action) => Some(action),
+                    None => {
+                        println!("Update cancelled.");
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        _ => None,
+    };
+
+    if let Some(repeat) = changes.repeat {
+        let recurrence = if repeat.trim().is_empty() || repeat.trim().eq_ignore_ascii_case("none") {
+            None
+        } else {
+            Some(parse_recurrence(&repeat)?)
+        };
+        todo_list.set_recurrence(id, recurrence)?;
+    }
+
+    if let Some(parent) = changes.parent {
+        let parent_id = if parent.trim().is_empty() || parent.trim().eq_ignore_ascii_case("none") {
+            None
+        } else {
+            Some(
+                parent
+                    .trim()
+                    .trim_start_matches('#')
+                    .parse::<u32>()
+                    .with_context(|| format!("Invalid parent ID: {}", parent))?,
+            )
+        };
+        todo_list.set_parent(id, parent_id)?;
+    }
+
+    if let Some(blocked_by) = changes.blocked_by {
+        todo_list.update_blockers(id, &blocked_by)?;
+    }
+
+    let next_occurrence = todo_list.update_todo(
+        id,
+        changes.status,
+        changes.priority,
+        changes.tags,
+        changes.category,
+        changes.project,
+        changes.notes,
+        changes.due,
+    )?;
+
+    let cascaded = match (child_action, new_status) {
+        (Some(ChildAction::Cascade), Some(status)) => todo_list.cascade_status(id, status)?,
+        _ => 0,
+    };
+
     todo_list.save().await?;
     println!("✓ TODO updated successfully");
+
+    if cascaded > 0 {
+        println!("✓ {} open subtasks updated too", cascaded);
+    }
+
+    if let Some(next) = next_occurrence.and_then(|next_id| todo_list.get_todo(next_id)) {
+        println!(
+            "✓ Next occurrence created: #{} (due {})",
+            next.id,
+            next.format_due().unwrap_or_else(|| "-".to_string())
+        );
+    }
     Ok(())
 }
 
+/// Resolves what to do with open subtasks, prompting when no `--children` flag was given.
+///
+/// Returns `None` if the user aborts.
+fn choose_child_action(
+    id: u32,
+    open: usize,
+    children: Option<&str>,
+) -> Result<Option<ChildAction>> {
+    if let Some(action) = children {
+        return parse_child_action(action).map(Some);
+    }
+
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "TODO #{} has {} open subtasks. Pass --children complete or --children leave",
+            id,
+            open
+        );
+    }
+
+    println!("TODO #{} has {} open subtasks.", id, open);
+    print!("[c] Update them too  [l] Leave them open  [a] Abort: ");
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    match input.trim().to_lowercase().as_str() {
+        "c" => Ok(Some(ChildAction::Cascade)),
+        "l" => Ok(Some(ChildAction::Leave)),
+        _ => Ok(None),
+    }
+}
+
 async fn delete_todo(
     id: Option<u32>,
     category: Option<String>,
@@ -149,10 +553,87 @@ async fn delete_todo(
 async fn show_todo(id: u32) -> Result<()> {
     let todo_list = load_todos().await?;
     if let Some(todo) = todo_list.get_todo(id) {
-        format_detail(todo);
+        format_detail(todo, &todo_list);
     } else {
         anyhow::bail!("TODO with ID {} not found", id);
     }
+
+    // History panel: the latest few changes, when the TODO file is under git
+    if let Ok(events) = todo_history(id).await {
+        if !events.is_empty() {
+            println!("{}:", "History".bold());
+            let recent = events.len().saturating_sub(SHOW_HISTORY_EVENTS);
+            format_history(&events[recent..]);
+            if recent > 0 {
+                println!("  ... {} earlier changes (todo history {})", recent, id);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn restore(id: Option<u32>, at: String, all: bool, yes: bool) -> Result<()> {
+    let tracked = TrackedFile::current().await?;
+    let revision = tracked.resolve_revision(&at)?;
+    let snapshot = tracked.load_at(&revision)?;
+    let mut todo_list = load_todos().await?;
+
+    let mut target = TodoList::clone(&todo_list);
+    match (id, all) {
+        (Some(_), true) | (None, false) => {
+            anyhow::bail!("Specify either a TODO ID or --all")
+        }
+        (Some(id), false) => {
+            let todo = snapshot.get_todo(id).cloned().ok_or_else(|| {
+                anyhow::anyhow!("TODO #{} did not exist at {}", id, revision.summary)
+            })?;
+            target.restore_todo(todo);
+        }
+        (None, true) => target.restore_all(&snapshot),
+    }
+
+    let diffs = diff_todo_lists(&todo_list, &target);
+    if diffs.is_empty() {
+        println!("Nothing to restore: already matches {}", revision.summary);
+        return Ok(());
+    }
+
+    println!("Restoring from {}:", revision.summary);
+    format_todo_diff(&diffs);
+    if !yes && !confirm("Apply these changes?")? {
+        println!("Restore cancelled.");
+        return Ok(());
+    }
+
+    *todo_list = target;
+    todo_list.save().await?;
+    println!(
+        "✓ Restored {} TODOs from {} (todo undo reverts this)",
+        diffs.len(),
+        revision.summary
+    );
+    Ok(())
+}
+
+/// Asks a yes/no question on the terminal; refuses to guess without one
+fn confirm(question: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Not a terminal. Pass --yes to apply without confirmation");
+    }
+
+    print!("{} [y/N]: ", question);
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+async fn show_history(id: u32) -> Result<()> {
+    let events = todo_history(id).await?;
+    if events.is_empty() {
+        anyhow::bail!("No history found for TODO #{}", id);
+    }
+    format_history(&events);
     Ok(())
 }
 
@@ -162,7 +643,70 @@ async fn show_stats() -> Result<()> {
     Ok(())
 }
 
-async fn push_to_github(message: Option<String>, force: bool) -> Result<()> {
+async fn start_timer(id: u32) -> Result<()> {
+    let mut todo_list = load_todos().await?;
+    let stopped = todo_list.start_timer(id)?;
+    todo_list.save().await?;
+
+    if let Some(stopped) = stopped.and_then(|stopped_id| todo_list.get_todo(stopped_id)) {
+        println!("✓ Stopped timer on #{} {}", stopped.id, stopped.title);
+    }
+    if let Some(todo) = todo_list.get_todo(id) {
+        println!("✓ Timer started on #{} {}", todo.id, todo.title);
+    }
+    Ok(())
+}
+
+async fn stop_timer() -> Result<()> {
+    let mut todo_list = load_todos().await?;
+    match todo_list.stop_timer() {
+        Some((id, session)) => {
+            todo_list.save().await?;
+            let now = chrono::Local::now();
+            if let Some(todo) = todo_list.get_todo(id) {
+                println!(
+                    "✓ Timer stopped on #{} {} ({}, {} total)",
+                    todo.id,
+                    todo.title,
+                    format_duration(session),
+                    format_duration(todo.tracked_time(None, now))
+                );
+            }
+        }
+        None => println!("No timer is running."),
+    }
+    Ok(())
+}
+
+async fn undo() -> Result<()> {
+    match undo_last().await? {
+        Some(description) => println!("✓ Undid: {}", description),
+        None => println!("Nothing to undo."),
+    }
+    Ok(())
+}
+
+async fn redo() -> Result<()> {
+    match redo_next().await? {
+        Some(description) => println!("✓ Redid: {}", description),
+        None => println!("Nothing to redo."),
+    }
+    Ok(())
+}
+
+async fn report_time(since: Option<String>) -> Result<()> {
+    let todo_list = load_todos().await?;
+    let since = since.as_deref().map(parse_since).transpose()?;
+    format_time_report(&todo_list, since);
+    Ok(())
+}
+
+async fn push_to_github(
+    message: Option<String>,
+    force: bool,
+    remote: Option<String>,
+    branch: Option<String>,
+) -> Result<()> {
     let data_dir = get_data_dir()?;
 
     if !data_dir.exists() {
@@ -174,116 +718,188 @@ async fn push_to_github(message: Option<String>, force: bool) -> Result<()> {
         anyhow::bail!("Not a git repository. Run 'todo init' to set up GitHub integration.");
     }
 
-    // Change to data directory
-    let current_dir = std::env::current_dir()?;
-    std::env::set_current_dir(&data_dir)?;
+    let repo = open_repo(&data_dir)?;
+    let target = remote_target(&repo, remote, branch)?;
+    let has_changes = has_changes(&repo)?;
 
-    let result = async {
-        // Check if there are any changes to commit
-        let status_output = Command::new("git")
-            .args(&["status", "--porcelain"])
-            .output()
-            .context("Failed to check git status")?;
+    if !has_changes && !force {
+        println!("No changes to push. Use --force to push anyway.");
+        return Ok(());
+    }
 
-        if !status_output.status.success() {
-            anyhow::bail!(
-                "Git status failed: {}",
-                String::from_utf8_lossy(&status_output.stderr)
-            );
+    if has_changes {
+        // Show what changed and describe it in the commit message
+        let pending = global_todo_file()
+            .map(|path| uncommitted_changes(&path))
+            .unwrap_or_default();
+        if !pending.is_empty() {
+            format_todo_diff(&pending);
         }
+        let commit_message = message.unwrap_or_else(|| changes_commit_message(&pending));
 
-        let has_changes = !status_output.stdout.is_empty();
-
-        if !has_changes && !force {
-            println!("No changes to push. Use --force to push anyway.");
-            return Ok(());
-        }
+        commit_all(&repo, &commit_message)?;
+        println!(
+            "✓ Changes committed: {}",
+            commit_message.lines().next().unwrap_or_default()
+        );
+    }
 
-        if has_changes {
-            // Add all changes
-            let add_output = Command::new("git")
-                .args(&["add", "."])
-                .output()
-                .context("Failed to add files to git")?;
+    match push(&repo, &target) {
+        Ok(()) => {}
+        Err(PushError::NonFastForward) => anyhow::bail!(
+            "Push to {} was rejected: the remote has commits this machine doesn't.\n\
+             Run 'todo sync' to merge them, then push again.",
+            target
+        ),
+        Err(PushError::Other(e)) => return Err(e),
+    }
 
-            if !add_output.status.success() {
-                anyhow::bail!(
-                    "Git add failed: {}",
-                    String::from_utf8_lossy(&add_output.stderr)
-                );
-            }
+    println!("✓ Successfully pushed to {}", target);
+    Ok(())
+}
 
-            // Commit changes
-            let commit_message = message.unwrap_or_else(|| {
-                format!(
-                    "Update TODOs - {}",
-                    chrono::Local::now().format("%Y-%m-%d %H:%M")
-                )
-            });
-
-            let commit_output = Command::new("git")
-                .args(&["commit", "-m", &commit_message])
-                .output()
-                .context("Failed to commit changes")?;
-
-            if !commit_output.status.success() {
-                anyhow::bail!(
-                    "Git commit failed: {}",
-                    String::from_utf8_lossy(&commit_output.stderr)
-                );
-            }
+async fn sync(prefer: Option<String>) -> Result<()> {
+    let prefer = prefer.as_deref().map(parse_resolution).transpose()?;
+    let report = sync_data_repo(|conflict| match prefer {
+        Some(resolution) => Ok(resolution),
+        None => choose_resolution(conflict),
+    })?;
 
-            println!("✓ Changes committed: {}", commit_message);
-        }
+    let target = format!("{}/{}", report.remote, report.branch);
+    if report.committed {
+        println!("✓ Committed local changes");
+    }
+    if report.pulled_commits > 0 {
+        println!("✓ Pulled {} commits from {}", report.pulled_commits, target);
+    }
+    if report.remote_changes > 0 {
+        println!(
+            "✓ Merged {} remote TODO changes with local edits",
+            report.remote_changes
+        );
+    }
+    for (old, new) in &report.renumbered {
+        println!(
+            "✓ Renumbered local #{} to #{} (the ID was taken on the remote)",
+            old, new
+        );
+    }
+    for id in &report.detached {
+        println!(
+            "✓ Moved #{} to the top level (its merged parents formed a loop)",
+            id
+        );
+    }
+    if report.conflicts_resolved > 0 {
+        println!("✓ Resolved {} conflicts", report.conflicts_resolved);
+    }
+    if report.pushed {
+        println!("✓ Pushed to {}", target);
+    }
+    if report.pulled_commits == 0 && !report.pushed {
+        println!("Already up to date with {}.", target);
+    }
+    Ok(())
+}
 
-        // Push to GitHub
-        let push_output = Command::new("git")
-            .args(&["push", "origin", "main"])
-            .output()
-            .context("Failed to push to GitHub")?;
+fn parse_resolution(value: &str) -> Result<Resolution> {
+    match value.to_lowercase().as_str() {
+        "local" | "ours" => Ok(Resolution::Local),
+        "remote" | "theirs" => Ok(Resolution::Remote),
+        _ => anyhow::bail!("Invalid choice '{}'. Use local or remote", value),
+    }
+}
 
-        if !push_output.status.success() {
-            let stderr = String::from_utf8_lossy(&push_output.stderr);
+fn choose_resolution(conflict: &MergeConflict) -> Result<Resolution> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Sync conflict on #{} \"{}\". Run 'todo sync' in a terminal or pass --prefer local|remote",
+            conflict.id,
+            conflict.title
+        );
+    }
 
-            // Check if it's a "no upstream branch" error
-            if stderr.contains("no upstream branch") {
-                println!("Setting up upstream branch...");
+    println!("\nConflict on #{} \"{}\"", conflict.id, conflict.title);
+    match &conflict.kind {
+        ConflictKind::Field {
+            field,
+            local,
+            remote,
+        } => {
+            println!("  {} changed on both machines", field);
+            println!("  local:  {}", format_conflict_value(local));
+            println!("  remote: {}", format_conflict_value(remote));
+        }
+        ConflictKind::DeletedLocally { .. } => {
+            println!("  local:  deleted");
+            println!("  remote: edited");
+        }
+        ConflictKind::DeletedRemotely { .. } => {
+            println!("  local:  edited");
+            println!("  remote: deleted");
+        }
+    }
 
-                let upstream_output = Command::new("git")
-                    .args(&["push", "--set-upstream", "origin", "main"])
-                    .output()
-                    .context("Failed to set upstream branch")?;
+    loop {
+        print!("[l] Keep local  [r] Take remote  [a] Abort: ");
+        std::io::stdout().flush()?;
 
-                if !upstream_output.status.success() {
-                    anyhow::bail!(
-                        "Failed to set upstream: {}",
-                        String::from_utf8_lossy(&upstream_output.stderr)
-                    );
-                }
-            } else {
-                anyhow::bail!("Git push failed: {}", stderr);
-            }
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        match input.trim().to_lowercase().as_str() {
+            "l" => return Ok(Resolution::Local),
+            "r" => return Ok(Resolution::Remote),
+            "a" | "" => anyhow::bail!("Sync aborted; nothing was merged"),
+            _ => {}
         }
-
-        println!("✓ Successfully pushed to GitHub!");
-        Ok(())
     }
-    .await;
+}
 
-    // Always restore the original directory
-    std::env::set_current_dir(current_dir)?;
-    result
+fn format_conflict_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "(none)".to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
 }
 
-fn get_data_dir() -> Result<PathBuf> {
-    let home = home_dir().ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+async fn show_diff(from: Option<String>, to: Option<String>) -> Result<()> {
+    let tracked = TrackedFile::current().await?;
+    let load = |at: &str| -> Result<(TodoList, String)> {
+        let revision = tracked.resolve_revision(at)?;
+        Ok((tracked.load_at(&revision)?, revision.summary))
+    };
+
+    let (old, old_label) = match from.as_deref() {
+        Some(at) => load(at)?,
+        None => (tracked.load_head()?, "HEAD".to_string()),
+    };
+    let (new, new_label) = match to.as_deref() {
+        Some(at) => load(at)?,
+        None => (tracked.load_working()?, "working copy".to_string()),
+    };
+
+    let diffs = diff_todo_lists(&old, &new);
+    if diffs.is_empty() {
+        println!("No TODO changes between {} and {}.", old_label, new_label);
+        return Ok(());
+    }
 
-    Ok(home.join(".local").join("share").join("guidebook"))
+    println!("Changes from {} to {}:", old_label, new_label);
+    format_todo_diff(&diffs);
+    Ok(())
 }
 
 async fn open_in_vscode() -> Result<()> {
     let todo_file_path = find_todo_file().await?;
 
+    if BackendKind::from_path(&todo_file_path) == BackendKind::Sqlite {
+        anyhow::bail!(
+            "TODOs are stored in a SQLite database ({}), which can't be edited as text",
+            todo_file_path.display()
+        );
+    }
+
     // Check if the file exists, create it if it doesn't
     if !todo_file_path.exists() {
         println!("TODO file doesn't exist yet. Creating empty file...");
@@ -293,8 +909,7 @@ async fn open_in_vscode() -> Result<()> {
         }
 
         // Create an empty TODO list file
-        let empty_todo_list = TodoList::new();
-        empty_todo_list.save().await?;
+        save_todos(&TodoList::new(), &todo_file_path).await?;
     }
 
     // Try to open with VS Code
@@ -0,0 +1,616 @@
+use crate::config::{
+    credentials_path, load_token, remove_token, save_token, settings_path, url_host,
+    GitHubSettings, HostKind, RepoVisibility, Settings, DEFAULT_GITLAB_URL, TOKEN_ENV_VAR,
+};
+use anyhow::{Context, Result};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+use std::io::{self, IsTerminal, Write};
+use tokio::time::{Duration, Instant};
+
+/// A service that can create and host the data repository
+#[derive(Debug, Clone)]
+pub enum HostingProvider {
+    /// Authorized with the OAuth device flow
+    GitHub(GitHubSettings),
+    /// Authorized with a personal access token
+    GitLab { url: String },
+    /// Gitea or Forgejo, authorized with a personal access token
+    Gitea { url: String },
+}
+
+/// A token the host accepted, and the user it belongs to
+#[derive(Debug, Clone)]
+pub struct HostAccount {
+    pub token: String,
+    pub login: String,
+}
+
+impl HostingProvider {
+    pub fn from_settings(kind: HostKind, settings: &Settings) -> Result<HostingProvider> {
+        Ok(match kind {
+            HostKind::GitHub => HostingProvider::GitHub(settings.github.clone()),
+            HostKind::GitLab => HostingProvider::GitLab {
+                url: base_url(settings.gitlab.url.as_deref().unwrap_or(DEFAULT_GITLAB_URL)),
+            },
+            HostKind::Gitea => {
+                let Some(url) = settings.gitea.url.as_deref() else {
+                    anyhow::bail!(
+                        "No Gitea or Forgejo server configured. Set gitea.url in {}.",
+                        settings_path()?.display()
+                    );
+                };
+                HostingProvider::Gitea { url: base_url(url) }
+            }
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HostingProvider::GitHub(_) => "GitHub",
+            HostingProvider::GitLab { .. } => "GitLab",
+            HostingProvider::Gitea { .. } => "Gitea",
+        }
+    }
+
+    /// Web URL of the host; saved tokens are keyed by its host name
+    fn url(&self) -> &str {
+        match self {
+            HostingProvider::GitHub(github) => github.url(),
+            HostingProvider::GitLab { url } | HostingProvider::Gitea { url } => url,
+        }
+    }
+
+    fn api_url(&self) -> String {
+        match self {
+            HostingProvider::GitHub(github) => github.api_url().to_string(),
+            HostingProvider::GitLab { url } => format!("{}/api/v4", url),
+            HostingProvider::Gitea { url } => format!("{}/api/v1", url),
+        }
+    }
+
+    /// Adds the host's authorization header to an API request
+    fn authorize(&self, request: RequestBuilder, token: &str) -> RequestBuilder {
+        let request = request.header("User-Agent", "guidebook-todo");
+        match self {
+            HostingProvider::GitHub(_) => {
+                request.header("Authorization", format!("Bearer {}", token))
+            }
+            HostingProvider::GitLab { .. } => request.header("PRIVATE-TOKEN", token),
+            HostingProvider::Gitea { .. } => {
+                request.header("Authorization", format!("token {}", token))
+            }
+        }
+    }
+
+    /// Uses the token saved for this host while it still works, then the
+    /// `GUIDEBOOK_TODO_TOKEN` environment variable, then asks for a new one.
+    /// New tokens are saved for next time.
+    pub async fn authenticate(&self, client: &Client) -> Result<HostAccount> {
+        let host = url_host(self.url())
+            .ok_or_else(|| anyhow::anyhow!("Invalid {} URL: {}", self.name(), self.url()))?;
+
+        if let Some(token) = load_token(&host) {
+            if let Some(login) = self.login(client, &token).await? {
+                eprintln!("Using saved {} token for {}", self.name(), login);
+                return Ok(HostAccount { token, login });
+            }
+            eprintln!(
+                "The saved {} token was rejected, authorizing again",
+                self.name()
+            );
+            remove_token(&host)?;
+        }
+
+        let token = match std::env::var(TOKEN_ENV_VAR) {
+            Ok(token) if !token.trim().is_empty() => token.trim().to_string(),
+            _ => match self {
+                HostingProvider::GitHub(github) => device_flow(client, github).await?,
+                _ => self.prompt_for_token()?,
+            },
+        };
+        let login = self
+            .login(client, &token)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("{} rejected the access token", self.name()))?;
+        save_token(&host, &token)?;
+        eprintln!(
+            "✓ Saved {} token to {}",
+            self.name(),
+            credentials_path()?.display()
+        );
+        Ok(HostAccount { token, login })
+    }
+
+    fn prompt_for_token(&self) -> Result<String> {
+        let (settings_page, scopes) = match self {
+            HostingProvider::GitLab { url } => (
+                format!("{}/-/user_settings/personal_access_tokens", url),
+                "api",
+            ),
+            _ => (
+                format!("{}/user/settings/applications", self.url()),
+                "write:repository, read:user",
+            ),
+        };
+        if !io::stdin().is_terminal() {
+            anyhow::bail!(
+                "No {} token. Set {} to a personal access token with these scopes: {}",
+                self.name(),
+                TOKEN_ENV_VAR,
+                scopes
+            );
+        }
+
+        eprintln!("Create a personal access token at: {}", settings_page);
+        eprintln!("It needs these scopes: {}", scopes);
+        eprint!("Paste the token: ");
+        io::stderr().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let token = input.trim();
+        if token.is_empty() {
+            anyhow::bail!("No token entered");
+        }
+        Ok(token.to_string())
+    }
+
+    /// The login of the token's user, or `None` if the host rejects the token
+    async fn login(&self, client: &Client, token: &str) -> Result<Option<String>> {
+        let request = client.get(format!("{}/user", self.api_url()));
+        let response = self
+            .authorize(request, token)
+            .send()
+            .await
+            .with_context(|| format!("Failed to look up {} user", self.name()))?;
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Ok(None);
+        }
+        let user: Value = response
+            .error_for_status()
+            .with_context(|| format!("Failed to look up {} user", self.name()))?
+            .json()
+            .await?;
+        // GitLab calls it username, GitHub and Gitea login
+        let login = user["login"]
+            .as_str()
+            .or_else(|| user["username"].as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing login in {} user", self.name()))?;
+        Ok(Some(login.to_string()))
+    }
+
+    /// Clone URL of the user's repository called `name`, if it exists
+    pub async fn find_repository(
+        &self,
+        client: &Client,
+        account: &HostAccount,
+        name: &str,
+    ) -> Result<Option<String>> {
+        let url = match self {
+            HostingProvider::GitLab { .. } => {
+                let path = format!("{}/{}", account.login, name).replace('/', "%2F");
+                format!("{}/projects/{}", self.api_url(), path)
+            }
+            _ => format!("{}/repos/{}/{}", self.api_url(), account.login, name),
+        };
+        let response = self
+            .authorize(client.get(url), &account.token)
+            .send()
+            .await
+            .with_context(|| format!("Failed to look up {} repository", self.name()))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            anyhow::bail!("Failed to look up repository: {}", error_text);
+        }
+
+        let repo_data: Value = response.json().await?;
+        self.clone_url(&repo_data).map(Some)
+    }
+
+    /// Creates an empty repository and returns its clone URL
+    pub async fn create_repository(
+        &self,
+        client: &Client,
+        account: &HostAccount,
+        name: &str,
+        visibility: RepoVisibility,
+    ) -> Result<String> {
+        let private = visibility == RepoVisibility::Private;
+        let description = "Personal guidebook data repository";
+        let (url, body) = match self {
+            HostingProvider::GitLab { .. } => (
+                format!("{}/projects", self.api_url()),
+                json!({
+                    "name": name,
+                    "path": name,
+                    "description": description,
+                    "visibility": if private { "private" } else { "public" },
+                    "initialize_with_readme": false
+                }),
+            ),
+            _ => (
+                format!("{}/user/repos", self.api_url()),
+                json!({
+                    "name": name,
+                    "description": description,
+                    "private": private,
+                    "auto_init": false
+                }),
+            ),
+        };
+
+        let response = self
+            .authorize(client.post(url), &account.token)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to create {} repository", self.name()))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            anyhow::bail!("Failed to create repository: {}", error_text);
+        }
+
+        let repo_data: Value = response.json().await?;
+        self.clone_url(&repo_data)
+    }
+
+    fn clone_url(&self, repo_data: &Value) -> Result<String> {
+        let field = match self {
+            HostingProvider::GitLab { .. } => "http_url_to_repo",
+            _ => "clone_url",
+        };
+        repo_data[field]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Missing {} in {} repository", field, self.name()))
+    }
+}
+
+fn base_url(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
+
+/// Asks the user to authorize guidebook-todo in the browser and waits for the token
+async fn device_flow(client: &Client, github: &GitHubSettings) -> Result<String> {
+    let Some(client_id) = github.client_id.as_deref() else {
+        anyhow::bail!(
+            "No GitHub OAuth client ID configured. Set github.client_id in {} to the client ID of an OAuth app with device flow enabled, or set {} to a personal access token.",
+            settings_path()?.display(),
+            TOKEN_ENV_VAR
+        );
+    };
+
+    // 1. Request device code
+    let device_response = client
+        .post(format!("{}/login/device/code", github.url()))
+        .header("Accept", "application/json")
+        .form(&[("client_id", client_id), ("scope", "repo")])
+        .send()
+        .await
+        .context("Failed to request device code")?;
+
+    let device_data: Value = device_response.json().await?;
+    let verification_uri = device_data["verification_uri"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing verification_uri"))?;
+    let user_code = device_data["user_code"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing user_code"))?;
+
+    // 2. Show user instructions
+    eprintln!("To authorize guidebook-todo, visit: {}", verification_uri);
+    eprintln!("And enter the code: {}", user_code);
+    eprintln!("Waiting for authorization...");
+
+    // 3. Poll for token
+    poll_for_token(client, github, client_id, &device_data).await
+}
+
+/// Polls for the token as RFC 8628 (section 3.5) asks: every `interval`
+/// seconds, five seconds slower for good after each `slow_down`, and no
+/// longer than the device code's `expires_in`
+async fn poll_for_token(
+    client: &Client,
+    github: &GitHubSettings,
+    client_id: &str,
+    device_data: &Value,
+) -> Result<String> {
+    let device_code = device_data["device_code"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing device_code"))?;
+    let mut interval = device_data["interval"].as_u64().unwrap_or(5);
+    let expires_in = device_data["expires_in"].as_u64().unwrap_or(900);
+    let deadline = Instant::now() + Duration::from_secs(expires_in);
+    let expired = || {
+        anyhow::anyhow!(
+            "The GitHub device code expired before it was authorized. Run the command again."
+        )
+    };
+
+    loop {
+        if Instant::now() + Duration::from_secs(interval) > deadline {
+            return Err(expired());
+        }
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+
+        let token_response = client
+            .post(format!("{}/login/oauth/access_token", github.url()))
+            .header("Accept", "application/json")
+            .form(&[
+                ("client_id", client_id),
+                ("device_code", device_code),
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+            ])
+            .send()
+            .await?;
+
+        let token_data: Value = token_response.json().await?;
+
+        if let Some(access_token) = token_data["access_token"].as_str() {
+            return Ok(access_token.to_string());
+        }
+
+        if let Some(error) = token_data["error"].as_str() {
+            match error {
+                "authorization_pending" => continue,
+                "slow_down" => {
+                    // GitHub sends the new interval; the RFC only says to add 5 seconds
+                    interval = token_data["interval"]
+                        .as_u64()
+                        .unwrap_or(0)
+                        .max(interval + 5);
+                    continue;
+                }
+                "expired_token" => return Err(expired()),
+                _ => anyhow::bail!("OAuth error: {}", error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    /// A client that keeps no idle connections, so no pool timers run while
+    /// the clock is paused
+    fn client() -> Client {
+        Client::builder().pool_max_idle_per_host(0).build().unwrap()
+    }
+
+    fn github(server: &Server) -> GitHubSettings {
+        GitHubSettings {
+            client_id: Some("client-id".to_string()),
+            url: Some(server.url()),
+            api_url: Some(server.url()),
+        }
+    }
+
+    fn account() -> HostAccount {
+        HostAccount {
+            token: "secret".to_string(),
+            login: "octocat".to_string(),
+        }
+    }
+
+    async fn token_response(server: &mut Server, body: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/login/oauth/access_token")
+            .match_body(Matcher::UrlEncoded(
+                "device_code".to_string(),
+                "device-code".to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn device_flow_slows_down_for_good_until_the_token_arrives() {
+        let mut server = Server::new_async().await;
+        let device_code = server
+            .mock("POST", "/login/device/code")
+            .match_body(Matcher::UrlEncoded(
+                "client_id".to_string(),
+                "client-id".to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"device_code": "device-code", "user_code": "ABCD-1234",
+                    "verification_uri": "https://github.com/login/device",
+                    "interval": 1, "expires_in": 900}"#,
+            )
+            .create_async()
+            .await;
+        let polls = [
+            token_response(&mut server, r#"{"error": "authorization_pending"}"#).await,
+            token_response(&mut server, r#"{"error": "slow_down"}"#).await,
+            token_response(&mut server, r#"{"error": "authorization_pending"}"#).await,
+            token_response(&mut server, r#"{"access_token": "gho_token"}"#).await,
+        ];
+
+        let start = Instant::now();
+        let token = device_flow(&client(), &github(&server)).await.unwrap();
+
+        assert_eq!(token, "gho_token");
+        device_code.assert_async().await;
+        for poll in &polls {
+            poll.assert_async().await;
+        }
+        // 1s, 1s, then 6s for both polls after the slow_down
+        assert!(start.elapsed() >= Duration::from_secs(14));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn device_flow_stops_polling_when_the_code_expires() {
+        let mut server = Server::new_async().await;
+        let polls = server
+            .mock("POST", "/login/oauth/access_token")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": "authorization_pending"}"#)
+            .expect(3)
+            .create_async()
+            .await;
+        let device_data = json!({"device_code": "device-code", "interval": 1, "expires_in": 3});
+
+        let error = poll_for_token(&client(), &github(&server), "client-id", &device_data)
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("expired"), "{}", error);
+        polls.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn github_finds_an_existing_repository_or_creates_one() {
+        let mut server = Server::new_async().await;
+        let provider = HostingProvider::GitHub(github(&server));
+        let existing = server
+            .mock("GET", "/repos/octocat/todos")
+            .match_header("authorization", "Bearer secret")
+            .with_body(r#"{"clone_url": "https://github.com/octocat/todos.git"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/octocat/new-todos")
+            .with_status(404)
+            .create_async()
+            .await;
+        let created = server
+            .mock("POST", "/user/repos")
+            .match_header("authorization", "Bearer secret")
+            .match_body(Matcher::PartialJson(
+                json!({"name": "new-todos", "private": true}),
+            ))
+            .with_status(201)
+            .with_body(r#"{"clone_url": "https://github.com/octocat/new-todos.git"}"#)
+            .create_async()
+            .await;
+
+        let client = client();
+        let found = provider
+            .find_repository(&client, &account(), "todos")
+            .await
+            .unwrap();
+        assert_eq!(
+            found.as_deref(),
+            Some("https://github.com/octocat/todos.git")
+        );
+        let missing = provider
+            .find_repository(&client, &account(), "new-todos")
+            .await
+            .unwrap();
+        assert_eq!(missing, None);
+        let clone_url = provider
+            .create_repository(&client, &account(), "new-todos", RepoVisibility::Private)
+            .await
+            .unwrap();
+        assert_eq!(clone_url, "https://github.com/octocat/new-todos.git");
+
+        existing.assert_async().await;
+        created.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn gitlab_sends_a_private_token() {
+        let mut server = Server::new_async().await;
+        let provider = HostingProvider::GitLab { url: server.url() };
+        let user = server
+            .mock("GET", "/api/v4/user")
+            .match_header("private-token", "secret")
+            .with_body(r#"{"username": "tanuki"}"#)
+            .create_async()
+            .await;
+        let existing = server
+            .mock("GET", "/api/v4/projects/octocat%2Ftodos")
+            .match_header("private-token", "secret")
+            .with_body(r#"{"http_url_to_repo": "https://gitlab.com/octocat/todos.git"}"#)
+            .create_async()
+            .await;
+        let created = server
+            .mock("POST", "/api/v4/projects")
+            .match_header("private-token", "secret")
+            .match_body(Matcher::PartialJson(
+                json!({"path": "new-todos", "visibility": "public"}),
+            ))
+            .with_status(201)
+            .with_body(r#"{"http_url_to_repo": "https://gitlab.com/octocat/new-todos.git"}"#)
+            .create_async()
+            .await;
+
+        let client = client();
+        let login = provider.login(&client, "secret").await.unwrap();
+        assert_eq!(login.as_deref(), Some("tanuki"));
+        let found = provider
+            .find_repository(&client, &account(), "todos")
+            .await
+            .unwrap();
+        assert_eq!(
+            found.as_deref(),
+            Some("https://gitlab.com/octocat/todos.git")
+        );
+        let clone_url = provider
+            .create_repository(&client, &account(), "new-todos", RepoVisibility::Public)
+            .await
+            .unwrap();
+        assert_eq!(clone_url, "https://gitlab.com/octocat/new-todos.git");
+
+        user.assert_async().await;
+        existing.assert_async().await;
+        created.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn gitea_sends_a_token_header() {
+        let mut server = Server::new_async().await;
+        let provider = HostingProvider::Gitea { url: server.url() };
+        let rejected = server
+            .mock("GET", "/api/v1/user")
+            .match_header("authorization", "token expired")
+            .with_status(401)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/repos/octocat/todos")
+            .match_header("authorization", "token secret")
+            .with_status(404)
+            .create_async()
+            .await;
+        let created = server
+            .mock("POST", "/api/v1/user/repos")
+            .match_header("authorization", "token secret")
+            .match_body(Matcher::PartialJson(
+                json!({"name": "todos", "private": true}),
+            ))
+            .with_status(201)
+            .with_body(r#"{"clone_url": "https://git.example.com/octocat/todos.git"}"#)
+            .create_async()
+            .await;
+
+        let client = client();
+        assert_eq!(provider.login(&client, "expired").await.unwrap(), None);
+        let found = provider
+            .find_repository(&client, &account(), "todos")
+            .await
+            .unwrap();
+        assert_eq!(found, None);
+        let clone_url = provider
+            .create_repository(&client, &account(), "todos", RepoVisibility::Private)
+            .await
+            .unwrap();
+        assert_eq!(clone_url, "https://git.example.com/octocat/todos.git");
+
+        rejected.assert_async().await;
+        created.assert_async().await;
+    }
+}
@@ -1,36 +1,172 @@
 use anyhow::{Context, Result};
+use crate::cli::hosting::HostingProvider;
+use crate::config::{load_settings, save_setting, HostKind};
+use crate::core::{
+    clone_repo, commit_all, commit_file, current_branch, fetch, find_local_todo_file,
+    get_data_dir, head_commit, init_repo, open_backend, open_repo, push_with_token, remote_target, remote_url,
+    LoadStamp, TodoList,
+};
+use git2::Repository;
+use chrono::Local;
 use dirs::home_dir;
 use reqwest::Client;
-use serde_json::{json, Value};
-use std::path::PathBuf;
-use std::io::{self, Write};
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+use std::io::{self, IsTerminal, Write};
 
-pub async fn run_init() -> Result<()> {
-    let data_dir = get_data_dir()?;
+/// How `todo init` sets up the data repository when run from a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InitMode {
+    Local,
+    Hosted(HostKind),
+    Remote(String),
+}
+
+/// Options for `todo init`; without a mode it asks interactively
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    pub mode: Option<InitMode>,
+    pub data_dir: Option<PathBuf>,
+    pub force: bool,
+    pub migrate_local: bool,
+}
+
+/// What `todo init` did, printed as JSON when run with a mode flag
+#[derive(Debug, Serialize)]
+struct InitSummary {
+    /// `created`, `linked` when an existing setup was given a remote, or
+    /// `unchanged` when the data directory was already set up as asked
+    status: &'static str,
+    data_dir: PathBuf,
+    todo_file: PathBuf,
+    remote: Option<String>,
+    remote_url: Option<String>,
+    branch: Option<String>,
+    /// Where a replaced data directory was moved by `--force`
+    backup_dir: Option<PathBuf>,
+    imported: usize,
+    imported_from: Option<PathBuf>,
+}
+
+pub async fn run_init(options: InitOptions) -> Result<()> {
+    let data_dir = match &options.data_dir {
+        Some(path) => absolute_path(path)?,
+        None => get_data_dir()?,
+    };
+    let scripted = options.mode.is_some();
+    
+    let mut backup_dir = None;
+    if options.force && data_dir.exists() {
+        let backup = move_aside(&data_dir)?;
+        eprintln!("Moved existing data directory to {}", backup.display());
+        backup_dir = Some(backup);
+    }
     
-    if data_dir.exists() {
-        println!("guidebook-todo is already initialized.");
+    let mut token = None;
+    let status = if is_initialized(&data_dir) {
+        match &options.mode {
+            Some(mode) => {
+                let (status, host_token) = update_existing_setup(&data_dir, mode).await?;
+                token = host_token;
+                status
+            }
+            None => {
+                println!("guidebook-todo is already initialized.");
+                "unchanged"
+            }
+        }
+    } else {
+        let mode = match options.mode.clone() {
+            Some(mode) => mode,
+            None if !io::stdin().is_terminal() => {
+                anyhow::bail!("No setup mode given. Pass --local, --github or --remote <url> to run 'todo init' without prompts.");
+            }
+            None => match prompt_for_mode()? {
+                Some(mode) => mode,
+                None => {
+                    println!("Setup cancelled.");
+                    return Ok(());
+                }
+            },
+        };
+        
+        match mode {
+            InitMode::Local => setup_local_only(&data_dir)?,
+            InitMode::Hosted(kind) => token = Some(setup_with_host(&data_dir, kind).await?),
+            InitMode::Remote(url) => setup_with_remote(&data_dir, &url, None)?,
+        }
+        "created"
+    };
+    let created = status == "created";
+    
+    let mut imported = 0;
+    let mut imported_from = None;
+    if options.migrate_local {
+        let local_file = find_local_todo_file(&std::env::current_dir()?)
+            .ok_or_else(|| anyhow::anyhow!("No TODO file in the current directory to migrate"))?;
+        let push_import = status != "unchanged";
+        imported = migrate_local_todos(&data_dir, &local_file, push_import, token.as_deref())?;
+        imported_from = Some(local_file);
+    }
+    
+    // Remember a custom location so every other command finds the data there
+    if options.data_dir.is_some() {
+        let value = if data_dir == default_data_dir()? {
+            serde_yaml::Value::Null
+        } else {
+            serde_yaml::Value::String(data_dir.to_string_lossy().to_string())
+        };
+        save_setting("data_dir", value)?;
+    }
+    
+    if !scripted {
+        if created {
+            println!("✓ guidebook-todo is ready to use!");
+        }
         return Ok(());
     }
     
+    let (remote, remote_url, branch) = describe_remote(&data_dir);
+    let summary = InitSummary {
+        status,
+        todo_file: todo_file(&data_dir)?,
+        data_dir,
+        remote,
+        remote_url,
+        branch,
+        backup_dir,
+        imported,
+        imported_from,
+    };
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    Ok(())
+}
+
+/// Asks which setup to run, or `None` to exit without changes
+fn prompt_for_mode() -> Result<Option<InitMode>> {
     println!("Welcome to guidebook-todo!");
     println!("Setting up your TODO management system...");
     println!();
     
     // Check for local TODO files
-    if let Ok(local_file) = find_local_todo_file() {
+    if let Some(local_file) = find_local_todo_file(&std::env::current_dir()?) {
         println!("Found existing TODO file: {}", local_file.display());
         println!("You can continue using this file, or set up the global guidebook directory.");
+        println!("Run 'todo init --migrate-local' to copy its TODOs into the global list.");
         println!();
     }
     
-    println!("guidebook-todo stores its data locally with optional GitHub backup.");
-    println!("If you already have a 'guidebook-data' repository on GitHub, you can");
+    let settings = load_settings()?;
+    let host = HostingProvider::from_settings(settings.host, &settings)?.name();
+    let repo_name = settings.repo_name();
+    
+    println!("guidebook-todo stores its data locally with optional {} backup.", host);
+    println!("If you already have a '{}' repository on {}, you can", repo_name, host);
     println!("link it for automatic synchronization.");
     println!();
     println!("Would you like to:");
-    println!("1. Create a new guidebook-data repository on GitHub");
-    println!("2. Skip GitHub integration (local-only mode)");
+    println!("1. Create or link the {} repository on {}", repo_name, host);
+    println!("2. Skip {} integration (local-only mode)", host);
     println!("3. Exit without making changes");
     println!();
     
@@ -42,205 +178,382 @@ pub async fn run_init() -> Result<()> {
     let choice = input.trim();
     
     match choice {
-        "1" => {
-            setup_with_github(&data_dir).await?;
-        }
-        "2" => {
-            setup_local_only(&data_dir)?;
-        }
-        "3" => {
-            println!("Setup cancelled.");
-            return Ok(());
-        }
-        _ => {
-            anyhow::bail!("Invalid choice. Please run 'todo init' again.");
-        }
+        "1" => Ok(Some(InitMode::Hosted(settings.host))),
+        "2" => Ok(Some(InitMode::Local)),
+        "3" => Ok(None),
+        _ => anyhow::bail!("Invalid choice. Please run 'todo init' again."),
     }
-    
-    println!("✓ guidebook-todo is ready to use!");
-    Ok(())
 }
 
-fn get_data_dir() -> Result<PathBuf> {
+fn default_data_dir() -> Result<PathBuf> {
     let home = home_dir()
         .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
     
     Ok(home.join(".local").join("share").join("guidebook"))
 }
 
-fn find_local_todo_file() -> Result<PathBuf> {
-    let current_dir = std::env::current_dir()?;
-    let local_files = ["TODO.yaml", "TODO.yml", "todo.yaml", "todo.yml"];
-    
-    for filename in &local_files {
-        let path = current_dir.join(filename);
-        if path.exists() {
-            return Ok(path);
+/// `path` made absolute and without `.` or `..`, so the saved setting works from any directory
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    let absolute = std::path::absolute(path)
+        .with_context(|| format!("Invalid data directory: {}", path.display()))?;
+    
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
         }
     }
-    
-    anyhow::bail!("No local TODO file found")
+    Ok(normalized)
+}
+
+fn todo_file(data_dir: &Path) -> Result<PathBuf> {
+    let settings = load_settings()?;
+    Ok(data_dir
+        .join("guidebook-todo")
+        .join(settings.storage.default_file_name()))
+}
+
+/// Other commands create the data directory on demand, so only a TODO file
+/// or a git repository there counts as a previous `todo init`
+fn is_initialized(data_dir: &Path) -> bool {
+    data_dir.join(".git").exists()
+        || todo_file(data_dir).is_ok_and(|todo_file| todo_file.exists())
 }
 
-async fn setup_with_github(data_dir: &PathBuf) -> Result<()> {
-    println!("Setting up GitHub integration...");
+/// Makes sure an existing setup is the one asked for, so running the same
+/// `todo init` again changes nothing. A setup without a remote (say, from
+/// running `todo list` before `todo init`) is linked to the one asked for.
+///
+/// Returns the status for the summary, and the access token when a hosting
+/// service was signed in to.
+async fn update_existing_setup(
+    data_dir: &Path,
+    mode: &InitMode,
+) -> Result<(&'static str, Option<String>)> {
+    let (_, remote_url, _) = describe_remote(data_dir);
+    
+    match (mode, remote_url) {
+        (InitMode::Remote(url), None) => {
+            attach_remote(data_dir, url, None)?;
+            return Ok(("linked", None));
+        }
+        (InitMode::Hosted(kind), None) => {
+            let token = setup_with_host(data_dir, *kind).await?;
+            return Ok(("linked", Some(token)));
+        }
+        (InitMode::Local, _) => {
+            if !data_dir.join(".git").exists() {
+                init_git_repository(data_dir)?;
+            }
+            if !todo_file(data_dir)?.exists() {
+                create_default_todo_file(data_dir)?;
+            }
+        }
+        (InitMode::Hosted(_), Some(_)) => {}
+        (InitMode::Remote(url), Some(existing)) if *url == existing => {}
+        (_, existing) => {
+            let current = match existing {
+                Some(existing) => format!("remote {}", existing),
+                None => "no remote".to_string(),
+            };
+            anyhow::bail!(
+                "{} is already initialized with {}. Pass --force to replace it.",
+                data_dir.display(),
+                current
+            );
+        }
+    }
     
-    let access_token = github_oauth().await?;
-    create_github_repository(&access_token).await?;
+    eprintln!("guidebook-todo is already initialized in {}", data_dir.display());
+    Ok(("unchanged", None))
+}
+
+/// The data repository's remote name, URL and branch, where it has them
+fn describe_remote(data_dir: &Path) -> (Option<String>, Option<String>, Option<String>) {
+    let Ok(repo) = open_repo(data_dir) else {
+        return (None, None, None);
+    };
+    match remote_target(&repo, None, None) {
+        Ok(target) => {
+            let url = remote_url(&repo, &target.remote);
+            (Some(target.remote), url, Some(target.branch))
+        }
+        Err(_) => (None, None, current_branch(&repo).ok().flatten()),
+    }
+}
+
+/// Renames the data directory to `<dir>.backup-<timestamp>`
+fn move_aside(data_dir: &Path) -> Result<PathBuf> {
+    let mut backup = data_dir.as_os_str().to_owned();
+    backup.push(format!(".backup-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    let backup = PathBuf::from(backup);
+    
+    std::fs::rename(data_dir, &backup)
+        .with_context(|| format!("Failed to move {} aside", data_dir.display()))?;
+    Ok(backup)
+}
+
+/// Copies the TODOs in `local_file` into the global list and commits them.
+/// A freshly cloned repository is pushed too; an existing one is left for
+/// `todo sync`, since it may be behind its remote.
+fn migrate_local_todos(
+    data_dir: &Path,
+    local_file: &Path,
+    push_import: bool,
+    token: Option<&str>,
+) -> Result<usize> {
+    let (local, _) = open_backend(local_file.to_path_buf())
+        .load()
+        .with_context(|| format!("Failed to read {}", local_file.display()))?;
+    
+    let todo_file = todo_file(data_dir)?;
+    let backend = open_backend(todo_file.clone());
+    let (mut global, mut stamp) = if todo_file.exists() {
+        backend.load()?
+    } else {
+        (TodoList::default(), LoadStamp::Unloaded)
+    };
+    
+    let imported = global.import(&local);
+    if imported == 0 {
+        eprintln!("✓ TODOs from {} are already in the global list", local_file.display());
+        return Ok(0);
+    }
+    backend.save(&global, &mut stamp)?;
+    eprintln!("✓ Imported {} TODOs from {}", imported, local_file.display());
     
-    // Create local directory structure
-    std::fs::create_dir_all(&data_dir.join("guidebook-todo"))?;
+    if !data_dir.join(".git").exists() {
+        return Ok(imported);
+    }
+    let repo = open_repo(data_dir)?;
+    let relative_path = todo_file.strip_prefix(data_dir)?;
+    let message = format!("Import {} TODOs from {}", imported, local_file.display());
+    commit_file(&repo, relative_path, &message)?;
+    
+    if push_import {
+        if let Ok(target) = remote_target(&repo, None, None) {
+            push_with_token(&repo, &target, token)?;
+            eprintln!("✓ Pushed imported TODOs to {}", target);
+        }
+    }
+    Ok(imported)
+}
+
+/// Creates or links the data repository on a hosting service. Returns the
+/// access token, for pushing anything else during this init.
+async fn setup_with_host(data_dir: &Path, kind: HostKind) -> Result<String> {
+    let settings = load_settings()?;
+    let provider = HostingProvider::from_settings(kind, &settings)?;
+    eprintln!("Setting up {} integration...", provider.name());
+    
+    let repo_name = settings.repo_name();
+    let client = Client::new();
+    let account = provider.authenticate(&client).await?;
     
-    // Initialize git repository
-    init_git_repository(data_dir)?;
+    // Reuse an existing data repository rather than failing to create it again
+    let clone_url = match provider.find_repository(&client, &account, repo_name).await? {
+        Some(clone_url) => {
+            eprintln!("Found existing {} repository", repo_name);
+            clone_url
+        }
+        None => {
+            let clone_url = provider
+                .create_repository(&client, &account, repo_name, settings.repo_visibility)
+                .await?;
+            eprintln!("✓ Created {} repository on {}", repo_name, provider.name());
+            clone_url
+        }
+    };
     
-    // Create default TODO file
-    create_default_todo_file(data_dir)?;
+    if is_initialized(data_dir) {
+        attach_remote(data_dir, &clone_url, Some(&account.token))?;
+    } else {
+        setup_with_remote(data_dir, &clone_url, Some(&account.token))?;
+    }
     
-    println!("✓ GitHub repository created and linked");
-    Ok(())
+    eprintln!("✓ {} repository linked", provider.name());
+    Ok(account.token)
 }
 
-fn setup_local_only(data_dir: &PathBuf) -> Result<()> {
-    println!("Setting up local-only mode...");
-    
-    // Create local directory structure
-    std::fs::create_dir_all(&data_dir.join("guidebook-todo"))?;
-    
-    // Initialize git repository (local only)
-    init_git_repository(data_dir)?;
+/// Clones the data repository from `url` into the data directory. An empty
+/// repository gets the TODO file as its initial commit, pushed to set the
+/// upstream branch.
+fn setup_with_remote(data_dir: &Path, url: &str, token: Option<&str>) -> Result<()> {
+    eprintln!("Cloning {}...", url);
+    let repo = clone_repo(url, data_dir, token)?;
+    
+    if todo_file(data_dir)?.exists() {
+        eprintln!("✓ Cloned existing TODOs from {}", url);
+        return Ok(());
+    }
     
-    // Create default TODO file
     create_default_todo_file(data_dir)?;
+    commit_all(&repo, "Initialize guidebook-todo")?;
     
-    println!("✓ Local setup complete");
+    let target = remote_target(&repo, None, None)?;
+    push_with_token(&repo, &target, token)?;
+    eprintln!("✓ Pushed initial commit to {}", target);
     Ok(())
 }
 
-async fn github_oauth() -> Result<String> {
-    let client_id = "your-github-client-id"; // TODO: Replace with actual client ID
-    let client = Client::new();
+/// Links an existing local setup to `url`: adds it as `origin`, commits the
+/// TODO file and pushes. A remote that already has commits is refused, since
+/// the two histories have nothing in common to merge.
+fn attach_remote(data_dir: &Path, url: &str, token: Option<&str>) -> Result<()> {
+    eprintln!("Linking {} to {}...", data_dir.display(), url);
+    let repo = if data_dir.join(".git").exists() {
+        open_repo(data_dir)?
+    } else {
+        init_repo(data_dir)?
+    };
+    if !todo_file(data_dir)?.exists() {
+        create_default_todo_file(data_dir)?;
+    }
     
-    // 1. Request device code
-    let device_response = client
-        .post("https://github.com/login/device/code")
-        .header("Accept", "application/json")
-        .form(&[("client_id", client_id), ("scope", "repo")])
-        .send()
-        .await
-        .context("Failed to request device code")?;
-    
-    let device_data: Value = device_response.json().await?;
-    let verification_uri = device_data["verification_uri"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Missing verification_uri"))?;
-    let user_code = device_data["user_code"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Missing user_code"))?;
-    
-    // 2. Show user instructions
-    println!("To authorize guidebook-todo, visit: {}", verification_uri);
-    println!("And enter the code: {}", user_code);
-    println!("Waiting for authorization...");
-    
-    // 3. Poll for token
-    let access_token = poll_for_token(&client, client_id, &device_data).await?;
-    
-    Ok(access_token)
-}
-
-async fn poll_for_token(client: &Client, client_id: &str, device_data: &Value) -> Result<String> {
-    let device_code = device_data["device_code"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Missing device_code"))?;
-    let interval = device_data["interval"]
-        .as_u64()
-        .unwrap_or(5);
-    
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
-        
-        let token_response = client
-            .post("https://github.com/login/oauth/access_token")
-            .header("Accept", "application/json")
-            .form(&[
-                ("client_id", client_id),
-                ("device_code", device_code),
-                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
-            ])
-            .send()
-            .await?;
-        
-        let token_data: Value = token_response.json().await?;
-        
-        if let Some(access_token) = token_data["access_token"].as_str() {
-            return Ok(access_token.to_string());
-        }
-        
-        if let Some(error) = token_data["error"].as_str() {
-            match error {
-                "authorization_pending" => continue,
-                "slow_down" => {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    continue;
-                }
-                _ => anyhow::bail!("OAuth error: {}", error),
-            }
-        }
+    repo.remote("origin", url)
+        .with_context(|| format!("Failed to add {} as the origin remote", url))?;
+    // Leave no half-linked remote behind, so init can simply be run again
+    if let Err(e) = push_local_history(&repo, url, token) {
+        let _ = repo.remote_delete("origin");
+        return Err(e);
     }
+    Ok(())
 }
 
-async fn create_github_repository(access_token: &str) -> Result<()> {
-    let client = Client::new();
-    
-    let repo_response = client
-        .post("https://api.github.com/user/repos")
-        .header("Authorization", format!("Bearer {}", access_token))
-        .header("User-Agent", "guidebook-todo")
-        .json(&json!({
-            "name": "guidebook-data",
-            "description": "Personal guidebook data repository",
-            "private": false,
-            "auto_init": true
-        }))
-        .send()
-        .await
-        .context("Failed to create GitHub repository")?;
-    
-    if !repo_response.status().is_success() {
-        let error_text = repo_response.text().await?;
-        anyhow::bail!("Failed to create repository: {}", error_text);
+/// Pushes the local data repository to a newly added, empty `origin`
+fn push_local_history(repo: &Repository, url: &str, token: Option<&str>) -> Result<()> {
+    let target = remote_target(repo, None, None)?;
+    fetch(repo, &target.remote, &target.branch)
+        .with_context(|| format!("Could not reach {}", url))?;
+    if repo
+        .find_reference(&format!("refs/remotes/{}", target))
+        .is_ok()
+    {
+        anyhow::bail!(
+            "{} already has TODOs on '{}'. Run 'todo init --force --remote {}' to clone it instead (the current data directory is moved aside)",
+            url,
+            target.branch,
+            url
+        );
     }
     
+    let message = if head_commit(repo)?.is_some() {
+        format!("Update TODOs before linking {}", url)
+    } else {
+        "Initialize guidebook-todo".to_string()
+    };
+    commit_all(repo, &message)?;
+    push_with_token(repo, &target, token)?;
+    eprintln!("✓ Pushed existing TODOs to {}", target);
     Ok(())
 }
 
-fn init_git_repository(data_dir: &PathBuf) -> Result<()> {
-    use std::process::Command;
+fn setup_local_only(data_dir: &Path) -> Result<()> {
+    eprintln!("Setting up local-only mode...");
     
-    // Initialize git repository
-    let output = Command::new("git")
-        .args(&["init"])
-        .current_dir(data_dir)
-        .output()
-        .context("Failed to initialize git repository")?;
+    // Create local directory structure
+    std::fs::create_dir_all(data_dir.join("guidebook-todo"))?;
     
-    if !output.status.success() {
-        anyhow::bail!("Git init failed: {}", String::from_utf8_lossy(&output.stderr));
-    }
+    // Initialize git repository (local only)
+    init_git_repository(data_dir)?;
+    
+    // Create default TODO file
+    create_default_todo_file(data_dir)?;
     
+    eprintln!("✓ Local setup complete");
     Ok(())
 }
 
-fn create_default_todo_file(data_dir: &PathBuf) -> Result<()> {
-    let todo_file = data_dir.join("guidebook-todo").join("todo.yaml");
-    
-    let default_content = r#"next_id: 1
-todos: []
-"#;
+fn init_git_repository(data_dir: &Path) -> Result<()> {
+    init_repo(data_dir)?;
+    Ok(())
+}
+
+fn create_default_todo_file(data_dir: &Path) -> Result<()> {
+    // Use the configured storage format so the file is the one later commands read
+    let todo_file = todo_file(data_dir)?;
     
-    std::fs::write(&todo_file, default_content)
+    open_backend(todo_file.clone())
+        .save(&TodoList::default(), &mut LoadStamp::Unloaded)
         .with_context(|| format!("Failed to create TODO file: {}", todo_file.display()))?;
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::bare_remote;
+    use tempfile::TempDir;
+
+    fn remote_head(remote: &Repository, branch: &str) -> Option<git2::Oid> {
+        remote
+            .find_reference(&format!("refs/heads/{}", branch))
+            .ok()
+            .and_then(|reference| reference.target())
+    }
+
+    #[test]
+    fn remote_setup_pushes_a_new_list_and_clones_an_existing_one() {
+        let (remote_dir, remote) = bare_remote("main");
+        let url = remote_dir.path().to_string_lossy().to_string();
+        let first = TempDir::new().unwrap();
+        let first_dir = first.path().join("data");
+
+        setup_with_remote(&first_dir, &url, None).unwrap();
+        let pushed = remote_head(&remote, "main").expect("initial commit pushed");
+        assert_eq!(head_commit(&open_repo(&first_dir).unwrap()).unwrap(), Some(pushed));
+        assert!(todo_file(&first_dir).unwrap().exists());
+
+        let second = TempDir::new().unwrap();
+        let second_dir = second.path().join("data");
+        setup_with_remote(&second_dir, &url, None).unwrap();
+        assert_eq!(head_commit(&open_repo(&second_dir).unwrap()).unwrap(), Some(pushed));
+        assert_eq!(remote_head(&remote, "main"), Some(pushed));
+    }
+
+    #[tokio::test]
+    async fn existing_local_setup_is_linked_to_an_empty_remote() {
+        let data = TempDir::new().unwrap();
+        // As left by `todo add` before any `todo init`: a TODO file, no git
+        create_default_todo_file(data.path()).unwrap();
+        assert!(is_initialized(data.path()));
+        let (remote_dir, remote) = bare_remote("main");
+        let url = remote_dir.path().to_string_lossy().to_string();
+        let mode = InitMode::Remote(url.clone());
+
+        let (status, _) = update_existing_setup(data.path(), &mode).await.unwrap();
+        assert_eq!(status, "linked");
+        let repo = open_repo(data.path()).unwrap();
+        assert_eq!(remote_url(&repo, "origin"), Some(url));
+        let branch = current_branch(&repo).unwrap().unwrap();
+        assert_eq!(remote_head(&remote, &branch), head_commit(&repo).unwrap());
+
+        let (status, _) = update_existing_setup(data.path(), &mode).await.unwrap();
+        assert_eq!(status, "unchanged");
+    }
+
+    #[test]
+    fn linking_to_a_remote_with_history_is_refused() {
+        let (remote_dir, _remote) = bare_remote("main");
+        let url = remote_dir.path().to_string_lossy().to_string();
+        let other = TempDir::new().unwrap();
+        setup_with_remote(&other.path().join("data"), &url, None).unwrap();
+
+        let data = TempDir::new().unwrap();
+        let repo = Repository::init_opts(
+            data.path(),
+            git2::RepositoryInitOptions::new().initial_head("main"),
+        )
+        .unwrap();
+        create_default_todo_file(data.path()).unwrap();
+
+        let error = attach_remote(data.path(), &url, None).unwrap_err();
+        assert!(error.to_string().contains("--force"), "{}", error);
+        assert!(repo.find_remote("origin").is_err());
+    }
+}
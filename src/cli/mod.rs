@@ -1,5 +1,6 @@
 pub mod args;
 pub mod commands;
+pub mod hosting;
 pub mod init;
 
 pub use args::*;
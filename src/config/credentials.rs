@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Access tokens saved by `todo init`, keyed by host (with the port, if any)
+type Credentials = BTreeMap<String, String>;
+
+pub fn credentials_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
+
+    Ok(config_dir.join("guidebook-todo").join("credentials.yaml"))
+}
+
+fn load_credentials() -> Result<Credentials> {
+    read_credentials(&credentials_path()?)
+}
+
+fn read_credentials(path: &Path) -> Result<Credentials> {
+    if !path.exists() {
+        return Ok(Credentials::new());
+    }
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read credentials: {}", path.display()))?;
+    if content.trim().is_empty() {
+        return Ok(Credentials::new());
+    }
+
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse credentials: {}", path.display()))
+}
+
+/// The saved token for `host`, if there is one
+pub fn load_token(host: &str) -> Option<String> {
+    load_credentials().ok()?.remove(host)
+}
+
+/// The saved token for the host of a git or API URL
+pub fn token_for_url(url: &str) -> Option<String> {
+    load_token(&url_host(url)?)
+}
+
+/// Saves the token for `host`. The file is readable only by the current user.
+pub fn save_token(host: &str, token: &str) -> Result<()> {
+    let mut credentials = load_credentials()?;
+    credentials.insert(host.to_string(), token.to_string());
+    write_credentials(&credentials_path()?, &credentials)
+}
+
+/// Forgets the token for `host`, e.g. after the server rejected it
+pub fn remove_token(host: &str) -> Result<()> {
+    let mut credentials = load_credentials()?;
+    if credentials.remove(host).is_some() {
+        write_credentials(&credentials_path()?, &credentials)?;
+    }
+    Ok(())
+}
+
+fn write_credentials(path: &Path, credentials: &Credentials) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_yaml::to_string(credentials).context("Failed to serialize credentials")?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files, so also tighten an existing one
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to write credentials: {}", path.display()))?;
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write credentials: {}", path.display()))
+}
+
+/// Host (and port) of an `https://` URL or an scp-style `user@host:path` git URL
+pub fn url_host(url: &str) -> Option<String> {
+    if let Ok(parsed) = reqwest::Url::parse(url) {
+        let host = parsed.host_str()?;
+        return Some(match parsed.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        });
+    }
+
+    let (_, rest) = url.split_once('@')?;
+    let (host, _) = rest.split_once(':')?;
+    Some(host.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn tokens_are_kept_per_host() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("guidebook-todo").join("credentials.yaml");
+        let mut credentials = Credentials::new();
+        credentials.insert(
+            url_host("https://github.com/me/todos.git").unwrap(),
+            "gh".into(),
+        );
+        credentials.insert(
+            url_host("https://git.example.com:3000/me").unwrap(),
+            "gitea".into(),
+        );
+        write_credentials(&path, &credentials).unwrap();
+
+        let saved = read_credentials(&path).unwrap();
+        assert_eq!(saved.get("github.com").map(String::as_str), Some("gh"));
+        assert_eq!(
+            saved.get("git.example.com:3000").map(String::as_str),
+            Some("gitea")
+        );
+        assert_eq!(saved.get("git.example.com"), None);
+    }
+
+    #[test]
+    fn url_hosts_include_the_port() {
+        assert_eq!(
+            url_host("https://gitlab.com/me/todos.git").as_deref(),
+            Some("gitlab.com")
+        );
+        assert_eq!(
+            url_host("http://localhost:8080/api").as_deref(),
+            Some("localhost:8080")
+        );
+        assert_eq!(
+            url_host("git@github.com:me/todos.git").as_deref(),
+            Some("github.com")
+        );
+        assert_eq!(url_host("not a url"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn credentials_are_readable_only_by_the_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("credentials.yaml");
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        write_credentials(&path, &Credentials::new()).unwrap();
+        assert_eq!(mode(&path), 0o600);
+
+        // An existing file left readable by others is tightened on the next save
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        write_credentials(&path, &Credentials::new()).unwrap();
+        assert_eq!(mode(&path), 0o600);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::core::Priority;
 
-// Fixed application defaults (no user configuration files)
+// Built-in defaults. The sync and hosting ones can be overridden in the user's
+// config file (see `Settings` in settings.rs); the rest are fixed
 
 pub const DEFAULT_PRIORITY: Priority = Priority::P2;
 pub const MAX_TITLE_LENGTH: usize = 200;
@@ -15,3 +16,15 @@ pub const USE_COLORS: bool = true;
 
 // Filter defaults
 pub const HIDE_DONE_BY_DEFAULT: bool = true;
+
+// Sync defaults
+pub const DEFAULT_AUTO_SYNC_INTERVAL_SECS: u64 = 300;
+pub const AUTO_SYNC_RETRY_SECS: u64 = 30;
+
+// Hosting defaults
+pub const DEFAULT_DATA_REPO_NAME: &str = "guidebook-data";
+pub const DEFAULT_GITHUB_URL: &str = "https://github.com";
+pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+pub const DEFAULT_GITLAB_URL: &str = "https://gitlab.com";
+/// Personal access token used by `todo init` instead of asking for one
+pub const TOKEN_ENV_VAR: &str = "GUIDEBOOK_TODO_TOKEN";
@@ -1,5 +1,9 @@
 pub mod colors;
+pub mod credentials;
 pub mod defaults;
+pub mod settings;
 
 pub use colors::*;
+pub use credentials::*;
 pub use defaults::*;
+pub use settings::*;
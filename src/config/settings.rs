@@ -0,0 +1,160 @@
+use crate::config::{DEFAULT_DATA_REPO_NAME, DEFAULT_GITHUB_API_URL, DEFAULT_GITHUB_URL};
+use crate::core::BackendKind;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Optional user settings, read from `~/.config/guidebook-todo/config.yaml`.
+///
+/// Every field has a default, so a missing file or missing keys behave like
+/// the fixed defaults in `defaults.rs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Format of the TODO file in the global data directory (yaml, json, sqlite).
+    /// A TODO file in the current directory is always read by its extension.
+    pub storage: BackendKind,
+    /// Remote that `todo push` and `todo sync` use when the current branch
+    /// doesn't track one (default `origin`)
+    pub remote: Option<String>,
+    /// Remote branch to push to and sync with when the current branch doesn't
+    /// track one (default: the current branch's name)
+    pub branch: Option<String>,
+    /// Commit the data repository after every change to the global TODO list
+    pub auto_commit: bool,
+    /// Sync the data repository in the background while the search TUI is open
+    pub auto_sync: bool,
+    /// Seconds between background syncs (default 300)
+    pub auto_sync_interval: Option<u64>,
+    /// Guidebook data directory (default `~/.local/share/guidebook`), set by
+    /// `todo init --data-dir`
+    pub data_dir: Option<PathBuf>,
+    /// Name of the data repository `todo init` creates or links (default `guidebook-data`)
+    pub repo_name: Option<String>,
+    /// Visibility of a data repository created by `todo init` (default private)
+    pub repo_visibility: RepoVisibility,
+    /// Service `todo init` creates or links the data repository on (default github)
+    pub host: HostKind,
+    /// OAuth app and endpoints used by `todo init --github`
+    pub github: GitHubSettings,
+    /// GitLab instance used by `todo init --host gitlab` (default `https://gitlab.com`)
+    pub gitlab: HostSettings,
+    /// Gitea or Forgejo instance used by `todo init --host gitea`
+    pub gitea: HostSettings,
+}
+
+impl Settings {
+    pub fn repo_name(&self) -> &str {
+        self.repo_name.as_deref().unwrap_or(DEFAULT_DATA_REPO_NAME)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoVisibility {
+    #[default]
+    Private,
+    Public,
+}
+
+/// Services that can host the data repository
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKind {
+    #[default]
+    #[value(name = "github")]
+    GitHub,
+    #[value(name = "gitlab")]
+    GitLab,
+    /// Gitea and Forgejo share an API
+    #[serde(alias = "forgejo")]
+    #[value(name = "gitea", alias = "forgejo")]
+    Gitea,
+}
+
+/// A self-hostable service, reached at `url`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostSettings {
+    pub url: Option<String>,
+}
+
+/// GitHub (or GitHub Enterprise) settings. Both URLs can point at a local
+/// mock server to exercise the device flow without github.com.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitHubSettings {
+    /// Client ID of an OAuth app with device flow enabled
+    pub client_id: Option<String>,
+    /// Web URL serving the device flow endpoints (default `https://github.com`)
+    pub url: Option<String>,
+    /// REST API base URL (default `https://api.github.com`)
+    pub api_url: Option<String>,
+}
+
+impl GitHubSettings {
+    pub fn url(&self) -> &str {
+        self.url
+            .as_deref()
+            .unwrap_or(DEFAULT_GITHUB_URL)
+            .trim_end_matches('/')
+    }
+
+    pub fn api_url(&self) -> &str {
+        self.api_url
+            .as_deref()
+            .unwrap_or(DEFAULT_GITHUB_API_URL)
+            .trim_end_matches('/')
+    }
+}
+
+pub fn settings_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
+
+    Ok(config_dir.join("guidebook-todo").join("config.yaml"))
+}
+
+pub fn load_settings() -> Result<Settings> {
+    let path = settings_path()?;
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read settings: {}", path.display()))?;
+    if content.trim().is_empty() {
+        return Ok(Settings::default());
+    }
+
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse settings: {}", path.display()))
+}
+
+/// Sets one key in the settings file, keeping the others as written.
+/// A null value removes the key.
+pub fn save_setting(key: &str, value: serde_yaml::Value) -> Result<()> {
+    let path = settings_path()?;
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    let mut mapping: serde_yaml::Mapping = if content.trim().is_empty() {
+        serde_yaml::Mapping::new()
+    } else {
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse settings: {}", path.display()))?
+    };
+
+    let key = serde_yaml::Value::String(key.to_string());
+    if value.is_null() {
+        mapping.remove(&key);
+    } else {
+        mapping.insert(key, value);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_yaml::to_string(&mapping).context("Failed to serialize settings")?;
+    std::fs::write(&path, content)
+        .with_context(|| format!("Failed to write settings: {}", path.display()))
+}
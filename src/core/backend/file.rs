@@ -0,0 +1,188 @@
+use crate::core::backend::{changed_since_load_error, content_hash, LoadStamp, StorageBackend};
+use crate::core::TodoList;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Serialization format of a whole-file backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Yaml,
+    Json,
+}
+
+impl FileFormat {
+    /// Parses a whole TODO file; an empty file is an empty list
+    pub fn parse(self, content: &str) -> Result<TodoList> {
+        if content.trim().is_empty() {
+            return Ok(TodoList::default());
+        }
+        match self {
+            FileFormat::Yaml => serde_yaml::from_str(content).context("Failed to parse TODO file"),
+            FileFormat::Json => serde_json::from_str(content).context("Failed to parse TODO file"),
+        }
+    }
+
+    pub fn serialize(self, todo_list: &TodoList) -> Result<String> {
+        match self {
+            FileFormat::Yaml => {
+                serde_yaml::to_string(todo_list).context("Failed to serialize TODO list")
+            }
+            FileFormat::Json => {
+                serde_json::to_string_pretty(todo_list).context("Failed to serialize TODO list")
+            }
+        }
+    }
+}
+
+/// Stores the whole list in one human-editable file
+pub struct FileBackend {
+    path: PathBuf,
+    format: FileFormat,
+}
+
+impl FileBackend {
+    pub fn new(path: PathBuf, format: FileFormat) -> Self {
+        FileBackend { path, format }
+    }
+}
+
+impl StorageBackend for FileBackend {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<(TodoList, LoadStamp)> {
+        if !self.path.exists() {
+            // Create empty TODO list if file doesn't exist
+            let todo_list = TodoList::default();
+            let mut stamp = LoadStamp::Unloaded;
+            self.save(&todo_list, &mut stamp)?;
+            return Ok((todo_list, stamp));
+        }
+
+        let content = {
+            let _lock = FileLock::shared(&self.path)?;
+            std::fs::read_to_string(&self.path)
+                .with_context(|| format!("Failed to read TODO file: {}", self.path.display()))?
+        };
+
+        let todo_list = self.format.parse(&content)?;
+        let stamp = LoadStamp::File {
+            content_hash: content_hash(&content),
+        };
+        Ok((todo_list, stamp))
+    }
+
+    fn save(&self, todo_list: &TodoList, stamp: &mut LoadStamp) -> Result<()> {
+        let content = self.format.serialize(todo_list)?;
+
+        // Ensure parent directory exists
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let _lock = FileLock::exclusive(&self.path)?;
+
+        if let LoadStamp::File {
+            content_hash: loaded_hash,
+        } = *stamp
+        {
+            let current = match std::fs::read_to_string(&self.path) {
+                Ok(current) => Some(current),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("Failed to read TODO file: {}", self.path.display())
+                    })
+                }
+            };
+            if current.is_some_and(|current| content_hash(&current) != loaded_hash) {
+                return Err(changed_since_load_error(&self.path));
+            }
+        }
+
+        write_atomically(&self.path, content.as_bytes())?;
+        *stamp = LoadStamp::File {
+            content_hash: content_hash(&content),
+        };
+
+        Ok(())
+    }
+}
+
+/// Writes `content` to a temp file beside `path`, then renames it into place,
+/// so readers and crashes only ever see the old or the new file
+pub(crate) fn write_atomically(path: &Path, content: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid TODO file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = (|| -> Result<()> {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result.with_context(|| format!("Failed to write TODO file: {}", path.display()))
+}
+
+/// Advisory lock shared by every guidebook-todo process using the same TODO file.
+///
+/// The lock file lives in the cache directory rather than next to the data so
+/// it never shows up in the data repository. Released on drop.
+struct FileLock {
+    file: File,
+}
+
+impl FileLock {
+    fn shared(path: &Path) -> Result<Self> {
+        let file = Self::open(path)?;
+        file.lock_shared()
+            .with_context(|| format!("Failed to lock TODO file: {}", path.display()))?;
+        Ok(FileLock { file })
+    }
+
+    fn exclusive(path: &Path) -> Result<Self> {
+        let file = Self::open(path)?;
+        file.lock()
+            .with_context(|| format!("Failed to lock TODO file: {}", path.display()))?;
+        Ok(FileLock { file })
+    }
+
+    fn open(path: &Path) -> Result<File> {
+        let absolute = std::path::absolute(path)?;
+        let key = content_hash(&absolute.to_string_lossy());
+
+        let lock_dir = dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("guidebook-todo")
+            .join("locks");
+        std::fs::create_dir_all(&lock_dir)?;
+
+        let lock_path = lock_dir.join(format!("{:016x}.lock", key));
+        File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open lock file: {}", lock_path.display()))
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
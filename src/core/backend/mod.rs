@@ -0,0 +1,229 @@
+pub mod file;
+pub mod sqlite;
+
+pub use file::*;
+pub use sqlite::*;
+
+use crate::core::TodoList;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Where a `TodoList` is loaded from and saved to
+pub trait StorageBackend {
+    /// Location of the data on disk
+    fn path(&self) -> &Path;
+
+    /// Loads the list, creating empty storage if none exists yet, along with
+    /// the stamp to save it with
+    fn load(&self) -> Result<(TodoList, LoadStamp)>;
+
+    /// Saves the list, failing without writing if the stored data changed
+    /// since `stamp` was taken, then updates `stamp` to match what was written.
+    /// `LoadStamp::Unloaded` replaces whatever is stored.
+    fn save(&self, todo_list: &TodoList, stamp: &mut LoadStamp) -> Result<()>;
+}
+
+/// Storage formats, chosen by the `storage` setting or the data file's extension
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    #[default]
+    Yaml,
+    Json,
+    Sqlite,
+}
+
+impl BackendKind {
+    /// Picks a backend from a file extension, defaulting to YAML
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("json") => BackendKind::Json,
+            Some("db") | Some("sqlite") | Some("sqlite3") => BackendKind::Sqlite,
+            _ => BackendKind::Yaml,
+        }
+    }
+
+    /// File name used in the global data directory
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            BackendKind::Yaml => "todo.yaml",
+            BackendKind::Json => "todo.json",
+            BackendKind::Sqlite => "todo.db",
+        }
+    }
+}
+
+/// What a backend saw of the data a `TodoList` was loaded from, used to
+/// detect changes made by another process before saving
+#[derive(Debug, Clone, Default)]
+pub enum LoadStamp {
+    /// Not loaded from storage (e.g. a brand new list)
+    #[default]
+    Unloaded,
+    /// Hash of the whole file's contents
+    File { content_hash: u64 },
+    /// Database revision and a hash of every row, so saves only write changed TODOs
+    Database {
+        revision: i64,
+        row_hashes: HashMap<u32, u64>,
+    },
+}
+
+/// Opens the backend matching the data file's extension
+pub fn open_backend(path: PathBuf) -> Box<dyn StorageBackend> {
+    match BackendKind::from_path(&path) {
+        BackendKind::Yaml => Box::new(FileBackend::new(path, FileFormat::Yaml)),
+        BackendKind::Json => Box::new(FileBackend::new(path, FileFormat::Json)),
+        BackendKind::Sqlite => Box::new(SqliteBackend::new(path)),
+    }
+}
+
+/// 64-bit FNV-1a hash of `content`. Unlike `DefaultHasher` it is the same in
+/// every build, which matters because lock file names are derived from it and
+/// must agree between processes, even ones running different versions.
+pub(crate) fn content_hash(content: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    content.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Reads a TODO list from the bytes of a data file of `kind`, such as a
+/// committed version of it
+pub fn decode_todos(kind: BackendKind, content: &[u8]) -> Result<TodoList> {
+    let text = || std::str::from_utf8(content).context("TODO file is not valid UTF-8");
+    match kind {
+        BackendKind::Yaml => FileFormat::Yaml.parse(text()?),
+        BackendKind::Json => FileFormat::Json.parse(text()?),
+        BackendKind::Sqlite => decode_database(content),
+    }
+}
+
+/// The bytes `todo_list` is saved as in a data file of `kind`
+pub fn encode_todos(kind: BackendKind, todo_list: &TodoList) -> Result<Vec<u8>> {
+    match kind {
+        BackendKind::Yaml => Ok(FileFormat::Yaml.serialize(todo_list)?.into_bytes()),
+        BackendKind::Json => Ok(FileFormat::Json.serialize(todo_list)?.into_bytes()),
+        BackendKind::Sqlite => encode_database(todo_list),
+    }
+}
+
+pub(crate) fn changed_since_load_error(path: &Path) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{} was changed by another process since it was loaded; nothing was saved. Re-run the command to apply your change to the latest version.",
+        path.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Priority, Status, TimeEntry};
+    use chrono::{Local, NaiveDate};
+    use tempfile::TempDir;
+
+    const FILE_NAMES: [&str; 3] = ["todo.yaml", "todo.json", "todo.db"];
+
+    fn sample_list() -> TodoList {
+        let mut todo_list = TodoList::new();
+        let mut parent = todo_list.create_todo("Plan the trip".to_string());
+        parent.priority = Priority::P1;
+        parent.tags = vec!["travel".to_string(), "summer".to_string()];
+        parent.category = Some("Home".to_string());
+        parent.notes = Some("Book: flights\n\"hotel\" too".to_string());
+        parent.due_date = NaiveDate::from_ymd_opt(2026, 7, 1);
+        let mut child = todo_list.create_todo("Book flights".to_string());
+        child.parent_id = Some(parent.id);
+        child.status = Status::Done;
+        child.finished_date = Some(Local::now());
+        child.time_entries = vec![TimeEntry::start_now()];
+        let mut blocked = todo_list.create_todo("Pack".to_string());
+        blocked.blocked_by = vec![child.id];
+        todo_list.add_todo(parent);
+        todo_list.add_todo(child);
+        todo_list.add_todo(blocked);
+        todo_list
+    }
+
+    #[test]
+    fn every_backend_round_trips_a_list() {
+        let dir = TempDir::new().unwrap();
+        for name in FILE_NAMES {
+            let backend = open_backend(dir.path().join(name));
+            let saved = sample_list();
+            backend.save(&saved, &mut LoadStamp::Unloaded).unwrap();
+
+            let (loaded, mut stamp) = backend.load().unwrap();
+            assert_eq!(loaded.next_id, saved.next_id, "{}", name);
+            assert_eq!(loaded.todos, saved.todos, "{}", name);
+
+            // Saving what was loaded, then loading again, changes nothing
+            backend.save(&loaded, &mut stamp).unwrap();
+            assert_eq!(backend.load().unwrap().0.todos, saved.todos, "{}", name);
+        }
+    }
+
+    #[test]
+    fn a_save_over_a_newer_save_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        for name in FILE_NAMES {
+            let backend = open_backend(dir.path().join(name));
+            backend
+                .save(&sample_list(), &mut LoadStamp::Unloaded)
+                .unwrap();
+
+            let (mut first, mut first_stamp) = backend.load().unwrap();
+            let (mut second, mut second_stamp) = backend.load().unwrap();
+            first.todos[0].title = "Plan the summer trip".to_string();
+            backend.save(&first, &mut first_stamp).unwrap();
+            second.todos[2].title = "Pack light".to_string();
+
+            let error = backend.save(&second, &mut second_stamp).unwrap_err();
+            assert!(
+                error.to_string().contains("changed by another process"),
+                "{}: {}",
+                name,
+                error
+            );
+            let (stored, _) = backend.load().unwrap();
+            assert_eq!(stored.todos[0].title, "Plan the summer trip", "{}", name);
+            assert_eq!(stored.todos[2].title, "Pack", "{}", name);
+
+            // The first list saved again, being current, is accepted
+            first.todos[1].title = "Book cheap flights".to_string();
+            backend.save(&first, &mut first_stamp).unwrap();
+        }
+    }
+
+    #[test]
+    fn content_hashes_are_stable() {
+        // FNV-1a test vectors; lock file names depend on these never changing
+        assert_eq!(content_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(content_hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn lists_round_trip_through_bytes() {
+        for kind in [BackendKind::Yaml, BackendKind::Json, BackendKind::Sqlite] {
+            let saved = sample_list();
+            let bytes = encode_todos(kind, &saved).unwrap();
+            let decoded = decode_todos(kind, &bytes).unwrap();
+            assert_eq!(decoded.next_id, saved.next_id, "{:?}", kind);
+            assert_eq!(decoded.todos, saved.todos, "{:?}", kind);
+        }
+        assert!(decode_todos(BackendKind::Yaml, b"")
+            .unwrap()
+            .todos
+            .is_empty());
+        assert!(decode_todos(BackendKind::Json, b"{ not json").is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::core::backend::{changed_since_load_error, content_hash, LoadStamp, StorageBackend};
+use crate::core::{Todo, TodoList};
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Stores each TODO as its own row so a save only rewrites the TODOs that changed.
+///
+/// Rows hold the same fields as the YAML file, serialized as JSON. A
+/// `revision` counter in the `meta` table is bumped on every save and used to
+/// detect writes from other processes.
+pub struct SqliteBackend {
+    path: PathBuf,
+}
+
+impl SqliteBackend {
+    pub fn new(path: PathBuf) -> Self {
+        SqliteBackend { path }
+    }
+
+    fn connect(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(&self.path)
+            .with_context(|| format!("Failed to open TODO database: {}", self.path.display()))?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+             CREATE TABLE IF NOT EXISTS todos (id INTEGER PRIMARY KEY, data TEXT NOT NULL);",
+        )
+        .context("Failed to initialize TODO database")?;
+        Ok(conn)
+    }
+}
+
+/// Reads a database from its bytes, e.g. a committed version of the data file.
+///
+/// SQLite only opens files, so the bytes go through a temporary directory
+/// that only this user can access and that is removed afterwards.
+pub(crate) fn decode_database(content: &[u8]) -> Result<TodoList> {
+    let (_dir, backend) = scratch_database()?;
+    std::fs::write(backend.path(), content).context("Failed to write a scratch TODO database")?;
+    Ok(backend.load()?.0)
+}
+
+/// The bytes of a database holding `todo_list`, built like [`decode_database`]
+pub(crate) fn encode_database(todo_list: &TodoList) -> Result<Vec<u8>> {
+    let (_dir, backend) = scratch_database()?;
+    backend.save(todo_list, &mut LoadStamp::Unloaded)?;
+    std::fs::read(backend.path()).context("Failed to read a scratch TODO database")
+}
+
+fn scratch_database() -> Result<(TempDir, SqliteBackend)> {
+    let dir = TempDir::new().context("Failed to create a temporary directory")?;
+    let backend = SqliteBackend::new(dir.path().join("todo.db"));
+    Ok((dir, backend))
+}
+
+fn read_meta(conn: &Connection, key: &str) -> Result<Option<i64>> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+    .context("Failed to read TODO database")
+}
+
+fn write_meta(conn: &Connection, key: &str, value: i64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+impl StorageBackend for SqliteBackend {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<(TodoList, LoadStamp)> {
+        let conn = self.connect()?;
+
+        let revision = read_meta(&conn, "revision")?.unwrap_or(0);
+        let next_id = read_meta(&conn, "next_id")?.unwrap_or(1);
+
+        let mut statement = conn.prepare("SELECT id, data FROM todos ORDER BY id")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut todos = Vec::new();
+        let mut row_hashes = HashMap::new();
+        for row in rows {
+            let (id, data) = row.context("Failed to read TODO database")?;
+            let todo: Todo = serde_json::from_str(&data)
+                .with_context(|| format!("Failed to parse TODO #{} in database", id))?;
+            row_hashes.insert(id, content_hash(&data));
+            todos.push(todo);
+        }
+
+        let todo_list = TodoList {
+            next_id: next_id as u32,
+            todos,
+        };
+        let stamp = LoadStamp::Database {
+            revision,
+            row_hashes,
+        };
+        Ok((todo_list, stamp))
+    }
+
+    fn save(&self, todo_list: &TodoList, stamp: &mut LoadStamp) -> Result<()> {
+        let mut conn = self.connect()?;
+        // IMMEDIATE takes the write lock up front so the revision check and the
+        // writes happen atomically with respect to other processes
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let revision = read_meta(&tx, "revision")?.unwrap_or(0);
+        let mut row_hashes = match stamp {
+            LoadStamp::Database {
+                revision: loaded_revision,
+                row_hashes,
+            } => {
+                if *loaded_revision != revision {
+                    return Err(changed_since_load_error(&self.path));
+                }
+                row_hashes.clone()
+            }
+            // Not loaded from this database: replace its contents
+            _ => {
+                tx.execute("DELETE FROM todos", [])?;
+                HashMap::new()
+            }
+        };
+
+        let mut kept = HashMap::with_capacity(todo_list.todos.len());
+        for todo in &todo_list.todos {
+            let data = serde_json::to_string(todo).context("Failed to serialize TODO list")?;
+            let hash = content_hash(&data);
+            if row_hashes.remove(&todo.id) != Some(hash) {
+                tx.execute(
+                    "INSERT INTO todos (id, data) VALUES (?1, ?2)
+                     ON CONFLICT(id) DO UPDATE SET data = excluded.data",
+                    params![todo.id, data],
+                )?;
+            }
+            kept.insert(todo.id, hash);
+        }
+
+        // Whatever is left was deleted from the list
+        for id in row_hashes.keys() {
+            tx.execute("DELETE FROM todos WHERE id = ?1", [id])?;
+        }
+
+        write_meta(&tx, "next_id", todo_list.next_id as i64)?;
+        write_meta(&tx, "revision", revision + 1)?;
+        tx.commit()
+            .with_context(|| format!("Failed to write TODO database: {}", self.path.display()))?;
+
+        *stamp = LoadStamp::Database {
+            revision: revision + 1,
+            row_hashes: kept,
+        };
+        Ok(())
+    }
+}
@@ -1,147 +1,1556 @@
-use crate::core::{Priority, Status, Todo};
+use crate::core::{days_before_today, parse_day_count, parse_weekday, Status, Todo};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use clap::ValueEnum;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-pub fn search_todos<'a>(todos: &'a [Todo], query: &str) -> Vec<&'a Todo> {
-    let query_lower = query.to_lowercase();
+// Fuzzy match scoring: every matched character scores, runs and word starts
+// score extra, and skipped characters cost a little
+const MATCH_SCORE: i32 = 16;
+const CONSECUTIVE_BONUS: i32 = 24;
+const WORD_START_BONUS: i32 = 20;
+const TEXT_START_BONUS: i32 = 12;
+const GAP_PENALTY: i32 = 3;
+const TYPO_PENALTY: i32 = 24;
 
-    todos
+/// Characters a scattered match may skip, per character of the pattern
+const MAX_GAP_PER_CHAR: usize = 2;
+
+// Field weights, so a title match ranks above the same match in the notes
+const TITLE_WEIGHT: i32 = 3;
+const LABEL_WEIGHT: i32 = 2;
+const NOTES_WEIGHT: i32 = 1;
+
+/// How well a search word matches some text, and which characters (by
+/// index) it matched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    pub positions: Vec<usize>,
+}
+
+/// Matches a lowercase `pattern` against `text`, ignoring case.
+///
+/// The pattern's characters must appear in order: either contiguously, or
+/// within a single word from its start without skipping too much (`lgn`
+/// finds "login"). Words of four or more characters also match with a typo
+/// (`lgoin`, `logn`), scoring lower.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    let text: Vec<char> = text.chars().map(lowercase_char).collect();
+
+    let mut best: Option<FuzzyMatch> = None;
+    for start in (0..text.len()).filter(|&i| text[i] == pattern[0]) {
+        let mut positions = vec![start];
+        let mut i = start + 1;
+        while positions.len() < pattern.len() && i < text.len() {
+            if text[i] == pattern[positions.len()] {
+                positions.push(i);
+            }
+            i += 1;
+        }
+        // Starting later can only find fewer characters
+        if positions.len() < pattern.len() {
+            break;
+        }
+
+        let end = positions[positions.len() - 1];
+        let gaps = end + 1 - start - pattern.len();
+        let scattered_ok = is_word_start(&text, start)
+            && gaps <= MAX_GAP_PER_CHAR * pattern.len()
+            && !text[start..=end].iter().any(|c| c.is_whitespace());
+        if gaps > 0 && !scattered_ok {
+            continue;
+        }
+        let score = score_positions(&text, &positions) - GAP_PENALTY * gaps as i32;
+        if best.as_ref().is_none_or(|best| score > best.score) {
+            best = Some(FuzzyMatch { score, positions });
+        }
+    }
+
+    best.or_else(|| typo_match(&pattern, &text))
+}
+
+fn lowercase_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn is_word_start(text: &[char], index: usize) -> bool {
+    index == 0 || !text[index - 1].is_alphanumeric()
+}
+
+fn score_positions(text: &[char], positions: &[usize]) -> i32 {
+    let mut score = 0;
+    for (k, &position) in positions.iter().enumerate() {
+        score += MATCH_SCORE;
+        if k > 0 && position == positions[k - 1] + 1 {
+            score += CONSECUTIVE_BONUS;
+        }
+        if is_word_start(text, position) {
+            score += WORD_START_BONUS;
+        }
+        if position == 0 {
+            score += TEXT_START_BONUS;
+        }
+    }
+    score
+}
+
+/// A word of `text` (or its start, while still being typed) within one or
+/// two edits of `pattern`
+fn typo_match(pattern: &[char], text: &[char]) -> Option<FuzzyMatch> {
+    if pattern.len() < 4 {
+        return None;
+    }
+    let allowed = if pattern.len() >= 8 { 2 } else { 1 };
+
+    let mut best: Option<FuzzyMatch> = None;
+    let mut start = 0;
+    while start < text.len() {
+        if !text[start].is_alphanumeric() {
+            start += 1;
+            continue;
+        }
+        let end = (start..text.len())
+            .find(|&i| !text[i].is_alphanumeric())
+            .unwrap_or(text.len());
+        let word = &text[start..end];
+        let prefix = &word[..word.len().min(pattern.len())];
+
+        for candidate in [word, prefix] {
+            let distance = edit_distance(pattern, candidate);
+            if distance > allowed {
+                continue;
+            }
+            let score = MATCH_SCORE * pattern.len() as i32 - TYPO_PENALTY * distance as i32;
+            if best.as_ref().is_none_or(|best| score > best.score) {
+                best = Some(FuzzyMatch {
+                    score,
+                    positions: (start..start + candidate.len()).collect(),
+                });
+            }
+        }
+        start = end;
+    }
+    best
+}
+
+/// Edits (insertions, deletions, substitutions and swaps of neighbours) to
+/// turn `a` into `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Best weighted score of a search word across a TODO's text fields
+fn text_score(todo: &Todo, word: &str) -> Option<i32> {
+    let weighted = |text: &str, weight: i32| fuzzy_match(word, text).map(|m| m.score * weight);
+    let labels = todo
+        .tags
+        .iter()
+        .map(String::as_str)
+        .chain(todo.category.as_deref())
+        .chain(todo.project.as_deref());
+
+    std::iter::once(weighted(&todo.title, TITLE_WEIGHT))
+        .chain(labels.map(|label| weighted(label, LABEL_WEIGHT)))
+        .chain(
+            todo.notes
+                .as_deref()
+                .map(|notes| weighted(notes, NOTES_WEIGHT)),
+        )
+        .flatten()
+        .max()
+}
+
+/// Score of a quoted phrase, which must appear as written
+fn phrase_score(todo: &Todo, phrase: &str) -> Option<i32> {
+    let score = MATCH_SCORE * phrase.chars().count() as i32;
+    let labels = todo
+        .tags
         .iter()
-        .filter(|todo| {
-            // Exclude archived TODOs by default
-            if todo.status == Status::Archived {
-                return false;
+        .map(String::as_str)
+        .chain(todo.category.as_deref())
+        .chain(todo.project.as_deref());
+
+    std::iter::once((todo.title.as_str(), TITLE_WEIGHT))
+        .chain(labels.map(|label| (label, LABEL_WEIGHT)))
+        .chain(todo.notes.as_deref().map(|notes| (notes, NOTES_WEIGHT)))
+        .filter(|(text, _)| contains_lowercase(text, phrase))
+        .map(|(_, weight)| score * weight)
+        .max()
+}
+
+/// Orders TODOs by how well they match the query's words and phrases, best
+/// first, with higher priority breaking ties. A query without text keeps the
+/// order given.
+pub fn rank_by_query(todos: &mut [&Todo], query: &Query) {
+    if !query.has_text() {
+        return;
+    }
+    todos.sort_by_cached_key(|todo| (std::cmp::Reverse(query.score(todo)), todo.priority_value()));
+}
+
+/// A parsed search query, e.g. `#bug @work p0..p1 (!todo OR !inprogress) NOT title:"flaky test"`.
+///
+/// Terms next to each other must all match; `OR` binds looser than that, so
+/// `a b OR c` means `(a AND b) OR c`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// The empty query, matching everything
+    All,
+    Term(Term),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+/// One condition on a TODO
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// `#tag` or `tag:name`
+    Tag(String),
+    /// `@category` or `category:name`
+    Category(String),
+    /// `!status` or `status:name`
+    Status(Status),
+    /// `p1`, or `p0..p2` for a range, as priority values
+    Priority(u8, u8),
+    /// `title:`, `notes:` or `project:` followed by text the field contains
+    Field(Field, String),
+    /// A word fuzzily matching the title, notes, tags, category or project
+    Text(String),
+    /// A quoted phrase found as written in one of those fields
+    Phrase(String),
+    /// `created:`, `done:` or `due:` followed by a date, period or look-back,
+    /// optionally compared with `<`, `<=`, `>` or `>=`; `age:` becomes a
+    /// `created:` term
+    Date(DateField, DateFilter),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Title,
+    Notes,
+    Project,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+    Created,
+    /// When the TODO was finished, as `done:` or `finished:`
+    Finished,
+    Due,
+}
+
+impl DateField {
+    fn of(self, todo: &Todo) -> Option<NaiveDate> {
+        match self {
+            DateField::Created => Some(todo.created_date.date_naive()),
+            DateField::Finished => todo.finished_date.map(|date| date.date_naive()),
+            DateField::Due => todo.due_date,
+        }
+    }
+}
+
+/// How a date compares with a range of days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Before,
+    OnOrBefore,
+    Within,
+    OnOrAfter,
+    After,
+}
+
+/// Dates compared with the days from `first` to `last`, inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateFilter {
+    pub comparison: Comparison,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+impl DateFilter {
+    /// A date, period or look-back, optionally after a comparison, such as
+    /// `2026-09-01`, `>=last-week` or `<30d`
+    pub fn parse(value: &str) -> Result<DateFilter, String> {
+        let (comparison, span) = split_comparison(value);
+        DateFilter::compare(comparison, span)
+    }
+
+    /// Dates compared with `span`. A look-back such as `7d` covers the days
+    /// from then until today, but compared with `<`, `<=`, `>` or `>=` it is
+    /// the single day it reaches back to: `>7d` is anything since that day.
+    pub fn compare(comparison: Comparison, span: &str) -> Result<DateFilter, String> {
+        let (first, mut last) = parse_date_span(span)?;
+        if comparison != Comparison::Within && parse_day_count(&span.to_lowercase()).is_some() {
+            last = first;
+        }
+        Ok(DateFilter {
+            comparison,
+            first,
+            last,
+        })
+    }
+
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        match self.comparison {
+            Comparison::Before => date < self.first,
+            Comparison::OnOrBefore => date <= self.last,
+            Comparison::Within => self.first <= date && date <= self.last,
+            Comparison::OnOrAfter => date >= self.first,
+            Comparison::After => date > self.last,
+        }
+    }
+}
+
+fn split_comparison(value: &str) -> (Comparison, &str) {
+    if let Some(rest) = value.strip_prefix(">=") {
+        (Comparison::OnOrAfter, rest)
+    } else if let Some(rest) = value.strip_prefix("<=") {
+        (Comparison::OnOrBefore, rest)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (Comparison::After, rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (Comparison::Before, rest)
+    } else {
+        (Comparison::Within, value.strip_prefix('=').unwrap_or(value))
+    }
+}
+
+/// First and last day of `YYYY-MM-DD`, `today`, `yesterday`, a weekday (the
+/// most recent, today included), `this-week`, `last-week`, `this-month`,
+/// `last-month`, or a look-back such as `7d` / `2w` (from then until today)
+pub fn parse_date_span(span: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let today = Local::now().date_naive();
+    let lower = span.to_lowercase();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let first_of_month = today.with_day(1).unwrap_or(today);
+
+    let span = match lower.as_str() {
+        "today" => (today, today),
+        "yesterday" => (today - Duration::days(1), today - Duration::days(1)),
+        "this-week" => (monday, monday + Duration::days(6)),
+        "last-week" => (monday - Duration::weeks(1), monday - Duration::days(1)),
+        "this-month" => (first_of_month, last_of_month(first_of_month)),
+        "last-month" => {
+            let last = first_of_month - Duration::days(1);
+            (last.with_day(1).unwrap_or(last), last)
+        }
+        _ => {
+            if let Some(weekday) = parse_weekday(&lower) {
+                let back = (today.weekday().num_days_from_monday() as i64
+                    - weekday.num_days_from_monday() as i64)
+                    .rem_euclid(7);
+                let day = today - Duration::days(back);
+                (day, day)
+            } else if let Some(days) = parse_day_count(&lower) {
+                (days_ago(days, span)?, today)
+            } else {
+                let day = NaiveDate::parse_from_str(&lower, "%Y-%m-%d").map_err(|_| {
+                    format!(
+                        "Unknown date '{}'. Use YYYY-MM-DD, today, yesterday, a weekday, this-week, last-week, this-month, last-month, or 7d/2w",
+                        span
+                    )
+                })?;
+                (day, day)
             }
+        }
+    };
+    Ok(span)
+}
 
-            // Search in title
-            if todo.title.to_lowercase().contains(&query_lower) {
-                return true;
+/// The date `days` before today, or an error naming `text` if that is
+/// before the earliest date there is
+fn days_ago(days: u64, text: &str) -> Result<NaiveDate, String> {
+    days_before_today(days).ok_or_else(|| format!("'{}' reaches too far back", text))
+}
+
+fn last_of_month(first: NaiveDate) -> NaiveDate {
+    let next = if first.month() == 12 {
+        NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+    };
+    next.map_or(first, |next| next - Duration::days(1))
+}
+
+/// `age:>30d` (created more than 30 days ago) as a filter on the created date
+fn parse_age(value: &str) -> Result<DateFilter, String> {
+    let (comparison, age) = split_comparison(value);
+    let days = parse_day_count(&age.to_lowercase()).ok_or_else(|| {
+        format!(
+            "Unknown age '{}'. Use a number of days or weeks such as 30d or 2w",
+            age
+        )
+    })?;
+    let day = days_ago(days, age)?;
+    // An older TODO was created earlier, so the comparison flips
+    let comparison = match comparison {
+        Comparison::Before => Comparison::After,
+        Comparison::OnOrBefore => Comparison::OnOrAfter,
+        Comparison::Within => Comparison::Within,
+        Comparison::OnOrAfter => Comparison::OnOrBefore,
+        Comparison::After => Comparison::Before,
+    };
+    Ok(DateFilter {
+        comparison,
+        first: day,
+        last: day,
+    })
+}
+
+/// Why a query could not be parsed, with the column (from 1) it refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub message: String,
+    pub column: usize,
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (column {})", self.message, self.column)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Field names accepted before a `:`
+const QUERY_FIELDS: &str =
+    "title, notes, project, tag, category, status, priority, created, done, finished, due, age";
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, QueryError> {
+        let tokens = tokenize(input)?;
+        let mut parser = QueryParser {
+            tokens,
+            position: 0,
+            end: input.chars().count() + 1,
+        };
+        if parser.peek().is_none() {
+            return Ok(Query::All);
+        }
+
+        let query = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(QueryError {
+                message: "Unexpected ')' without a matching '('".to_string(),
+                column: token.column,
+            });
+        }
+        Ok(query)
+    }
+
+    pub fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Query::All => true,
+            Query::Term(term) => term.matches(todo),
+            Query::Not(query) => !query.matches(todo),
+            Query::And(queries) => queries.iter().all(|query| query.matches(todo)),
+            Query::Or(queries) => queries.iter().any(|query| query.matches(todo)),
+        }
+    }
+
+    /// Whether the query asks about status or when TODOs were finished, in
+    /// which case callers should not also hide done or archived TODOs by default
+    pub fn mentions_status(&self) -> bool {
+        match self {
+            Query::All => false,
+            Query::Term(term) => {
+                matches!(term, Term::Status(_) | Term::Date(DateField::Finished, _))
             }
+            Query::Not(query) => query.mentions_status(),
+            Query::And(queries) | Query::Or(queries) => queries.iter().any(Query::mentions_status),
+        }
+    }
 
-            // Search in notes
-            if let Some(ref notes) = todo.notes {
-                if notes.to_lowercase().contains(&query_lower) {
-                    return true;
+    /// Whether the query searches for words or phrases, so results can be ranked
+    pub fn has_text(&self) -> bool {
+        match self {
+            Query::All | Query::Not(_) => false,
+            Query::Term(term) => matches!(term, Term::Text(_) | Term::Phrase(_)),
+            Query::And(queries) | Query::Or(queries) => queries.iter().any(Query::has_text),
+        }
+    }
+
+    /// How well a matching TODO matches the query's words and phrases
+    pub fn score(&self, todo: &Todo) -> i32 {
+        match self {
+            Query::All | Query::Not(_) => 0,
+            Query::Term(Term::Text(word)) => text_score(todo, word).unwrap_or(0),
+            Query::Term(Term::Phrase(phrase)) => phrase_score(todo, phrase).unwrap_or(0),
+            Query::Term(_) => 0,
+            Query::And(queries) => queries.iter().map(|query| query.score(todo)).sum(),
+            Query::Or(queries) => queries
+                .iter()
+                .filter(|query| query.matches(todo))
+                .map(|query| query.score(todo))
+                .max()
+                .unwrap_or(0),
+        }
+    }
+
+    /// Character positions in `title` matched by the query's words, phrases
+    /// and `title:` terms, for highlighting
+    pub fn title_highlights(&self, title: &str) -> Vec<usize> {
+        let mut positions = Vec::new();
+        self.collect_title_highlights(title, &mut positions);
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+
+    fn collect_title_highlights(&self, title: &str, positions: &mut Vec<usize>) {
+        match self {
+            Query::All | Query::Not(_) => {}
+            Query::Term(Term::Text(word)) => {
+                if let Some(found) = fuzzy_match(word, title) {
+                    positions.extend(found.positions);
+                }
+            }
+            Query::Term(Term::Phrase(text)) | Query::Term(Term::Field(Field::Title, text)) => {
+                positions.extend(substring_positions(title, text));
+            }
+            Query::Term(_) => {}
+            Query::And(queries) | Query::Or(queries) => {
+                for query in queries {
+                    query.collect_title_highlights(title, positions);
                 }
             }
+        }
+    }
+}
 
-            // Search in tags
-            if todo
-                .tags
-                .iter()
-                .any(|tag| tag.to_lowercase().contains(&query_lower))
+/// Character positions of the first case-insensitive occurrence of `needle`
+fn substring_positions(haystack: &str, needle: &str) -> Vec<usize> {
+    let haystack: Vec<char> = haystack.chars().map(lowercase_char).collect();
+    let needle: Vec<char> = needle.chars().collect();
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return Vec::new();
+    }
+    (0..=haystack.len() - needle.len())
+        .find(|&start| haystack[start..start + needle.len()] == needle[..])
+        .map(|start| (start..start + needle.len()).collect())
+        .unwrap_or_default()
+}
+
+impl Term {
+    pub fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Term::Tag(tag) => todo.tags.iter().any(|t| t.to_lowercase() == *tag),
+            Term::Category(category) => todo
+                .category
+                .as_ref()
+                .is_some_and(|cat| cat.to_lowercase() == *category),
+            Term::Status(status) => todo.status == *status,
+            Term::Priority(min, max) => (*min..=*max).contains(&todo.priority_value()),
+            Term::Field(Field::Title, text) => contains_lowercase(&todo.title, text),
+            Term::Field(Field::Notes, text) => todo
+                .notes
+                .as_ref()
+                .is_some_and(|notes| contains_lowercase(notes, text)),
+            Term::Field(Field::Project, text) => todo
+                .project
+                .as_ref()
+                .is_some_and(|project| contains_lowercase(project, text)),
+            Term::Text(word) => text_score(todo, word).is_some(),
+            Term::Phrase(phrase) => phrase_score(todo, phrase).is_some(),
+            Term::Date(field, filter) => field.of(todo).is_some_and(|date| filter.matches(date)),
+        }
+    }
+}
+
+fn contains_lowercase(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(needle)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Open,
+    Close,
+    /// A run of text; `quoted` when it started with `"`, so it is never an
+    /// operator or special syntax
+    Word {
+        text: String,
+        quoted: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+impl Token {
+    fn is_operator(&self, operator: &str) -> bool {
+        matches!(&self.kind, TokenKind::Word { text, quoted: false } if text == operator)
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let column = i + 1;
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token {
+                    kind: TokenKind::Open,
+                    column,
+                });
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token {
+                    kind: TokenKind::Close,
+                    column,
+                });
+                i += 1;
+            }
+            _ => {
+                // Quotes may also start partway through, as in title:"fix login"
+                let quoted = chars[i] == '"';
+                let mut text = String::new();
+                while i < chars.len() && !chars[i].is_whitespace() && !"()".contains(chars[i]) {
+                    if chars[i] == '"' {
+                        let opening = i + 1;
+                        i += 1;
+                        while i < chars.len() && chars[i] != '"' {
+                            text.push(chars[i]);
+                            i += 1;
+                        }
+                        if i == chars.len() {
+                            return Err(QueryError {
+                                message: "Missing closing '\"' for the quote opened here"
+                                    .to_string(),
+                                column: opening,
+                            });
+                        }
+                    } else {
+                        text.push(chars[i]);
+                    }
+                    i += 1;
+                }
+                tokens.push(Token {
+                    kind: TokenKind::Word { text, quoted },
+                    column,
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct QueryParser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// Column just past the input, for errors at the end
+    end: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Errors unless a term follows `operator`, e.g. for `a OR` or `NOT )`
+    fn expect_term_after(&self, operator: &str, column: usize) -> Result<(), QueryError> {
+        match self.peek() {
+            None => Err(QueryError {
+                message: format!("Expected a search term after {}", operator),
+                column: self.end,
+            }),
+            Some(token)
+                if token.kind == TokenKind::Close
+                    || token.is_operator("AND")
+                    || token.is_operator("OR") =>
             {
-                return true;
+                Err(QueryError {
+                    message: format!("Expected a search term after {}", operator),
+                    column,
+                })
             }
+            Some(_) => Ok(()),
+        }
+    }
 
-            // Search in category
-            if let Some(ref category) = todo.category {
-                if category.to_lowercase().contains(&query_lower) {
-                    return true;
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut queries = vec![self.parse_and()?];
+        while let Some(token) = self.peek().filter(|token| token.is_operator("OR")) {
+            let column = token.column;
+            self.position += 1;
+            self.expect_term_after("OR", column)?;
+            queries.push(self.parse_and()?);
+        }
+        Ok(if queries.len() == 1 {
+            queries.remove(0)
+        } else {
+            Query::Or(queries)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut queries = vec![self.parse_unary()?];
+        while let Some(token) = self.peek() {
+            if token.kind == TokenKind::Close || token.is_operator("OR") {
+                break;
+            }
+            if token.is_operator("AND") {
+                let column = token.column;
+                self.position += 1;
+                self.expect_term_after("AND", column)?;
+            }
+            queries.push(self.parse_unary()?);
+        }
+        Ok(if queries.len() == 1 {
+            queries.remove(0)
+        } else {
+            Query::And(queries)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, QueryError> {
+        let Some(token) = self.next() else {
+            return Err(QueryError {
+                message: "Expected a search term".to_string(),
+                column: self.end,
+            });
+        };
+
+        if token.is_operator("NOT") || token.is_operator("-") {
+            self.expect_term_after("NOT", token.column)?;
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+        if token.is_operator("AND") || token.is_operator("OR") {
+            return Err(QueryError {
+                message: "Expected a search term before this operator".to_string(),
+                column: token.column,
+            });
+        }
+
+        match token.kind {
+            TokenKind::Open => {
+                if self
+                    .peek()
+                    .is_some_and(|next| next.kind == TokenKind::Close)
+                {
+                    return Err(QueryError {
+                        message: "Empty parentheses".to_string(),
+                        column: token.column,
+                    });
+                }
+                let query = self.parse_or()?;
+                match self.next() {
+                    Some(Token {
+                        kind: TokenKind::Close,
+                        ..
+                    }) => Ok(query),
+                    _ => Err(QueryError {
+                        message: "Missing ')' for the '(' opened here".to_string(),
+                        column: token.column,
+                    }),
                 }
             }
+            TokenKind::Close => Err(QueryError {
+                message: "Unexpected ')' without a matching '('".to_string(),
+                column: token.column,
+            }),
+            TokenKind::Word { text, quoted } => parse_word(&text, quoted, token.column),
+        }
+    }
+}
 
-            // Search in project
-            if let Some(ref project) = todo.project {
-                if project.to_lowercase().contains(&query_lower) {
-                    return true;
+/// Turns one word of a query into a term, or a negated one for `-word`
+fn parse_word(text: &str, quoted: bool, column: usize) -> Result<Query, QueryError> {
+    let error = |message: String| QueryError { message, column };
+    if quoted {
+        return Ok(Query::Term(Term::Phrase(text.to_lowercase())));
+    }
+
+    if let Some(rest) = text.strip_prefix('-').filter(|rest| !rest.is_empty()) {
+        let term = parse_word(rest, false, column + 1)?;
+        return Ok(Query::Not(Box::new(term)));
+    }
+
+    let term = if let Some(tag) = text.strip_prefix('#') {
+        Term::Tag(non_empty(tag, "a tag name after '#'", column)?)
+    } else if let Some(category) = text.strip_prefix('@') {
+        Term::Category(non_empty(category, "a category after '@'", column)?)
+    } else if let Some(status) = text.strip_prefix('!') {
+        Term::Status(parse_query_status(status).map_err(error)?)
+    } else if let Some(priority) = parse_query_priority(text) {
+        let (min, max) = priority.map_err(error)?;
+        Term::Priority(min, max)
+    } else if let Some((field, value)) = split_field(text) {
+        let value = non_empty(value, &format!("a value after '{}:'", field), column)?;
+        match field.to_lowercase().as_str() {
+            "title" => Term::Field(Field::Title, value),
+            "notes" => Term::Field(Field::Notes, value),
+            "project" => Term::Field(Field::Project, value),
+            "tag" => Term::Tag(value),
+            "category" => Term::Category(value),
+            "status" => Term::Status(parse_query_status(&value).map_err(error)?),
+            "created" => Term::Date(
+                DateField::Created,
+                DateFilter::parse(&value).map_err(error)?,
+            ),
+            "done" | "finished" => Term::Date(
+                DateField::Finished,
+                DateFilter::parse(&value).map_err(error)?,
+            ),
+            "due" => Term::Date(DateField::Due, DateFilter::parse(&value).map_err(error)?),
+            "age" => Term::Date(DateField::Created, parse_age(&value).map_err(error)?),
+            "priority" => match parse_query_priority(&value) {
+                Some(priority) => {
+                    let (min, max) = priority.map_err(error)?;
+                    Term::Priority(min, max)
+                }
+                None => {
+                    return Err(error(format!(
+                        "Unknown priority '{}'. Use p0-p5 or a range such as p0..p2",
+                        value
+                    )))
                 }
+            },
+            other => {
+                return Err(error(format!(
+                    "Unknown field '{}:'. Fields: {} (quote the word to search for it as text)",
+                    other, QUERY_FIELDS
+                )))
             }
+        }
+    } else {
+        Term::Text(text.to_lowercase())
+    };
+    Ok(Query::Term(term))
+}
 
-            false
-        })
-        .collect()
+fn non_empty(value: &str, what: &str, column: usize) -> Result<String, QueryError> {
+    if value.is_empty() {
+        return Err(QueryError {
+            message: format!("Expected {}", what),
+            column,
+        });
+    }
+    Ok(value.to_lowercase())
 }
 
-pub fn filter_by_special_syntax<'a>(todos: &'a [Todo], query: &str) -> Vec<&'a Todo> {
-    if query.starts_with('#') {
-        // Tag filter: #bug
-        let tag = query[1..].to_lowercase();
-        return todos
-            .iter()
-            .filter(|todo| todo.tags.iter().any(|t| t == &tag))
-            .collect();
+fn parse_query_status(status: &str) -> Result<Status, String> {
+    crate::core::parse_status(status).map_err(|_| {
+        format!(
+            "Unknown status '{}'. Use todo, inprogress, done or archived",
+            status
+        )
+    })
+}
+
+/// `p1` or `p0..p2` as a range of priority values; `None` if the word is not a priority
+fn parse_query_priority(text: &str) -> Option<Result<(u8, u8), String>> {
+    let lower = text.to_lowercase();
+    let value = |p: &str| -> Option<u8> {
+        let digit = p.strip_prefix('p')?;
+        if digit.len() == 1 {
+            digit.parse().ok()
+        } else {
+            None
+        }
+    };
+
+    match lower.split_once("..") {
+        Some((from, to)) => {
+            let (from, to) = (value(from)?, value(to)?);
+            if from > 5 || to > 5 {
+                return Some(Err(format!(
+                    "Unknown priority in '{}'. Priorities run from p0 to p5",
+                    text
+                )));
+            }
+            Some(Ok((from.min(to), from.max(to))))
+        }
+        None => value(&lower).filter(|p| *p <= 5).map(|p| Ok((p, p))),
     }
+}
 
-    if query.starts_with('@') {
-        // Category filter: @work
-        let category = query[1..].to_lowercase();
-        return todos
-            .iter()
-            .filter(|todo| {
-                todo.category
-                    .as_ref()
-                    .map_or(false, |cat| cat.to_lowercase() == category)
-            })
-            .collect();
+/// `field:value`, where the field is a plain word; URLs such as
+/// `https://...` are left as text
+fn split_field(text: &str) -> Option<(&str, &str)> {
+    let (field, value) = text.split_once(':')?;
+    if field.is_empty()
+        || !field.chars().all(|c| c.is_ascii_alphabetic())
+        || value.starts_with("//")
+    {
+        return None;
     }
+    Some((field, value))
+}
 
-    if query.starts_with('!') {
-        // Status filter: !done
-        let status_str = query[1..].to_lowercase();
-        if let Ok(status) = parse_status_for_filter(&status_str) {
-            return todos.iter().filter(|todo| todo.status == status).collect();
+/// What TODOs can be ordered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Id,
+    Priority,
+    Status,
+    Created,
+    Finished,
+    Due,
+    Title,
+    Category,
+    Project,
+}
+
+/// Field names accepted in a sort order
+const SORT_FIELDS: &str = "id, priority, status, created, finished, due, title, category, project";
+
+/// One key of a sort order; `-created` sorts newest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn ascending(field: SortField) -> SortKey {
+        SortKey {
+            field,
+            descending: false,
         }
     }
 
-    if query.starts_with('p') && query.len() == 2 {
-        // Priority filter: p0, p1, etc.
-        if let Ok(priority) = parse_priority_for_filter(query) {
-            return todos
-                .iter()
-                .filter(|todo| todo.priority == priority)
-                .collect();
+    pub fn descending(field: SortField) -> SortKey {
+        SortKey {
+            field,
+            descending: true,
         }
     }
+}
 
-    // Fallback to regular search
-    search_todos(todos, query)
+/// Parses a sort order such as `priority,-created,title`: fields compared in
+/// turn, each reversed by a leading `-`
+pub fn parse_sort(spec: &str) -> Result<Vec<SortKey>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            let (name, descending) = match key.strip_prefix('-') {
+                Some(name) => (name, true),
+                None => (key.strip_prefix('+').unwrap_or(key), false),
+            };
+            let field = match name.to_lowercase().as_str() {
+                "id" => SortField::Id,
+                "priority" => SortField::Priority,
+                "status" => SortField::Status,
+                "created" => SortField::Created,
+                "finished" | "done" => SortField::Finished,
+                "due" => SortField::Due,
+                "title" => SortField::Title,
+                "category" => SortField::Category,
+                "project" => SortField::Project,
+                _ => {
+                    return Err(format!(
+                        "Unknown sort field '{}'. Fields: {}",
+                        name, SORT_FIELDS
+                    ))
+                }
+            };
+            Ok(SortKey { field, descending })
+        })
+        .collect()
+}
+
+/// Sorts TODOs by each key in turn, keeping their order where all keys tie.
+/// TODOs without the field (no due date, say) come last either way.
+pub fn sort_todos(todos: &mut [&Todo], keys: &[SortKey]) {
+    todos.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| compare_field(a, b, *key))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
 }
 
-fn parse_status_for_filter(status_str: &str) -> Result<Status, ()> {
-    match status_str {
-        "todo" => Ok(Status::Todo),
-        "inprogress" | "in-progress" | "in_progress" => Ok(Status::InProgress),
-        "done" => Ok(Status::Done),
-        "archived" => Ok(Status::Archived),
-        _ => Err(()),
+fn compare_field(a: &Todo, b: &Todo, key: SortKey) -> Ordering {
+    fn optional<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) if descending => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+    let lowercase = |text: Option<&String>| text.map(|text| text.to_lowercase());
+
+    match key.field {
+        SortField::Id => optional(Some(a.id), Some(b.id), key.descending),
+        SortField::Priority => optional(
+            Some(a.priority_value()),
+            Some(b.priority_value()),
+            key.descending,
+        ),
+        SortField::Status => optional(
+            Some(status_rank(&a.status)),
+            Some(status_rank(&b.status)),
+            key.descending,
+        ),
+        SortField::Created => optional(Some(a.created_date), Some(b.created_date), key.descending),
+        SortField::Finished => optional(a.finished_date, b.finished_date, key.descending),
+        SortField::Due => optional(
+            a.due_date.map(|date| (date, a.due_time)),
+            b.due_date.map(|date| (date, b.due_time)),
+            key.descending,
+        ),
+        SortField::Title => optional(
+            Some(a.title.to_lowercase()),
+            Some(b.title.to_lowercase()),
+            key.descending,
+        ),
+        SortField::Category => optional(
+            lowercase(a.category.as_ref()),
+            lowercase(b.category.as_ref()),
+            key.descending,
+        ),
+        SortField::Project => optional(
+            lowercase(a.project.as_ref()),
+            lowercase(b.project.as_ref()),
+            key.descending,
+        ),
     }
 }
 
-fn parse_priority_for_filter(priority_str: &str) -> Result<Priority, ()> {
-    match priority_str {
-        "p0" => Ok(Priority::P0),
-        "p1" => Ok(Priority::P1),
-        "p2" => Ok(Priority::P2),
-        "p3" => Ok(Priority::P3),
-        "p4" => Ok(Priority::P4),
-        "p5" => Ok(Priority::P5),
-        _ => Err(()),
+/// Work in progress first, then open, done and archived TODOs
+fn status_rank(status: &Status) -> u8 {
+    match status {
+        Status::InProgress => 0,
+        Status::Todo => 1,
+        Status::Done => 2,
+        Status::Archived => 3,
     }
 }
 
-pub fn sort_todos_by_priority(todos: &mut [&Todo]) {
-    todos.sort_by(|a, b| {
-        // Sort by priority first (P0 is highest)
-        match a.priority_value().cmp(&b.priority_value()) {
-            std::cmp::Ordering::Equal => {
-                // Then by creation date (newest first)
-                b.created_date.cmp(&a.created_date)
+/// What `todo list --group-by` splits TODOs into sections by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    Category,
+    Project,
+    Status,
+    Tag,
+    Priority,
+}
+
+/// Splits TODOs into named sections, keeping their order within each.
+///
+/// Priorities and statuses come in their natural order, other sections
+/// alphabetically with TODOs lacking the field last. Like sorting, names
+/// ignore case: `Work` and `work` share a section, named as first seen. A
+/// TODO with several tags appears under each of them.
+pub fn group_todos<'a>(todos: &[&'a Todo], group_by: GroupBy) -> Vec<(String, Vec<&'a Todo>)> {
+    // Sections keyed by (sort position, lowercase name), holding the name shown
+    let mut groups: BTreeMap<(u8, String), (String, Vec<&'a Todo>)> = BTreeMap::new();
+    let labelled = |name: Option<&String>, missing: &str| match name {
+        Some(name) => (0, name.clone()),
+        None => (1, missing.to_string()),
+    };
+
+    for todo in todos {
+        let names = match group_by {
+            GroupBy::Category => vec![labelled(todo.category.as_ref(), "(no category)")],
+            GroupBy::Project => vec![labelled(todo.project.as_ref(), "(no project)")],
+            GroupBy::Status => vec![(
+                status_rank(&todo.status),
+                todo.status.to_string().to_lowercase(),
+            )],
+            GroupBy::Tag if todo.tags.is_empty() => vec![(1, "(no tags)".to_string())],
+            GroupBy::Tag => todo
+                .tags
+                .iter()
+                .map(|tag| (0, format!("#{}", tag)))
+                .collect(),
+            GroupBy::Priority => vec![(todo.priority_value(), todo.priority.to_string())],
+        };
+        for (position, name) in names {
+            let (_, group) = groups
+                .entry((position, name.to_lowercase()))
+                .or_insert_with(|| (name, Vec::new()));
+            // Duplicate tags on one TODO list it once
+            if !group.iter().any(|t| t.id == todo.id) {
+                group.push(todo);
             }
-            other => other,
         }
-    });
+    }
+
+    groups.into_values().collect()
 }
 
-pub fn sort_todos_by_date(todos: &mut [&Todo]) {
-    todos.sort_by(|a, b| b.created_date.cmp(&a.created_date));
+/// Orders TODOs so every subtask follows its parent, pairing each with its depth.
+///
+/// Subtasks whose parent is not among `todos` are shown at the top level.
+pub fn order_as_tree<'a>(todos: &[&'a Todo]) -> Vec<(&'a Todo, usize)> {
+    let ids: HashSet<u32> = todos.iter().map(|todo| todo.id).collect();
+    let mut children: HashMap<u32, Vec<&'a Todo>> = HashMap::new();
+    for todo in todos {
+        if let Some(parent_id) = todo.parent_id.filter(|id| ids.contains(id)) {
+            children.entry(parent_id).or_default().push(todo);
+        }
+    }
+    let mut ordered = Vec::with_capacity(todos.len());
+    let mut visited = HashSet::new();
+
+    fn visit<'a>(
+        todo: &'a Todo,
+        depth: usize,
+        children: &HashMap<u32, Vec<&'a Todo>>,
+        visited: &mut HashSet<u32>,
+        ordered: &mut Vec<(&'a Todo, usize)>,
+    ) {
+        if !visited.insert(todo.id) {
+            return;
+        }
+        ordered.push((todo, depth));
+        for child in children.get(&todo.id).into_iter().flatten() {
+            visit(child, depth + 1, children, visited, ordered);
+        }
+    }
+
+    for todo in todos {
+        let is_root = todo
+            .parent_id
+            .is_none_or(|parent_id| !ids.contains(&parent_id));
+        if is_root {
+            visit(todo, 0, &children, &mut visited, &mut ordered);
+        }
+    }
+    // TODOs in a parent loop have no root above them; never drop them
+    for todo in todos {
+        visit(todo, 0, &children, &mut visited, &mut ordered);
+    }
+
+    ordered
 }
 
-pub fn get_active_todos<'a>(todos: &'a [Todo]) -> Vec<&'a Todo> {
+pub fn get_active_todos(todos: &[Todo]) -> Vec<&Todo> {
     todos
         .iter()
         .filter(|todo| todo.is_active() && todo.status != Status::Archived)
         .collect()
 }
 
-pub fn get_all_todos_including_archived<'a>(todos: &'a [Todo]) -> Vec<&'a Todo> {
+pub fn get_all_todos_including_archived(todos: &[Todo]) -> Vec<&Todo> {
     todos.iter().collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Priority;
+
+    #[test]
+    fn huge_look_backs_are_errors_not_panics() {
+        for query in [
+            "age:>100000000d",
+            "created:>100000000d",
+            "done:3000000000000000w",
+            "age:<99999999999999999999d",
+        ] {
+            assert!(Query::parse(query).is_err(), "{} should not parse", query);
+        }
+        assert!(parse_date_span("100000000d").is_err());
+        assert!(Query::parse("age:>30d created:2w").is_ok());
+    }
+
+    fn created_days_ago(days: i64) -> Todo {
+        let mut todo = Todo::new(format!("Created {} days ago", days));
+        todo.id = days as u32;
+        todo.created_date = Local::now() - Duration::days(days);
+        todo
+    }
+
+    /// Ages (in days) of the TODOs created 6, 7 and 8 days ago that `query` finds
+    fn ages_matching(query: &str) -> Vec<u32> {
+        let todos = [
+            created_days_ago(6),
+            created_days_ago(7),
+            created_days_ago(8),
+        ];
+        let query = Query::parse(query).unwrap();
+        todos
+            .iter()
+            .filter(|todo| query.matches(todo))
+            .map(|todo| todo.id)
+            .collect()
+    }
+
+    #[test]
+    fn date_comparisons_with_look_backs_and_dates() {
+        let week_ago = (Local::now() - Duration::days(7)).format("%Y-%m-%d");
+        for span in ["7d".to_string(), "1w".to_string(), week_ago.to_string()] {
+            assert_eq!(
+                ages_matching(&format!("created:>{}", span)),
+                [6],
+                "> {}",
+                span
+            );
+            assert_eq!(
+                ages_matching(&format!("created:>={}", span)),
+                [6, 7],
+                ">= {}",
+                span
+            );
+            assert_eq!(
+                ages_matching(&format!("created:<{}", span)),
+                [8],
+                "< {}",
+                span
+            );
+            assert_eq!(
+                ages_matching(&format!("created:<={}", span)),
+                [7, 8],
+                "<= {}",
+                span
+            );
+        }
+        // Without a comparison a look-back runs until today, a date is one day
+        assert_eq!(ages_matching("created:7d"), [6, 7]);
+        assert_eq!(ages_matching(&format!("created:{}", week_ago)), [7]);
+        // The opposite way round for age
+        assert_eq!(ages_matching("age:<7d"), [6]);
+        assert_eq!(ages_matching("age:>=7d"), [7, 8]);
+
+        // What --created-after builds
+        let after = DateFilter::compare(Comparison::After, "7d").unwrap();
+        assert!(after.matches(created_days_ago(6).created_date.date_naive()));
+        assert!(!after.matches(created_days_ago(7).created_date.date_naive()));
+    }
+
+    fn text(word: &str) -> Query {
+        Query::Term(Term::Text(word.to_string()))
+    }
+
+    fn parse_error(query: &str) -> QueryError {
+        Query::parse(query).unwrap_err()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            Query::parse("a b OR c").unwrap(),
+            Query::Or(vec![Query::And(vec![text("a"), text("b")]), text("c")])
+        );
+        assert_eq!(
+            Query::parse("a OR b AND c").unwrap(),
+            Query::Or(vec![text("a"), Query::And(vec![text("b"), text("c")])])
+        );
+        assert_eq!(Query::parse("").unwrap(), Query::All);
+    }
+
+    #[test]
+    fn not_and_parentheses() {
+        let not = |query: Query| Query::Not(Box::new(query));
+        assert_eq!(
+            Query::parse("a (b OR c)").unwrap(),
+            Query::And(vec![text("a"), Query::Or(vec![text("b"), text("c")])])
+        );
+        assert_eq!(
+            Query::parse("NOT (a OR b) -c").unwrap(),
+            Query::And(vec![
+                not(Query::Or(vec![text("a"), text("b")])),
+                not(text("c"))
+            ])
+        );
+        assert_eq!(
+            Query::parse("-#work").unwrap(),
+            not(Query::Term(Term::Tag("work".to_string())))
+        );
+        // Lowercase words are searched for, not operators
+        assert_eq!(
+            Query::parse("not or").unwrap(),
+            Query::And(vec![text("not"), text("or")])
+        );
+    }
+
+    #[test]
+    fn quoted_phrases() {
+        assert_eq!(
+            Query::parse(r#""Fix Login" OR bug"#).unwrap(),
+            Query::Or(vec![
+                Query::Term(Term::Phrase("fix login".to_string())),
+                text("bug")
+            ])
+        );
+        // Quoted operators and field names are plain text
+        assert_eq!(
+            Query::parse(r#""OR" "due:today""#).unwrap(),
+            Query::And(vec![
+                Query::Term(Term::Phrase("or".to_string())),
+                Query::Term(Term::Phrase("due:today".to_string()))
+            ])
+        );
+        assert_eq!(
+            Query::parse(r#"title:"fix login""#).unwrap(),
+            Query::Term(Term::Field(Field::Title, "fix login".to_string()))
+        );
+    }
+
+    #[test]
+    fn field_qualifiers() {
+        let term = |query: &str| match Query::parse(query).unwrap() {
+            Query::Term(term) => term,
+            other => panic!("{} parsed as {:?}", query, other),
+        };
+        assert_eq!(term("#Work"), Term::Tag("work".to_string()));
+        assert_eq!(term("tag:work"), Term::Tag("work".to_string()));
+        assert_eq!(term("@Home"), Term::Category("home".to_string()));
+        assert_eq!(term("!done"), Term::Status(Status::Done));
+        assert_eq!(term("status:inprogress"), Term::Status(Status::InProgress));
+        assert_eq!(term("p1"), Term::Priority(1, 1));
+        assert_eq!(term("priority:p2..p0"), Term::Priority(0, 2));
+        assert_eq!(
+            term("notes:Server"),
+            Term::Field(Field::Notes, "server".to_string())
+        );
+        assert_eq!(
+            term("project:guidebook"),
+            Term::Field(Field::Project, "guidebook".to_string())
+        );
+        assert!(matches!(term("due:today"), Term::Date(DateField::Due, _)));
+        assert!(matches!(
+            term("done:this-week"),
+            Term::Date(DateField::Finished, _)
+        ));
+        // URLs are text, not a field
+        assert_eq!(
+            term("https://example.com"),
+            Term::Text("https://example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_column() {
+        let cases = [
+            ("a OR", 5, "after OR"),
+            ("a AND OR b", 3, "after AND"),
+            ("OR a", 1, "before this operator"),
+            ("NOT", 4, "after NOT"),
+            ("a (b OR c", 3, "Missing ')'"),
+            ("a b)", 4, "Unexpected ')'"),
+            ("()", 1, "Empty parentheses"),
+            (r#"a "open phrase"#, 3, "closing '\"'"),
+            ("size:large", 1, "Unknown field 'size:'"),
+            ("a status:later", 3, "Unknown status 'later'"),
+            ("p0..p9", 1, "Unknown priority"),
+            ("b #", 3, "tag name"),
+            ("-size:x", 2, "Unknown field"),
+        ];
+        for (query, column, message) in cases {
+            let error = parse_error(query);
+            assert_eq!(error.column, column, "{}: {}", query, error);
+            assert!(error.message.contains(message), "{}: {}", query, error);
+        }
+    }
+
+    fn titled(id: u32, title: &str) -> Todo {
+        let mut todo = Todo::new(title.to_string());
+        todo.id = id;
+        todo
+    }
+
+    /// IDs of the TODOs `query` finds, best match first
+    fn ranked(todos: &[Todo], query: &str) -> Vec<u32> {
+        let query = Query::parse(query).unwrap();
+        let mut matches: Vec<&Todo> = todos.iter().filter(|todo| query.matches(todo)).collect();
+        rank_by_query(&mut matches, &query);
+        matches.iter().map(|todo| todo.id).collect()
+    }
+
+    #[test]
+    fn abbreviated_words_find_the_title() {
+        let todos = [
+            titled(1, "Fix the login bug"),
+            titled(2, "Write the release notes"),
+            titled(3, "Log in to the bug tracker"),
+        ];
+        assert_eq!(ranked(&todos, "lgn bug"), vec![1]);
+        assert_eq!(ranked(&todos, "lgoin"), vec![1]);
+        assert_eq!(
+            Query::parse("lgn")
+                .unwrap()
+                .title_highlights("Fix the login bug"),
+            vec![8, 10, 12]
+        );
+    }
+
+    #[test]
+    fn title_matches_rank_above_notes_matches() {
+        let mut in_notes = titled(1, "Call the bank");
+        in_notes.notes = Some("Ask about the deploy fee".to_string());
+        let in_title = titled(2, "Deploy the site");
+        let mut tagged = titled(3, "Update the docs");
+        tagged.tags = vec!["deploy".to_string()];
+
+        assert_eq!(
+            ranked(&[in_notes, in_title, tagged], "deploy"),
+            vec![2, 3, 1]
+        );
+    }
+
+    #[test]
+    fn priority_breaks_ties_between_equal_matches() {
+        let mut low = titled(1, "Renew passport");
+        low.priority = Priority::P3;
+        let mut high = titled(2, "Renew passport");
+        high.priority = Priority::P0;
+        let mut medium = titled(3, "Renew passport");
+        medium.priority = Priority::P1;
+
+        assert_eq!(ranked(&[low, high, medium], "passport"), vec![2, 3, 1]);
+        // Without words to rank by, the given order stays
+        assert_eq!(
+            ranked(&[titled(5, "b"), titled(4, "a")], "!todo"),
+            vec![5, 4]
+        );
+    }
+
+    #[test]
+    fn sort_orders_parse_with_descending_keys() {
+        assert_eq!(
+            parse_sort("priority, -Created,+title,").unwrap(),
+            vec![
+                SortKey::ascending(SortField::Priority),
+                SortKey::descending(SortField::Created),
+                SortKey::ascending(SortField::Title),
+            ]
+        );
+        assert_eq!(
+            parse_sort("-done").unwrap(),
+            vec![SortKey::descending(SortField::Finished)]
+        );
+        assert_eq!(parse_sort("").unwrap(), vec![]);
+        for spec in ["size", "priority,-", "-due,urgency"] {
+            let error = parse_sort(spec).unwrap_err();
+            assert!(
+                error.starts_with("Unknown sort field"),
+                "{}: {}",
+                spec,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn descending_sorts_keep_missing_fields_last() {
+        let mut due_soon = titled(1, "a");
+        due_soon.due_date = NaiveDate::from_ymd_opt(2026, 1, 1);
+        let mut due_later = titled(2, "b");
+        due_later.due_date = NaiveDate::from_ymd_opt(2026, 6, 1);
+        let undated = titled(3, "c");
+        let todos = [undated, due_soon, due_later];
+        let sorted = |spec: &str| {
+            let mut refs: Vec<&Todo> = todos.iter().collect();
+            sort_todos(&mut refs, &parse_sort(spec).unwrap());
+            refs.iter().map(|todo| todo.id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted("due"), vec![1, 2, 3]);
+        assert_eq!(sorted("-due"), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn groups_ignore_case_and_list_missing_fields_last() {
+        let mut todos: Vec<Todo> = (1..=5).map(|id| titled(id, "task")).collect();
+        todos[0].category = Some("Work".to_string());
+        todos[1].category = Some("home".to_string());
+        todos[2].category = Some("work".to_string());
+        todos[4].category = Some("Errands".to_string());
+        todos[0].tags = vec!["Urgent".to_string(), "urgent".to_string()];
+        todos[3].tags = vec!["urgent".to_string(), "later".to_string()];
+        let refs: Vec<&Todo> = todos.iter().collect();
+        let names = |group_by: GroupBy| {
+            group_todos(&refs, group_by)
+                .into_iter()
+                .map(|(name, todos)| (name, todos.iter().map(|todo| todo.id).collect()))
+                .collect::<Vec<(String, Vec<u32>)>>()
+        };
+
+        assert_eq!(
+            names(GroupBy::Category),
+            vec![
+                ("Errands".to_string(), vec![5]),
+                ("home".to_string(), vec![2]),
+                ("Work".to_string(), vec![1, 3]),
+                ("(no category)".to_string(), vec![4]),
+            ]
+        );
+        assert_eq!(
+            names(GroupBy::Tag),
+            vec![
+                ("#later".to_string(), vec![4]),
+                ("#Urgent".to_string(), vec![1, 4]),
+                ("(no tags)".to_string(), vec![2, 3, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn subtasks_follow_their_parents() {
+        let mut todos: Vec<Todo> = (1..=5).map(|id| titled(id, "task")).collect();
+        todos[0].parent_id = Some(3);
+        todos[1].parent_id = Some(1);
+        todos[3].parent_id = Some(3);
+        // Parent filtered out, so shown at the top level
+        todos[4].parent_id = Some(9);
+        let refs: Vec<&Todo> = todos.iter().collect();
+        let tree: Vec<(u32, usize)> = order_as_tree(&refs)
+            .into_iter()
+            .map(|(todo, depth)| (todo.id, depth))
+            .collect();
+
+        assert_eq!(tree, vec![(3, 0), (1, 1), (2, 2), (4, 1), (5, 0)]);
+    }
+
+    #[test]
+    fn parent_loops_still_list_every_todo() {
+        let mut todos: Vec<Todo> = (1..=3).map(|id| titled(id, "task")).collect();
+        todos[0].parent_id = Some(2);
+        todos[1].parent_id = Some(1);
+        todos[2].parent_id = Some(2);
+        let refs: Vec<&Todo> = todos.iter().collect();
+        let tree: Vec<(u32, usize)> = order_as_tree(&refs)
+            .into_iter()
+            .map(|(todo, depth)| (todo.id, depth))
+            .collect();
+
+        assert_eq!(tree, vec![(1, 0), (2, 1), (3, 2)]);
+    }
+}
@@ -1,6 +1,19 @@
-use anyhow::Result;
-use std::path::PathBuf;
-use std::process::Command;
+use crate::config::{load_settings, token_for_url};
+use crate::core::{decode_todos, encode_todos, get_data_dir, BackendKind, TodoList};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeZone};
+use git2::{
+    BranchType, Cred, CredentialType, ErrorClass, ErrorCode, FetchOptions, IndexAddOption, Oid,
+    PushOptions, RemoteCallbacks, Repository, Signature, StatusOptions,
+};
+use std::path::{Path, PathBuf};
+
+/// Identity used for commits when git has no `user.name`/`user.email` configured
+const FALLBACK_NAME: &str = "guidebook-todo";
+const FALLBACK_EMAIL: &str = "guidebook-todo@localhost";
+
+/// Credential attempts per fetch or push before giving up, so a rejected key can't loop forever
+const MAX_CREDENTIAL_ATTEMPTS: usize = 3;
 
 /// Git status information for the data directory
 #[derive(Debug, Clone)]
@@ -8,71 +21,773 @@ pub struct GitStatus {
     pub has_changes: bool,
     pub pretty_path: String,
     pub status_message: String,
+    /// Current branch, `None` outside a repository or on a detached HEAD
+    pub branch: Option<String>,
+    /// Commits not yet pushed to / pulled from the upstream branch, as of the last fetch
+    pub ahead: usize,
+    pub behind: usize,
+    pub remote_url: Option<String>,
 }
 
 /// Get git status for the guidebook data directory
 pub fn get_git_status() -> Result<GitStatus> {
-    let data_dir = get_data_dir()?;
-    
-    // Check if this is a git repository
-    let git_dir = data_dir.join(".git");
-    if !git_dir.exists() {
-        return Ok(GitStatus {
-            has_changes: false,
-            pretty_path: format_pretty_path(&data_dir)?,
-            status_message: "not a git repository".to_string(),
-        });
-    }
-    
-    // Run git status --porcelain to check for changes
-    let output = Command::new("git")
-        .args(&["status", "--porcelain"])
-        .current_dir(&data_dir)
-        .output()?;
-    
-    if !output.status.success() {
-        return Ok(GitStatus {
-            has_changes: false,
-            pretty_path: format_pretty_path(&data_dir)?,
-            status_message: "git error".to_string(),
-        });
+    repo_status(&get_data_dir()?)
+}
+
+/// Git status of the repository at `path`
+pub fn repo_status(path: &Path) -> Result<GitStatus> {
+    let pretty_path = format_pretty_path(path)?;
+    let not_a_repo = |message: &str| GitStatus {
+        has_changes: false,
+        pretty_path: pretty_path.clone(),
+        status_message: message.to_string(),
+        branch: None,
+        ahead: 0,
+        behind: 0,
+        remote_url: None,
+    };
+
+    if !path.join(".git").exists() {
+        return Ok(not_a_repo("not a git repository"));
     }
-    
-    let status_output = String::from_utf8_lossy(&output.stdout);
-    let has_changes = !status_output.trim().is_empty();
-    
-    let status_message = if has_changes {
+    let Ok(repo) = Repository::open(path) else {
+        return Ok(not_a_repo("git error"));
+    };
+
+    let has_changes = has_changes(&repo)?;
+    let branch = current_branch(&repo)?;
+    let (ahead, behind) = match &branch {
+        Some(branch) => upstream_ahead_behind(&repo, branch).unwrap_or((0, 0)),
+        None => (0, 0),
+    };
+    let remote = branch
+        .as_deref()
+        .and_then(|branch| upstream_remote(&repo, branch))
+        .unwrap_or_else(|| "origin".to_string());
+    let remote_url = remote_url(&repo, &remote);
+
+    let mut status_message = if has_changes {
         "modified".to_string()
     } else {
         "up to date".to_string()
     };
-    
+    if ahead > 0 {
+        status_message.push_str(&format!(" ↑{}", ahead));
+    }
+    if behind > 0 {
+        status_message.push_str(&format!(" ↓{}", behind));
+    }
+
     Ok(GitStatus {
         has_changes,
-        pretty_path: format_pretty_path(&data_dir)?,
+        pretty_path,
         status_message,
+        branch,
+        ahead,
+        behind,
+        remote_url,
     })
 }
 
-/// Get the guidebook data directory
-fn get_data_dir() -> Result<PathBuf> {
-    let home = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
-    
-    Ok(home.join(".local").join("share").join("guidebook"))
-}
-
 /// Format a path with ~ for home directory
-fn format_pretty_path(path: &PathBuf) -> Result<String> {
-    let home = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
-    
+fn format_pretty_path(path: &Path) -> Result<String> {
+    let home =
+        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+
     let path_str = path.to_string_lossy();
     let home_str = home.to_string_lossy();
-    
+
     if path_str.starts_with(&*home_str) {
         Ok(path_str.replace(&*home_str, "~"))
     } else {
         Ok(path_str.to_string())
     }
 }
+
+/// Root of the git repository containing `path`, if any
+pub fn repo_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+pub fn open_repo(path: &Path) -> Result<Repository> {
+    Repository::open(path).with_context(|| format!("{} is not a git repository", path.display()))
+}
+
+/// Creates a repository at `path`, or opens the one already there
+pub fn init_repo(path: &Path) -> Result<Repository> {
+    Repository::init(path)
+        .with_context(|| format!("Failed to initialize git repository in {}", path.display()))
+}
+
+/// Clones `url` into `path`, which must not exist or be empty. Cloning an
+/// empty repository gives a repository with `origin` set and no commits.
+pub fn clone_repo(url: &str, path: &Path, token: Option<&str>) -> Result<Repository> {
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(remote_callbacks(token));
+    let repo = git2::build::RepoBuilder::new()
+        .fetch_options(options)
+        .clone(url, path)
+        .with_context(|| format!("Failed to clone {}", url))?;
+
+    // An empty clone starts on libgit2's default branch. Like git, use the
+    // remote's HEAD if it advertises one, then init.defaultBranch, then main
+    if head_commit(&repo)?.is_none() {
+        let branch = remote_default_branch(&repo, "origin", token).unwrap_or_else(|| {
+            let configured = git2::Config::open_default()
+                .and_then(|config| config.get_string("init.defaultBranch"))
+                .unwrap_or_else(|_| "main".to_string());
+            format!("refs/heads/{}", configured)
+        });
+        // Drop the tracking config clone wrote for the branch being replaced
+        if let Some(old) = current_branch(&repo)?.filter(|old| !branch.ends_with(old.as_str())) {
+            let mut config = repo.config()?;
+            let _ = config.remove(&format!("branch.{}.remote", old));
+            let _ = config.remove(&format!("branch.{}.merge", old));
+        }
+        repo.set_head(&branch)?;
+    }
+    Ok(repo)
+}
+
+/// The branch the remote's HEAD points to, e.g. `refs/heads/main`
+fn remote_default_branch(repo: &Repository, remote: &str, token: Option<&str>) -> Option<String> {
+    let mut remote = repo.find_remote(remote).ok()?;
+    let connection = remote
+        .connect_auth(git2::Direction::Fetch, Some(remote_callbacks(token)), None)
+        .ok()?;
+    let branch = connection.default_branch().ok()?;
+    branch.as_str().map(str::to_string)
+}
+
+/// Whether the working tree has uncommitted changes, including untracked files
+pub fn has_changes(repo: &Repository) -> Result<bool> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .context("Failed to read git status")?;
+    Ok(!statuses.is_empty())
+}
+
+/// The checked-out branch, also when it has no commits yet; `None` on a detached HEAD
+pub fn current_branch(repo: &Repository) -> Result<Option<String>> {
+    let head = repo.find_reference("HEAD").context("Failed to read HEAD")?;
+    Ok(head
+        .symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .map(str::to_string))
+}
+
+/// The commit HEAD points to, `None` before the first commit
+pub fn head_commit(repo: &Repository) -> Result<Option<Oid>> {
+    match repo.head() {
+        Ok(head) => Ok(head.target()),
+        Err(e) if e.code() == ErrorCode::UnbornBranch || e.code() == ErrorCode::NotFound => {
+            Ok(None)
+        }
+        Err(e) => Err(e).context("Failed to read HEAD"),
+    }
+}
+
+pub fn remote_url(repo: &Repository, remote: &str) -> Option<String> {
+    repo.find_remote(remote)
+        .ok()
+        .and_then(|remote| remote.url().map(str::to_string))
+}
+
+/// Remote that `branch` tracks (`branch.<name>.remote`)
+pub fn upstream_remote(repo: &Repository, branch: &str) -> Option<String> {
+    repo.config()
+        .ok()?
+        .get_string(&format!("branch.{}.remote", branch))
+        .ok()
+}
+
+/// Commits on `branch` missing from its upstream, and the other way around
+fn upstream_ahead_behind(repo: &Repository, branch: &str) -> Option<(usize, usize)> {
+    let local = repo.find_branch(branch, BranchType::Local).ok()?;
+    let upstream = local.upstream().ok()?;
+    let (local, upstream) = (local.get().target()?, upstream.get().target()?);
+    repo.graph_ahead_behind(local, upstream).ok()
+}
+
+/// Stages everything (like `git add -A`) and commits it on the current branch.
+/// Returns `None` if there was nothing to commit.
+pub fn commit_all(repo: &Repository, message: &str) -> Result<Option<Oid>> {
+    let mut index = repo.index().context("Failed to read the git index")?;
+    index
+        .add_all(["*"], IndexAddOption::DEFAULT, None)
+        .context("Failed to stage changes")?;
+    index
+        .update_all(["*"], None)
+        .context("Failed to stage deletions")?;
+    index.write().context("Failed to write the git index")?;
+    let tree_id = index.write_tree().context("Failed to write tree")?;
+    commit_tree(repo, tree_id, message)
+}
+
+/// Stages `path` (relative to the repository root) and commits it, leaving
+/// other changes in the working tree alone. Returns `None` if it was unchanged.
+pub fn commit_file(repo: &Repository, path: &Path, message: &str) -> Result<Option<Oid>> {
+    let mut index = repo.index().context("Failed to read the git index")?;
+    let absolute = repo.workdir().map(|dir| dir.join(path));
+    if absolute.is_some_and(|absolute| absolute.exists()) {
+        index.add_path(path)
+    } else {
+        index.remove_path(path)
+    }
+    .with_context(|| format!("Failed to stage {}", path.display()))?;
+    index.write().context("Failed to write the git index")?;
+    let tree_id = index.write_tree().context("Failed to write tree")?;
+    commit_tree(repo, tree_id, message)
+}
+
+/// Commits `tree_id` on top of HEAD, unless it matches HEAD's tree
+fn commit_tree(repo: &Repository, tree_id: Oid, message: &str) -> Result<Option<Oid>> {
+    let parent = head_commit(repo)?
+        .map(|oid| repo.find_commit(oid))
+        .transpose()?;
+    if parent
+        .as_ref()
+        .is_some_and(|parent| parent.tree_id() == tree_id)
+    {
+        return Ok(None);
+    }
+
+    let tree = repo.find_tree(tree_id)?;
+    let signature = signature(repo)?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let oid = repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .context("Failed to commit")?;
+    Ok(Some(oid))
+}
+
+/// The configured git identity, or a placeholder so commits never fail for lack of one
+pub fn signature(repo: &Repository) -> Result<Signature<'static>> {
+    match repo.signature() {
+        Ok(signature) => Ok(signature.to_owned()),
+        Err(_) => Ok(Signature::now(FALLBACK_NAME, FALLBACK_EMAIL)?),
+    }
+}
+
+/// Callbacks that authenticate through the SSH agent or git's credential
+/// helpers; `token` (e.g. from GitHub OAuth) is tried first for HTTPS remotes
+fn remote_callbacks(token: Option<&str>) -> RemoteCallbacks<'_> {
+    let mut attempts = 0;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        attempts += 1;
+        if attempts > MAX_CREDENTIAL_ATTEMPTS {
+            return Err(git2::Error::from_str("authentication failed"));
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            // A token saved by `todo init` is tried before the credential helper
+            if attempts == 1 {
+                let token = token.map(str::to_string).or_else(|| token_for_url(url));
+                if let Some(token) = token {
+                    return Cred::userpass_plaintext("x-access-token", &token);
+                }
+            }
+            let config = git2::Config::open_default()?;
+            return Cred::credential_helper(&config, url, username);
+        }
+        Cred::default()
+    });
+    callbacks
+}
+
+/// Where the current branch is pushed to and synced with
+#[derive(Debug, Clone)]
+pub struct RemoteTarget {
+    pub remote: String,
+    /// Local branch being pushed
+    pub local_branch: String,
+    /// Branch on the remote
+    pub branch: String,
+}
+
+impl std::fmt::Display for RemoteTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.remote, self.branch)
+    }
+}
+
+/// Picks the remote and branch for the current branch: explicit arguments
+/// first, then the branch's tracking config, then the `remote`/`branch`
+/// settings, then `origin` and a remote branch with the same name
+pub fn remote_target(
+    repo: &Repository,
+    remote: Option<String>,
+    branch: Option<String>,
+) -> Result<RemoteTarget> {
+    let local_branch = current_branch(repo)?.ok_or_else(|| {
+        anyhow::anyhow!("The data repository is not on a branch. Check one out with git first")
+    })?;
+    let settings = load_settings()?;
+    let config = repo.config().context("Failed to open git config")?;
+    let tracked = |key: &str| {
+        config
+            .get_string(&format!("branch.{}.{}", local_branch, key))
+            .ok()
+    };
+
+    let remote = remote
+        .or_else(|| tracked("remote"))
+        .or(settings.remote)
+        .unwrap_or_else(|| "origin".to_string());
+    let branch = branch
+        .or_else(|| {
+            tracked("merge").map(|merge| {
+                merge
+                    .strip_prefix("refs/heads/")
+                    .map(str::to_string)
+                    .unwrap_or(merge)
+            })
+        })
+        .or(settings.branch)
+        .unwrap_or_else(|| local_branch.clone());
+
+    if repo.find_remote(&remote).is_err() {
+        let known: Vec<String> = repo
+            .remotes()
+            .map(|names| names.iter().flatten().map(str::to_string).collect())
+            .unwrap_or_default();
+        if known.is_empty() {
+            anyhow::bail!(
+                "No remote is configured. Add one with: git -C {} remote add origin <url>",
+                repo.workdir().unwrap_or(repo.path()).display()
+            );
+        }
+        anyhow::bail!(
+            "No '{}' remote is configured (available: {})",
+            remote,
+            known.join(", ")
+        );
+    }
+
+    Ok(RemoteTarget {
+        remote,
+        local_branch,
+        branch,
+    })
+}
+
+/// Updates `refs/remotes/<remote>/<branch>` from the remote
+pub fn fetch(repo: &Repository, remote: &str, branch: &str) -> Result<()> {
+    let mut remote_handle = repo
+        .find_remote(remote)
+        .with_context(|| format!("No '{}' remote is configured", remote))?;
+    let refspec = format!("+refs/heads/{0}:refs/remotes/{1}/{0}", branch, remote);
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(remote_callbacks(None));
+    remote_handle
+        .fetch(&[&refspec], Some(&mut options), None)
+        .with_context(|| format!("Could not fetch from '{}'", remote))
+}
+
+/// Whether `error` came from the network (DNS, connection, TLS, SSH transport)
+/// rather than from git itself
+pub fn is_network_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.downcast_ref::<git2::Error>().is_some_and(|e| {
+            matches!(
+                e.class(),
+                ErrorClass::Net | ErrorClass::Ssh | ErrorClass::Ssl | ErrorClass::Http
+            )
+        })
+    })
+}
+
+/// Why a push was refused
+#[derive(Debug)]
+pub enum PushError {
+    /// The remote has commits this branch doesn't, so it must be merged first
+    NonFastForward,
+    Other(anyhow::Error),
+}
+
+/// Pushes the local branch to `target`, recording it as the upstream if the branch has none
+pub fn push(repo: &Repository, target: &RemoteTarget) -> std::result::Result<(), PushError> {
+    push_with_token(repo, target, None)
+}
+
+/// [`push`], authenticating to HTTPS remotes with `token` if given
+pub fn push_with_token(
+    repo: &Repository,
+    target: &RemoteTarget,
+    token: Option<&str>,
+) -> std::result::Result<(), PushError> {
+    let remote = target.remote.as_str();
+    let mut remote_handle = repo
+        .find_remote(remote)
+        .with_context(|| format!("No '{}' remote is configured", remote))
+        .map_err(PushError::Other)?;
+
+    let mut rejection: Option<String> = None;
+    let mut callbacks = remote_callbacks(token);
+    callbacks.push_update_reference(|_, status| {
+        if let Some(status) = status {
+            rejection = Some(status.to_string());
+        }
+        Ok(())
+    });
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+
+    let refspec = format!(
+        "refs/heads/{}:refs/heads/{}",
+        target.local_branch, target.branch
+    );
+    let result = remote_handle.push(&[&refspec], Some(&mut options));
+    drop(options);
+
+    match (result, rejection) {
+        (Err(e), _) if e.code() == ErrorCode::NotFastForward => {
+            return Err(PushError::NonFastForward)
+        }
+        (Err(e), _) => {
+            return Err(PushError::Other(
+                anyhow::Error::new(e).context(format!("Could not push to '{}'", remote)),
+            ))
+        }
+        (Ok(()), Some(reason)) if reason.contains("fast-forward") || reason.contains("fetch") => {
+            return Err(PushError::NonFastForward)
+        }
+        (Ok(()), Some(reason)) => {
+            return Err(PushError::Other(anyhow::anyhow!(
+                "The remote rejected the push: {}",
+                reason
+            )))
+        }
+        (Ok(()), None) => {}
+    }
+
+    set_upstream(repo, target).map_err(PushError::Other)
+}
+
+impl From<PushError> for anyhow::Error {
+    fn from(error: PushError) -> Self {
+        match error {
+            PushError::NonFastForward => anyhow::anyhow!(
+                "The remote has commits this machine doesn't. Run 'todo sync' to merge them"
+            ),
+            PushError::Other(e) => e,
+        }
+    }
+}
+
+/// Records `target` as the upstream of the local branch, like `git push --set-upstream`,
+/// unless it already tracks something
+fn set_upstream(repo: &Repository, target: &RemoteTarget) -> Result<()> {
+    let mut config = repo.config().context("Failed to open git config")?;
+    let key = |name: &str| format!("branch.{}.{}", target.local_branch, name);
+    if config.get_string(&key("remote")).is_ok() {
+        return Ok(());
+    }
+    config.set_str(&key("remote"), &target.remote)?;
+    config.set_str(&key("merge"), &format!("refs/heads/{}", target.branch))?;
+    Ok(())
+}
+
+/// Resolves a commit hash, branch, tag or expression like `HEAD~2`
+pub fn resolve_commit(repo: &Repository, spec: &str) -> Option<Oid> {
+    repo.revparse_single(spec)
+        .ok()?
+        .peel_to_commit()
+        .ok()
+        .map(|commit| commit.id())
+}
+
+/// Contents of `path` (relative to the repository root) at `commit`, or `None` if it didn't exist there
+pub fn read_file_at(repo: &Repository, commit: Oid, path: &Path) -> Result<Option<Vec<u8>>> {
+    let tree = repo.find_commit(commit)?.tree()?;
+    let Ok(entry) = tree.get_path(path) else {
+        return Ok(None);
+    };
+    let blob = repo
+        .find_blob(entry.id())
+        .with_context(|| format!("{} is not a file", path.display()))?;
+    Ok(Some(blob.content().to_vec()))
+}
+
+/// Loads the TODO file as it was at `commit`; a commit without the file is an empty list
+pub fn load_todos_at(repo: &Repository, commit: Oid, relative_path: &Path) -> Result<TodoList> {
+    let Some(content) = read_file_at(repo, commit, relative_path)? else {
+        return Ok(TodoList::default());
+    };
+    decode_todos(BackendKind::from_path(relative_path), &content)
+        .with_context(|| format!("Failed to read the TODO file at {}", commit))
+}
+
+/// The bytes `todo_list` would be saved as in a file like `relative_path`
+pub fn serialize_todos(todo_list: &TodoList, relative_path: &Path) -> Result<Vec<u8>> {
+    encode_todos(BackendKind::from_path(relative_path), todo_list)
+}
+
+/// A commit in the history of a file
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    pub id: Oid,
+    pub short_id: String,
+    pub timestamp: DateTime<Local>,
+    pub summary: String,
+}
+
+impl CommitInfo {
+    fn new(commit: &git2::Commit) -> Result<Self> {
+        let short_id = commit
+            .as_object()
+            .short_id()?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let timestamp = Local
+            .timestamp_opt(commit.time().seconds(), 0)
+            .single()
+            .unwrap_or_else(Local::now);
+        Ok(CommitInfo {
+            id: commit.id(),
+            short_id,
+            timestamp,
+            summary: commit.summary().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+pub fn commit_info(repo: &Repository, commit: Oid) -> Result<CommitInfo> {
+    CommitInfo::new(&repo.find_commit(commit)?)
+}
+
+/// Commits reachable from HEAD that changed `path`, oldest first (like `git log --reverse -- path`)
+pub fn file_log(repo: &Repository, path: &Path) -> Result<Vec<CommitInfo>> {
+    let Some(head) = head_commit(repo)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME | git2::Sort::REVERSE)?;
+    walk.push(head)?;
+
+    let blob_at = |commit: &git2::Commit| -> Option<Oid> {
+        commit
+            .tree()
+            .ok()?
+            .get_path(path)
+            .ok()
+            .map(|entry| entry.id())
+    };
+
+    let mut log = Vec::new();
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let blob = blob_at(&commit);
+        // Like git's history simplification, skip commits matching any parent
+        let changed = if commit.parent_count() == 0 {
+            blob.is_some()
+        } else {
+            commit.parents().all(|parent| blob_at(&parent) != blob)
+        };
+        if changed {
+            log.push(CommitInfo::new(&commit)?);
+        }
+    }
+    Ok(log)
+}
+
+/// Commits on `local` missing from `upstream`, and the other way around
+pub fn ahead_behind(repo: &Repository, local: Oid, upstream: Oid) -> Result<(usize, usize)> {
+    repo.graph_ahead_behind(local, upstream)
+        .context("Failed to compare with the remote branch")
+}
+
+/// Checks out `target` and moves `branch` there, leaving the branch alone if
+/// the checkout would overwrite uncommitted changes
+pub fn fast_forward(repo: &Repository, branch: &str, target: Oid, message: &str) -> Result<()> {
+    checkout_commit(repo, target)?;
+    let refname = format!("refs/heads/{}", branch);
+    repo.reference(&refname, target, true, message)
+        .with_context(|| format!("Failed to update {}", branch))?;
+    repo.set_head(&refname)?;
+    Ok(())
+}
+
+/// Makes the working tree and index match `commit`. The checkout is safe,
+/// not forced: if a file it would replace has changed since HEAD (say a
+/// `todo add` from another terminal in the middle of a sync), nothing is
+/// overwritten and it fails instead.
+fn checkout_commit(repo: &Repository, commit: Oid) -> Result<()> {
+    let tree = repo.find_commit(commit)?.into_object();
+    repo.checkout_tree(&tree, Some(git2::build::CheckoutBuilder::new().safe()))
+        .context("The TODO file changed while syncing, so nothing was overwritten. Sync again to include the change")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{bare_remote, working_repo, write_file};
+    use tempfile::TempDir;
+
+    fn remote_head(remote: &Repository, branch: &str) -> Option<Oid> {
+        remote
+            .find_reference(&format!("refs/heads/{}", branch))
+            .ok()
+            .and_then(|reference| reference.target())
+    }
+
+    #[test]
+    fn commit_all_commits_only_when_something_changed() {
+        let (remote_dir, _remote) = bare_remote("main");
+        let work = TempDir::new().unwrap();
+        let repo = working_repo(work.path(), "main", remote_dir.path());
+
+        write_file(&repo, "todo.yaml", "next_id: 1\n");
+        let first = commit_all(&repo, "First").unwrap();
+        assert!(first.is_some());
+        assert_eq!(head_commit(&repo).unwrap(), first);
+        assert!(!has_changes(&repo).unwrap());
+        assert_eq!(commit_all(&repo, "Nothing").unwrap(), None);
+
+        std::fs::remove_file(work.path().join("todo.yaml")).unwrap();
+        assert!(has_changes(&repo).unwrap());
+        let deleted = commit_all(&repo, "Delete").unwrap().unwrap();
+        let tree = repo.find_commit(deleted).unwrap().tree().unwrap();
+        assert!(tree.get_name("todo.yaml").is_none());
+    }
+
+    #[test]
+    fn fast_forward_never_overwrites_uncommitted_changes() {
+        let (remote_dir, _remote) = bare_remote("main");
+        let work = TempDir::new().unwrap();
+        let repo = working_repo(work.path(), "main", remote_dir.path());
+        let path = work.path().join("todo.yaml");
+        write_file(&repo, "todo.yaml", "next_id: 1\n");
+        let first = commit_all(&repo, "First").unwrap().unwrap();
+        write_file(&repo, "todo.yaml", "next_id: 2\n");
+        let second = commit_all(&repo, "Second").unwrap().unwrap();
+        repo.reference("refs/heads/main", first, true, "rewind")
+            .unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+
+        // Saved by another process after the sync committed
+        write_file(&repo, "todo.yaml", "next_id: 7\n");
+        assert!(fast_forward(&repo, "main", second, "sync").is_err());
+        assert_eq!(head_commit(&repo).unwrap(), Some(first));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "next_id: 7\n");
+
+        write_file(&repo, "todo.yaml", "next_id: 1\n");
+        fast_forward(&repo, "main", second, "sync").unwrap();
+        assert_eq!(head_commit(&repo).unwrap(), Some(second));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "next_id: 2\n");
+        assert!(!has_changes(&repo).unwrap());
+    }
+
+    #[test]
+    fn remote_target_follows_the_current_branch() {
+        for branch in ["master", "main"] {
+            let (remote_dir, _remote) = bare_remote(branch);
+            let work = TempDir::new().unwrap();
+            let repo = working_repo(work.path(), branch, remote_dir.path());
+
+            let target = remote_target(&repo, None, None).unwrap();
+            assert_eq!(target.remote, "origin");
+            assert_eq!(target.local_branch, branch);
+            assert_eq!(target.branch, branch);
+            assert_eq!(target.to_string(), format!("origin/{}", branch));
+
+            let explicit =
+                remote_target(&repo, Some("origin".to_string()), Some("other".to_string()))
+                    .unwrap();
+            assert_eq!(explicit.branch, "other");
+            assert!(remote_target(&repo, Some("upstream".to_string()), None).is_err());
+        }
+    }
+
+    #[test]
+    fn push_then_reject_a_non_fast_forward() {
+        let (remote_dir, remote) = bare_remote("main");
+        let (first, second) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+
+        let repo = working_repo(first.path(), "main", remote_dir.path());
+        write_file(&repo, "todo.yaml", "next_id: 1\n");
+        let pushed = commit_all(&repo, "First").unwrap().unwrap();
+        let target = remote_target(&repo, None, None).unwrap();
+        push(&repo, &target).unwrap();
+        assert_eq!(remote_head(&remote, "main"), Some(pushed));
+        // The first push records the upstream
+        assert_eq!(upstream_remote(&repo, "main").as_deref(), Some("origin"));
+
+        let other = clone_repo(&remote_dir.path().to_string_lossy(), second.path(), None).unwrap();
+        write_file(&other, "notes.md", "from the other machine\n");
+        let theirs = commit_all(&other, "Theirs").unwrap().unwrap();
+        push(&other, &remote_target(&other, None, None).unwrap()).unwrap();
+
+        write_file(&repo, "todo.yaml", "next_id: 2\n");
+        commit_all(&repo, "Ours").unwrap();
+        assert!(matches!(
+            push(&repo, &target),
+            Err(PushError::NonFastForward)
+        ));
+        assert_eq!(remote_head(&remote, "main"), Some(theirs));
+    }
+
+    #[test]
+    fn fetch_updates_the_tracking_branch_and_ahead_behind() {
+        let (remote_dir, _remote) = bare_remote("master");
+        let (first, second) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+
+        let repo = working_repo(first.path(), "master", remote_dir.path());
+        write_file(&repo, "todo.yaml", "next_id: 1\n");
+        commit_all(&repo, "First").unwrap();
+        let target = remote_target(&repo, None, None).unwrap();
+        push(&repo, &target).unwrap();
+
+        let other = clone_repo(&remote_dir.path().to_string_lossy(), second.path(), None).unwrap();
+        for n in 0..2 {
+            write_file(&other, "notes.md", &format!("edit {}\n", n));
+            commit_all(&other, "Theirs").unwrap();
+        }
+        push(&other, &remote_target(&other, None, None).unwrap()).unwrap();
+        let theirs = head_commit(&other).unwrap().unwrap();
+
+        write_file(&repo, "todo.yaml", "next_id: 2\n");
+        let ours = commit_all(&repo, "Ours").unwrap().unwrap();
+        let status = repo_status(first.path()).unwrap();
+        assert_eq!((status.ahead, status.behind), (1, 0));
+
+        fetch(&repo, "origin", "master").unwrap();
+        let tracking = repo
+            .find_reference("refs/remotes/origin/master")
+            .unwrap()
+            .target()
+            .unwrap();
+        assert_eq!(tracking, theirs);
+        assert_eq!(ahead_behind(&repo, ours, tracking).unwrap(), (1, 2));
+
+        let status = repo_status(first.path()).unwrap();
+        assert_eq!(status.branch.as_deref(), Some("master"));
+        assert_eq!((status.ahead, status.behind), (1, 2));
+        assert!(status.status_message.contains("↑1 ↓2"));
+        assert_eq!(
+            status.remote_url.as_deref(),
+            Some(remote_dir.path().to_string_lossy().as_ref())
+        );
+    }
+}
@@ -0,0 +1,661 @@
+use crate::core::{
+    commit_info, file_log, find_todo_file, head_commit, load_todos_at, open_backend, open_repo,
+    parse_since, repo_root, resolve_commit, Status, Todo, TodoList,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use git2::Oid;
+use std::path::{Path, PathBuf};
+
+/// One point in a TODO's history: a commit that changed it, or uncommitted edits
+#[derive(Debug, Clone)]
+pub struct HistoryEvent {
+    /// Short commit hash, `None` for changes not committed yet
+    pub commit: Option<String>,
+    pub timestamp: DateTime<Local>,
+    pub message: String,
+    pub changes: Vec<String>,
+}
+
+/// The current TODO file and the git repository it is tracked in
+pub struct TrackedFile {
+    pub path: PathBuf,
+    pub repo: PathBuf,
+    /// Path of the TODO file relative to the repository root
+    pub relative_path: PathBuf,
+}
+
+impl TrackedFile {
+    pub async fn current() -> Result<Self> {
+        Self::for_path(find_todo_file().await?)
+    }
+
+    pub fn for_path(path: PathBuf) -> Result<Self> {
+        let repo = repo_root(&path).ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} is not in a git repository, so it has no history",
+                path.display()
+            )
+        })?;
+        let relative_path = path
+            .strip_prefix(&repo)
+            .context("TODO file is outside its git repository")?
+            .to_path_buf();
+        Ok(TrackedFile {
+            path,
+            repo,
+            relative_path,
+        })
+    }
+
+    /// Resolves a commit (hash, `HEAD~2`, branch) or a date (`2026-09-01`,
+    /// `yesterday`, `7d`) to a commit; a date means the last commit touching
+    /// the TODO file by the end of that day
+    pub fn resolve_revision(&self, at: &str) -> Result<Revision> {
+        let repo = open_repo(&self.repo)?;
+        let commit = match resolve_commit(&repo, at) {
+            Some(commit) => commit,
+            None => {
+                let date = parse_since(at).map_err(|_| {
+                    anyhow::anyhow!(
+                        "'{}' is neither a commit nor a date (YYYY-MM-DD, yesterday, 7d, ...)",
+                        at
+                    )
+                })?;
+                let end_of_day = date.and_hms_opt(23, 59, 59).unwrap_or_default();
+                file_log(&repo, &self.relative_path)?
+                    .into_iter()
+                    .rfind(|commit| commit.timestamp.naive_local() <= end_of_day)
+                    .map(|commit| commit.id)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("The TODO file has no commits on or before {}", date)
+                    })?
+            }
+        };
+
+        let info = commit_info(&repo, commit)?;
+        let summary = format!(
+            "{} {} {}",
+            info.short_id,
+            info.timestamp.format("%Y-%m-%d"),
+            info.summary
+        );
+        Ok(Revision { commit, summary })
+    }
+
+    /// The TODO list as committed at `revision`
+    pub fn load_at(&self, revision: &Revision) -> Result<TodoList> {
+        load_todos_at(
+            &open_repo(&self.repo)?,
+            revision.commit,
+            &self.relative_path,
+        )
+    }
+
+    /// The TODO list as of the last commit, empty if nothing is committed yet
+    pub fn load_head(&self) -> Result<TodoList> {
+        let repo = open_repo(&self.repo)?;
+        match head_commit(&repo)? {
+            Some(head) => load_todos_at(&repo, head, &self.relative_path),
+            None => Ok(TodoList::default()),
+        }
+    }
+
+    /// The TODO list in the working tree, including uncommitted changes
+    pub fn load_working(&self) -> Result<TodoList> {
+        Ok(open_backend(self.path.clone()).load()?.0)
+    }
+
+    /// Every change to one TODO across the file's commits, oldest first,
+    /// followed by any uncommitted change
+    pub fn todo_history(&self, id: u32) -> Result<Vec<HistoryEvent>> {
+        let repo = open_repo(&self.repo)?;
+
+        let mut events = Vec::new();
+        let mut previous: Option<Todo> = None;
+        for commit in file_log(&repo, &self.relative_path)? {
+            let todo_list = load_todos_at(&repo, commit.id, &self.relative_path)?;
+            let current = todo_list.get_todo(id).cloned();
+            let changes = describe_revision(previous.as_ref(), current.as_ref());
+            if !changes.is_empty() {
+                events.push(HistoryEvent {
+                    commit: Some(commit.short_id),
+                    timestamp: commit.timestamp,
+                    message: commit.summary,
+                    changes,
+                });
+            }
+            previous = current;
+        }
+
+        let current = self.load_working()?.get_todo(id).cloned();
+        let changes = describe_revision(previous.as_ref(), current.as_ref());
+        if !changes.is_empty() {
+            let modified = std::fs::metadata(&self.path)
+                .and_then(|metadata| metadata.modified())
+                .map(DateTime::<Local>::from)
+                .unwrap_or_else(|_| Local::now());
+            events.push(HistoryEvent {
+                commit: None,
+                timestamp: modified,
+                message: "Not committed yet".to_string(),
+                changes,
+            });
+        }
+
+        Ok(events)
+    }
+}
+
+/// A resolved commit in the data repository
+pub struct Revision {
+    pub commit: Oid,
+    /// Short hash, date and subject, e.g. `3b6f968 2026-10-01 Add launch`
+    pub summary: String,
+}
+
+/// Walks the git history of the TODO file and lists every change to one TODO, oldest first
+pub async fn todo_history(id: u32) -> Result<Vec<HistoryEvent>> {
+    TrackedFile::current().await?.todo_history(id)
+}
+
+/// How one TODO differs between two versions of the list
+#[derive(Debug, Clone)]
+pub enum TodoDiff {
+    Added(Todo),
+    Removed(Todo),
+    Changed {
+        todo: Todo,
+        previous: Box<Todo>,
+        changes: Vec<String>,
+    },
+}
+
+/// Per-TODO differences from `old` to `new`, in ID order
+pub fn diff_todo_lists(old: &TodoList, new: &TodoList) -> Vec<TodoDiff> {
+    let mut diffs: Vec<(u32, TodoDiff)> = Vec::new();
+    for old_todo in &old.todos {
+        match new.get_todo(old_todo.id) {
+            None => diffs.push((old_todo.id, TodoDiff::Removed(old_todo.clone()))),
+            Some(new_todo) => {
+                let changes = describe_field_changes(old_todo, new_todo);
+                if !changes.is_empty() {
+                    diffs.push((
+                        new_todo.id,
+                        TodoDiff::Changed {
+                            todo: new_todo.clone(),
+                            previous: Box::new(old_todo.clone()),
+                            changes,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+    for new_todo in &new.todos {
+        if old.get_todo(new_todo.id).is_none() {
+            diffs.push((new_todo.id, TodoDiff::Added(new_todo.clone())));
+        }
+    }
+
+    diffs.sort_by_key(|(id, _)| *id);
+    diffs.into_iter().map(|(_, diff)| diff).collect()
+}
+
+impl TodoList {
+    /// Puts a TODO back as it was in an older revision, replacing the current
+    /// version or re-inserting it if it was deleted
+    pub fn restore_todo(&mut self, todo: Todo) {
+        let id = todo.id;
+        match self.todos.iter().position(|existing| existing.id == id) {
+            Some(index) => self.todos[index] = todo,
+            None => {
+                let index = self
+                    .todos
+                    .iter()
+                    .position(|existing| existing.id > id)
+                    .unwrap_or(self.todos.len());
+                self.todos.insert(index, todo);
+            }
+        }
+        self.next_id = self.next_id.max(id + 1);
+        self.prune_dangling_links();
+    }
+
+    /// Replaces every TODO with an older revision's. `next_id` never moves
+    /// back, so IDs used since then are not handed out again
+    pub fn restore_all(&mut self, snapshot: &TodoList) {
+        self.todos = snapshot.todos.clone();
+        self.next_id = self.next_id.max(snapshot.next_id);
+    }
+}
+
+/// Uncommitted changes to the TODO file at `path`, compared with its last
+/// commit; empty if either version can't be read
+pub fn uncommitted_changes(path: &Path) -> Vec<TodoDiff> {
+    let diff = || -> Result<Vec<TodoDiff>> {
+        let tracked = TrackedFile::for_path(path.to_path_buf())?;
+        Ok(diff_todo_lists(
+            &tracked.load_head()?,
+            &tracked.load_working()?,
+        ))
+    };
+    diff().unwrap_or_default()
+}
+
+/// Commit message for committing `diffs`: [`diff_commit_message`], or a dated
+/// one when no TODO changed (only other files in the repository, say)
+pub fn changes_commit_message(diffs: &[TodoDiff]) -> String {
+    if diffs.is_empty() {
+        format!(
+            "Update TODOs - {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M")
+        )
+    } else {
+        diff_commit_message(diffs)
+    }
+}
+
+/// Commit message summarizing `diffs`: a one-line subject, then one line per TODO.
+///
+/// For example `Mark #3 done; #7 priority P2 → P0`, or
+/// `Update 6 TODOs (2 added, 3 changed, 1 deleted)` for larger changes.
+pub fn diff_commit_message(diffs: &[TodoDiff]) -> String {
+    let short: Vec<String> = diffs.iter().map(describe_diff).collect();
+    let subject = if short.len() <= 3 {
+        short.join("; ")
+    } else {
+        let count = |kind: fn(&TodoDiff) -> bool| diffs.iter().filter(|d| kind(d)).count();
+        let parts: Vec<String> = [
+            (count(|d| matches!(d, TodoDiff::Added(_))), "added"),
+            (count(|d| matches!(d, TodoDiff::Changed { .. })), "changed"),
+            (count(|d| matches!(d, TodoDiff::Removed(_))), "deleted"),
+        ]
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, label)| format!("{} {}", n, label))
+        .collect();
+        format!("Update {} TODOs ({})", diffs.len(), parts.join(", "))
+    };
+
+    let mut message = subject;
+    if diffs.len() > 1 {
+        message.push('\n');
+        for diff in diffs {
+            message.push('\n');
+            message.push_str(&match diff {
+                TodoDiff::Changed { todo, changes, .. } => {
+                    format!("#{} {}: {}", todo.id, todo.title, changes.join(", "))
+                }
+                other => describe_diff(other),
+            });
+        }
+    }
+    message
+}
+
+fn describe_diff(diff: &TodoDiff) -> String {
+    match diff {
+        TodoDiff::Added(todo) => format!("Add #{} \"{}\"", todo.id, todo.title),
+        TodoDiff::Removed(todo) => format!("Delete #{} \"{}\"", todo.id, todo.title),
+        TodoDiff::Changed {
+            todo,
+            previous,
+            changes,
+        } => match todo.status {
+            Status::Done if changes.iter().any(|c| c.starts_with("status")) => {
+                format!("Mark #{} done", todo.id)
+            }
+            Status::Archived if changes.iter().any(|c| c.starts_with("status")) => {
+                format!("Mark #{} archived", todo.id)
+            }
+            _ if changes.len() == 1 && todo.priority != previous.priority => {
+                let verb = if todo.priority_value() < previous.priority_value() {
+                    "Bump"
+                } else {
+                    "Lower"
+                };
+                format!("{} #{} to {}", verb, todo.id, todo.priority)
+            }
+            _ => format!("#{} {}", todo.id, changes.join(", ")),
+        },
+    }
+}
+
+fn describe_revision(previous: Option<&Todo>, current: Option<&Todo>) -> Vec<String> {
+    match (previous, current) {
+        (None, Some(todo)) => vec![format!(
+            "created \"{}\" ({}, {})",
+            todo.title, todo.priority, todo.status
+        )],
+        (Some(_), None) => vec!["deleted".to_string()],
+        (Some(old), Some(new)) => describe_field_changes(old, new),
+        (None, None) => Vec::new(),
+    }
+}
+
+/// Human-readable changes between two versions of a TODO, e.g. `priority P2 → P0`
+pub fn describe_field_changes(old: &Todo, new: &Todo) -> Vec<String> {
+    let mut changes = Vec::new();
+    if old.title != new.title {
+        changes.push(format!("title \"{}\" → \"{}\"", old.title, new.title));
+    }
+    if old.status != new.status {
+        changes.push(format!("status {} → {}", old.status, new.status));
+    }
+    if old.priority != new.priority {
+        changes.push(format!("priority {} → {}", old.priority, new.priority));
+    }
+    if old.tags != new.tags {
+        let added = new.tags.iter().filter(|tag| !old.tags.contains(tag));
+        let removed = old.tags.iter().filter(|tag| !new.tags.contains(tag));
+        let diff: Vec<String> = added
+            .map(|tag| format!("+{}", tag))
+            .chain(removed.map(|tag| format!("-{}", tag)))
+            .collect();
+        changes.push(format!("tags {}", diff.join(" ")).trim_end().to_string());
+    }
+    if old.category != new.category {
+        changes.push(format!(
+            "category {} → {}",
+            or_none(old.category.as_deref()),
+            or_none(new.category.as_deref())
+        ));
+    }
+    if old.project != new.project {
+        changes.push(format!(
+            "project {} → {}",
+            or_none(old.project.as_deref()),
+            or_none(new.project.as_deref())
+        ));
+    }
+    if old.due_date != new.due_date || old.due_time != new.due_time {
+        changes.push(format!(
+            "due {} → {}",
+            or_none(old.format_due().as_deref()),
+            or_none(new.format_due().as_deref())
+        ));
+    }
+    if old.recurrence != new.recurrence {
+        let describe = |todo: &Todo| todo.recurrence.as_ref().map(|r| r.to_string());
+        changes.push(format!(
+            "repeat {} → {}",
+            or_none(describe(old).as_deref()),
+            or_none(describe(new).as_deref())
+        ));
+    }
+    if old.parent_id != new.parent_id {
+        let describe = |todo: &Todo| todo.parent_id.map(|id| format!("#{}", id));
+        changes.push(format!(
+            "parent {} → {}",
+            or_none(describe(old).as_deref()),
+            or_none(describe(new).as_deref())
+        ));
+    }
+    if old.blocked_by != new.blocked_by {
+        let added = new
+            .blocked_by
+            .iter()
+            .filter(|id| !old.blocked_by.contains(id));
+        let removed = old
+            .blocked_by
+            .iter()
+            .filter(|id| !new.blocked_by.contains(id));
+        let diff: Vec<String> = added
+            .map(|id| format!("+#{}", id))
+            .chain(removed.map(|id| format!("-#{}", id)))
+            .collect();
+        changes.push(
+            format!("blocked by {}", diff.join(" "))
+                .trim_end()
+                .to_string(),
+        );
+    }
+    if old.time_entries != new.time_entries {
+        changes.push("time tracked".to_string());
+    }
+    if old.notes != new.notes {
+        changes.push(
+            match (&old.notes, &new.notes) {
+                (None, Some(_)) => "notes added",
+                (Some(_), None) => "notes removed",
+                _ => "notes edited",
+            }
+            .to_string(),
+        );
+    }
+    changes
+}
+
+fn or_none(value: Option<&str>) -> &str {
+    value.unwrap_or("none")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{bare_remote, commit_all_at, working_repo, write_file};
+    use crate::core::{FileFormat, Priority};
+    use chrono::TimeZone;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn noon(year: i32, month: u32, day: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(year, month, day, 12, 0, 0)
+            .single()
+            .unwrap()
+    }
+
+    fn save(repo: &Repository, todo_list: &TodoList) {
+        let yaml = FileFormat::Yaml.serialize(todo_list).unwrap();
+        write_file(repo, "todo.yaml", &yaml);
+    }
+
+    /// A repository where #1 is added on Sep 1, bumped to P0 on Sep 3 and
+    /// finished on Sep 5, while #2 only appears on Sep 3
+    fn tracked_history(work: &TempDir) -> (TrackedFile, Vec<Oid>) {
+        let (remote_dir, _remote) = bare_remote("main");
+        let repo = working_repo(work.path(), "main", remote_dir.path());
+        let mut todo_list = TodoList::new();
+        let mut commits = Vec::new();
+
+        let todo = todo_list.create_todo("Write docs".to_string());
+        todo_list.add_todo(todo);
+        save(&repo, &todo_list);
+        commits.push(commit_all_at(&repo, "Add docs", noon(2026, 9, 1)));
+
+        todo_list.get_todo_mut(1).unwrap().priority = Priority::P0;
+        let todo = todo_list.create_todo("Review docs".to_string());
+        todo_list.add_todo(todo);
+        save(&repo, &todo_list);
+        commits.push(commit_all_at(&repo, "Bump docs", noon(2026, 9, 3)));
+
+        todo_list.set_status(1, Status::Done).unwrap();
+        save(&repo, &todo_list);
+        commits.push(commit_all_at(&repo, "Finish docs", noon(2026, 9, 5)));
+
+        let tracked = TrackedFile::for_path(work.path().join("todo.yaml")).unwrap();
+        (tracked, commits)
+    }
+
+    #[test]
+    fn revisions_resolve_by_commit_or_by_date() {
+        let work = TempDir::new().unwrap();
+        let (tracked, commits) = tracked_history(&work);
+
+        let by_hash = tracked.resolve_revision(&commits[1].to_string()).unwrap();
+        assert_eq!(by_hash.commit, commits[1]);
+        assert!(by_hash.summary.ends_with("2026-09-03 Bump docs"));
+        assert_eq!(
+            tracked.resolve_revision("HEAD~2").unwrap().commit,
+            commits[0]
+        );
+
+        // A date picks the last commit made by the end of that day
+        assert_eq!(
+            tracked.resolve_revision("2026-09-03").unwrap().commit,
+            commits[1]
+        );
+        assert_eq!(
+            tracked.resolve_revision("2026-09-04").unwrap().commit,
+            commits[1]
+        );
+        assert_eq!(
+            tracked.resolve_revision("2026-10-01").unwrap().commit,
+            commits[2]
+        );
+        assert!(tracked.resolve_revision("2026-08-31").is_err());
+        assert!(tracked.resolve_revision("not a revision").is_err());
+
+        let old = tracked.load_at(&tracked.resolve_revision("2026-09-02").unwrap());
+        assert_eq!(old.unwrap().todos.len(), 1);
+    }
+
+    #[test]
+    fn history_follows_one_todo_across_commits() {
+        let work = TempDir::new().unwrap();
+        let (tracked, _) = tracked_history(&work);
+
+        let events = tracked.todo_history(1).unwrap();
+        let messages: Vec<&str> = events.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["Add docs", "Bump docs", "Finish docs"]);
+        assert!(events.iter().all(|event| event.commit.is_some()));
+        assert_eq!(events[0].changes, ["created \"Write docs\" (P2, Todo)"]);
+        assert_eq!(events[1].changes, ["priority P2 → P0"]);
+        assert_eq!(events[2].changes[0], "status Todo → Done");
+
+        // #2 didn't change in the last commit, so it has a single event
+        let events = tracked.todo_history(2).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message, "Bump docs");
+
+        // Uncommitted edits come last
+        let mut todo_list = tracked.load_working().unwrap();
+        todo_list.delete_todo(2).unwrap();
+        std::fs::write(
+            &tracked.path,
+            FileFormat::Yaml.serialize(&todo_list).unwrap(),
+        )
+        .unwrap();
+        let events = tracked.todo_history(2).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].commit, None);
+        assert_eq!(events[1].changes, ["deleted"]);
+    }
+
+    #[test]
+    fn restoring_one_todo_puts_back_its_old_version() {
+        let work = TempDir::new().unwrap();
+        let (tracked, _) = tracked_history(&work);
+        let old = tracked
+            .load_at(&tracked.resolve_revision("2026-09-03").unwrap())
+            .unwrap();
+        let mut todo_list = tracked.load_working().unwrap();
+
+        todo_list.restore_todo(old.get_todo(1).unwrap().clone());
+        let restored = todo_list.get_todo(1).unwrap();
+        assert_eq!(restored.status, Status::Todo);
+        assert_eq!(restored.priority, Priority::P0);
+        assert_eq!(todo_list.todos.len(), 2);
+
+        // A TODO deleted since then comes back in ID order
+        todo_list.delete_todo(1).unwrap();
+        todo_list.restore_todo(old.get_todo(1).unwrap().clone());
+        let ids: Vec<u32> = todo_list.todos.iter().map(|todo| todo.id).collect();
+        assert_eq!(ids, [1, 2]);
+        assert_eq!(todo_list.next_id, 3);
+    }
+
+    #[test]
+    fn restoring_everything_never_reuses_ids() {
+        let work = TempDir::new().unwrap();
+        let (tracked, _) = tracked_history(&work);
+        let old = tracked
+            .load_at(&tracked.resolve_revision("2026-09-01").unwrap())
+            .unwrap();
+        let mut todo_list = tracked.load_working().unwrap();
+
+        todo_list.restore_all(&old);
+        assert_eq!(todo_list.todos, old.todos);
+        let max_id = todo_list.todos.iter().map(|todo| todo.id).max().unwrap();
+        assert!(todo_list.next_id > max_id);
+        // #2 existed after the snapshot, so its ID is not handed out again
+        assert_eq!(todo_list.next_id, 3);
+        assert_eq!(todo_list.create_todo("Ship docs".to_string()).id, 3);
+    }
+
+    fn lists() -> (TodoList, TodoList) {
+        let mut old = TodoList::new();
+        for title in ["Write docs", "Fix login", "Old idea"] {
+            let todo = old.create_todo(title.to_string());
+            old.add_todo(todo);
+        }
+        let mut new = old.clone();
+        new.get_todo_mut(1).unwrap().priority = Priority::P0;
+        new.delete_todo(3).unwrap();
+        let todo = new.create_todo("Ship it".to_string());
+        new.add_todo(todo);
+        (old, new)
+    }
+
+    #[test]
+    fn diffs_classify_added_changed_and_removed_todos() {
+        let (old, new) = lists();
+        let diffs = diff_todo_lists(&old, &new);
+        assert_eq!(diffs.len(), 3);
+        assert!(matches!(&diffs[0], TodoDiff::Changed { todo, .. } if todo.id == 1));
+        assert!(matches!(&diffs[1], TodoDiff::Removed(todo) if todo.id == 3));
+        assert!(matches!(&diffs[2], TodoDiff::Added(todo) if todo.id == 4));
+        assert!(diff_todo_lists(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn field_changes_are_described() {
+        let old = Todo::new("Write docs".to_string());
+        let mut new = old.clone();
+        new.title = "Write the docs".to_string();
+        new.priority = Priority::P0;
+        new.tags = vec!["docs".to_string()];
+        new.parent_id = Some(4);
+        new.blocked_by = vec![2];
+        new.notes = Some("Start with the README".to_string());
+        assert_eq!(
+            describe_field_changes(&old, &new),
+            [
+                "title \"Write docs\" → \"Write the docs\"",
+                "priority P2 → P0",
+                "tags +docs",
+                "parent none → #4",
+                "blocked by +#2",
+                "notes added",
+            ]
+        );
+        assert!(describe_field_changes(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn commit_messages_summarize_larger_diffs() {
+        let (old, mut new) = lists();
+        let message = diff_commit_message(&diff_todo_lists(&old, &new));
+        let subject = message.lines().next().unwrap();
+        assert_eq!(
+            subject,
+            "Bump #1 to P0; Delete #3 \"Old idea\"; Add #4 \"Ship it\""
+        );
+
+        new.get_todo_mut(2).unwrap().title = "Fix the login".to_string();
+        let message = diff_commit_message(&diff_todo_lists(&old, &new));
+        let lines: Vec<&str> = message.lines().collect();
+        assert_eq!(lines[0], "Update 4 TODOs (1 added, 2 changed, 1 deleted)");
+        assert_eq!(lines[1], "");
+        assert_eq!(lines[2], "#1 Write docs: priority P2 → P0");
+        assert_eq!(lines.len(), 6);
+
+        // A single change is just the subject
+        let mut one = old.clone();
+        one.set_status(2, Status::Done).unwrap();
+        let message = diff_commit_message(&diff_todo_lists(&old, &one));
+        assert_eq!(message, "Mark #2 done");
+    }
+}
@@ -0,0 +1,412 @@
+use crate::core::{
+    auto_commit, current_backend, describe_field_changes, repo_root, write_atomically, Todo,
+    TodoList,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Number of operations kept for undo
+const MAX_JOURNAL_ENTRIES: usize = 200;
+
+/// Git exclude pattern matching every journal file
+const JOURNAL_PATTERN: &str = ".*.journal";
+
+/// One TODO's state before and after an operation; `None` means it did not exist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoChange {
+    pub id: u32,
+    pub before: Option<Todo>,
+    pub after: Option<Todo>,
+    /// Position in the list before/after, so undo and redo restore the exact order
+    pub index_before: Option<usize>,
+    pub index_after: Option<usize>,
+}
+
+/// A saved mutation of the TODO list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Local>,
+    pub description: String,
+    pub next_id_before: u32,
+    pub next_id_after: u32,
+    pub changes: Vec<TodoChange>,
+}
+
+/// Operation log stored next to the data file.
+///
+/// `entries[..position]` can be undone and `entries[position..]` redone; a
+/// new operation discards anything that could still be redone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    pub entries: Vec<JournalEntry>,
+    pub position: usize,
+}
+
+impl Journal {
+    /// `todo.yaml` is journaled in `.todo.yaml.journal` in the same directory
+    pub fn path_for(data_path: &Path) -> PathBuf {
+        let file_name = data_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "todo".to_string());
+        data_path.with_file_name(format!(".{}.journal", file_name))
+    }
+
+    pub fn load(data_path: &Path) -> Result<Journal> {
+        let path = Self::path_for(data_path);
+        if !path.exists() {
+            return Ok(Journal::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read journal: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse journal: {}", path.display()))
+    }
+
+    pub fn save(&self, data_path: &Path) -> Result<()> {
+        let content = serde_json::to_string(self).context("Failed to serialize journal")?;
+        write_atomically(&Self::path_for(data_path), content.as_bytes())?;
+        exclude_from_git(data_path)
+    }
+
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.entries.truncate(self.position);
+        self.entries.push(entry);
+        if self.entries.len() > MAX_JOURNAL_ENTRIES {
+            let excess = self.entries.len() - MAX_JOURNAL_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.position = self.entries.len();
+    }
+}
+
+/// The journal is local to this machine, so keep `git add .` in the data
+/// repository from pushing it
+fn exclude_from_git(data_path: &Path) -> Result<()> {
+    let Some(git_dir) = repo_root(data_path).map(|root| root.join(".git")) else {
+        return Ok(());
+    };
+    if !git_dir.is_dir() {
+        return Ok(());
+    }
+    let info_dir = git_dir.join("info");
+
+    let exclude_path = info_dir.join("exclude");
+    let existing = std::fs::read_to_string(&exclude_path).unwrap_or_default();
+    if existing.lines().any(|line| line.trim() == JOURNAL_PATTERN) {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&info_dir)?;
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(JOURNAL_PATTERN);
+    content.push('\n');
+    std::fs::write(&exclude_path, content)
+        .with_context(|| format!("Failed to update {}", exclude_path.display()))
+}
+
+/// Compares the list as loaded with its current state, returning `None` if nothing changed
+pub fn diff_lists(before_next_id: u32, before: &[Todo], after: &TodoList) -> Option<JournalEntry> {
+    let mut changes = Vec::new();
+
+    for (index_before, old) in before.iter().enumerate() {
+        let found = after
+            .todos
+            .iter()
+            .enumerate()
+            .find(|(_, todo)| todo.id == old.id);
+        match found {
+            Some((index_after, new)) if new == old && index_after == index_before => {}
+            Some((index_after, new)) => changes.push(TodoChange {
+                id: old.id,
+                before: Some(old.clone()),
+                after: Some(new.clone()),
+                index_before: Some(index_before),
+                index_after: Some(index_after),
+            }),
+            None => changes.push(TodoChange {
+                id: old.id,
+                before: Some(old.clone()),
+                after: None,
+                index_before: Some(index_before),
+                index_after: None,
+            }),
+        }
+    }
+
+    let before_ids: HashSet<u32> = before.iter().map(|todo| todo.id).collect();
+    for (index_after, new) in after.todos.iter().enumerate() {
+        if !before_ids.contains(&new.id) {
+            changes.push(TodoChange {
+                id: new.id,
+                before: None,
+                after: Some(new.clone()),
+                index_before: None,
+                index_after: Some(index_after),
+            });
+        }
+    }
+
+    // Pure reorders (e.g. a deletion shifting later TODOs up) are not worth reporting
+    let meaningful = changes.iter().any(|change| change.before != change.after);
+    if !meaningful && before_next_id == after.next_id {
+        return None;
+    }
+
+    Some(JournalEntry {
+        timestamp: Local::now(),
+        description: describe_changes(&changes),
+        next_id_before: before_next_id,
+        next_id_after: after.next_id,
+        changes,
+    })
+}
+
+/// Summarizes an operation, e.g. `Update #4 "Fix login" (status Todo → Done)`
+fn describe_changes(changes: &[TodoChange]) -> String {
+    let parts: Vec<String> = changes
+        .iter()
+        .filter(|change| change.before != change.after)
+        .map(|change| match (&change.before, &change.after) {
+            (None, Some(new)) => format!("add #{} \"{}\"", new.id, new.title),
+            (Some(old), None) => format!("delete #{} \"{}\"", old.id, old.title),
+            (Some(old), Some(new)) => {
+                let mut fields = describe_field_changes(old, new);
+                if fields.is_empty() {
+                    fields.push("moved".to_string());
+                }
+                format!(
+                    "update #{} \"{}\" ({})",
+                    new.id,
+                    new.title,
+                    fields.join(", ")
+                )
+            }
+            (None, None) => String::new(),
+        })
+        .collect();
+
+    let summary = match parts.len() {
+        0 => "reorder TODOs".to_string(),
+        1..=3 => parts.join("; "),
+        n => {
+            let deleted = changes.iter().filter(|c| c.after.is_none()).count();
+            let added = changes.iter().filter(|c| c.before.is_none()).count();
+            if deleted == n {
+                format!("delete {} TODOs ({})", n, id_list(changes))
+            } else if added == n {
+                format!("add {} TODOs ({})", n, id_list(changes))
+            } else {
+                format!("{}; and {} more changes", parts[..2].join("; "), n - 2)
+            }
+        }
+    };
+
+    let mut chars = summary.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => summary,
+    }
+}
+
+fn id_list(changes: &[TodoChange]) -> String {
+    let ids: Vec<String> = changes
+        .iter()
+        .filter(|c| c.before != c.after)
+        .map(|c| format!("#{}", c.id))
+        .collect();
+    if ids.len() > 8 {
+        format!("{}, ...", ids[..8].join(", "))
+    } else {
+        ids.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+/// Reverts the most recent operation, returning its description, or `None` if there is nothing to undo
+pub async fn undo_last() -> Result<Option<String>> {
+    step(Direction::Undo).await
+}
+
+/// Re-applies the most recently undone operation
+pub async fn redo_next() -> Result<Option<String>> {
+    step(Direction::Redo).await
+}
+
+async fn step(direction: Direction) -> Result<Option<String>> {
+    let backend = current_backend().await?;
+    let data_path = backend.path().to_path_buf();
+    let mut journal = Journal::load(&data_path)?;
+
+    let index = match direction {
+        Direction::Undo if journal.position > 0 => journal.position - 1,
+        Direction::Redo if journal.position < journal.entries.len() => journal.position,
+        _ => return Ok(None),
+    };
+    let entry = journal.entries[index].clone();
+
+    let (mut todo_list, mut stamp) = backend.load()?;
+    apply_entry(&mut todo_list, &entry, direction)?;
+
+    // Saved straight through the backend so the undo itself is not journaled
+    backend.save(&todo_list, &mut stamp)?;
+
+    journal.position = match direction {
+        Direction::Undo => index,
+        Direction::Redo => index + 1,
+    };
+    journal.save(&data_path)?;
+
+    let verb = match direction {
+        Direction::Undo => "Undo",
+        Direction::Redo => "Redo",
+    };
+    auto_commit(&data_path, &format!("{}: {}", verb, entry.description))?;
+
+    Ok(Some(entry.description))
+}
+
+/// Moves every TODO touched by `entry` to its state on the other side of the operation
+fn apply_entry(todo_list: &mut TodoList, entry: &JournalEntry, direction: Direction) -> Result<()> {
+    // Refuse to clobber TODOs that changed after the operation was recorded
+    for change in &entry.changes {
+        let (expected, _) = sides(change, direction);
+        let current = todo_list.todos.iter().find(|todo| todo.id == change.id);
+        if current != expected.0 {
+            let verb = match direction {
+                Direction::Undo => "undo",
+                Direction::Redo => "redo",
+            };
+            anyhow::bail!(
+                "Cannot {} \"{}\": TODO #{} has changed since then",
+                verb,
+                entry.description,
+                change.id
+            );
+        }
+    }
+
+    let touched: HashSet<u32> = entry.changes.iter().map(|change| change.id).collect();
+    todo_list.todos.retain(|todo| !touched.contains(&todo.id));
+
+    let mut restored: Vec<(usize, Todo)> = entry
+        .changes
+        .iter()
+        .filter_map(|change| {
+            let (_, (target, index)) = sides(change, direction);
+            Some((index?, target?.clone()))
+        })
+        .collect();
+    restored.sort_by_key(|(index, _)| *index);
+    for (index, todo) in restored {
+        let index = index.min(todo_list.todos.len());
+        todo_list.todos.insert(index, todo);
+    }
+
+    // TODOs added outside the journal since then (by a sync, say) keep
+    // their IDs from being handed out again
+    let next_id = match direction {
+        Direction::Undo => entry.next_id_before,
+        Direction::Redo => entry.next_id_after,
+    };
+    let max_id = todo_list.todos.iter().map(|todo| todo.id).max().unwrap_or(0);
+    todo_list.next_id = next_id.max(max_id + 1);
+    Ok(())
+}
+
+type Side<'a> = (Option<&'a Todo>, Option<usize>);
+
+/// The (current, target) states of a change when stepping in `direction`
+fn sides(change: &TodoChange, direction: Direction) -> (Side<'_>, Side<'_>) {
+    let before = (change.before.as_ref(), change.index_before);
+    let after = (change.after.as_ref(), change.index_after);
+    match direction {
+        Direction::Undo => (after, before),
+        Direction::Redo => (before, after),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adds a TODO through the journal, as `todo add` would
+    fn journaled_add(todo_list: &mut TodoList, title: &str) -> JournalEntry {
+        let before = (todo_list.next_id, todo_list.todos.clone());
+        let todo = todo_list.create_todo(title.to_string());
+        todo_list.add_todo(todo);
+        diff_lists(before.0, &before.1, todo_list).unwrap()
+    }
+
+    /// Adds a TODO without a journal entry, as a sync merge would
+    fn unjournaled_add(todo_list: &mut TodoList, title: &str) {
+        let todo = todo_list.create_todo(title.to_string());
+        todo_list.add_todo(todo);
+    }
+
+    fn ids(todo_list: &TodoList) -> Vec<u32> {
+        todo_list.todos.iter().map(|todo| todo.id).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_list() {
+        let mut todo_list = TodoList::new();
+        let entry = journaled_add(&mut todo_list, "Write tests");
+
+        apply_entry(&mut todo_list, &entry, Direction::Undo).unwrap();
+        assert!(todo_list.todos.is_empty());
+        assert_eq!(todo_list.next_id, 1);
+
+        apply_entry(&mut todo_list, &entry, Direction::Redo).unwrap();
+        assert_eq!(ids(&todo_list), [1]);
+        assert_eq!(todo_list.next_id, 2);
+    }
+
+    #[test]
+    fn undo_after_an_unjournaled_add_keeps_its_id_in_use() {
+        let mut todo_list = TodoList::new();
+        let entry = journaled_add(&mut todo_list, "Journaled");
+        unjournaled_add(&mut todo_list, "From sync");
+        assert_eq!(ids(&todo_list), [1, 2]);
+
+        apply_entry(&mut todo_list, &entry, Direction::Undo).unwrap();
+        assert_eq!(ids(&todo_list), [2]);
+        let todo = todo_list.create_todo("Added after undo".to_string());
+        assert_eq!(todo.id, 3);
+        todo_list.add_todo(todo);
+
+        // The undone TODO's ID is free again, so redo can bring it back
+        apply_entry(&mut todo_list, &entry, Direction::Redo).unwrap();
+        assert_eq!(ids(&todo_list), [1, 2, 3]);
+        assert_eq!(todo_list.next_id, 4);
+    }
+
+    #[test]
+    fn redo_after_an_unjournaled_add_keeps_its_id_in_use() {
+        let mut todo_list = TodoList::new();
+        unjournaled_add(&mut todo_list, "Existing");
+        let before = (todo_list.next_id, todo_list.todos.clone());
+        todo_list.todos[0].title = "Renamed".to_string();
+        let entry = diff_lists(before.0, &before.1, &todo_list).unwrap();
+
+        apply_entry(&mut todo_list, &entry, Direction::Undo).unwrap();
+        unjournaled_add(&mut todo_list, "From sync");
+        apply_entry(&mut todo_list, &entry, Direction::Redo).unwrap();
+        assert_eq!(todo_list.todos[0].title, "Renamed");
+        assert_eq!(ids(&todo_list), [1, 2]);
+
+        let todo = todo_list.create_todo("Added after redo".to_string());
+        assert_eq!(todo.id, 3);
+    }
+}
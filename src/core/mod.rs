@@ -1,9 +1,25 @@
+pub mod backend;
 pub mod filters;
 pub mod git;
+pub mod history;
+pub mod journal;
+pub mod recurrence;
 pub mod storage;
+pub mod sync;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod timetrack;
 pub mod todo;
+pub mod views;
 
+pub use backend::*;
 pub use filters::*;
 pub use git::*;
+pub use history::*;
+pub use journal::*;
+pub use recurrence::*;
 pub use storage::*;
+pub use sync::*;
+pub use timetrack::*;
 pub use todo::*;
+pub use views::*;
@@ -0,0 +1,254 @@
+use anyhow::Result;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Rule describing when the next instance of a recurring TODO is due.
+///
+/// Stored in the YAML file in the same short form accepted on the command
+/// line, e.g. `daily`, `weekly:mon,thu`, `monthly:15` or `after:3d`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Recurrence {
+    Daily,
+    /// On the given weekdays; empty means the weekday of the previous due date
+    Weekly {
+        weekdays: Vec<Weekday>,
+    },
+    /// On day N of each month (clamped to short months); `None` keeps the previous day
+    Monthly {
+        day: Option<u32>,
+    },
+    /// N days after the previous instance was completed
+    AfterCompletion {
+        days: u32,
+    },
+}
+
+impl Recurrence {
+    /// Computes the due date of the next instance.
+    ///
+    /// Calendar rules pick the first matching date after the later of the
+    /// previous due date and the completion date, so finishing early or late
+    /// never schedules an instance in the past. Fails if that date would be
+    /// past the end of the calendar.
+    pub fn next_due(&self, due_date: Option<NaiveDate>, completed: NaiveDate) -> Result<NaiveDate> {
+        let anchor = due_date.map_or(completed, |due| due.max(completed));
+        let after = |date: NaiveDate, days: u32| date.checked_add_days(Days::new(days.into()));
+
+        let next = match self {
+            Recurrence::Daily => after(anchor, 1),
+            Recurrence::Weekly { weekdays } => {
+                let reference = due_date.unwrap_or(completed).weekday();
+                (1..=7)
+                    .map_while(|offset| after(anchor, offset))
+                    .find(|date| {
+                        if weekdays.is_empty() {
+                            date.weekday() == reference
+                        } else {
+                            weekdays.contains(&date.weekday())
+                        }
+                    })
+            }
+            Recurrence::Monthly { day } => {
+                let day = day.unwrap_or_else(|| due_date.unwrap_or(completed).day());
+                match month_day(anchor.year(), anchor.month(), day) {
+                    Some(this_month) if this_month > anchor => Some(this_month),
+                    _ if anchor.month() == 12 => month_day(anchor.year() + 1, 1, day),
+                    _ => month_day(anchor.year(), anchor.month() + 1, day),
+                }
+            }
+            Recurrence::AfterCompletion { days } => after(completed, *days),
+        };
+        next.ok_or_else(|| {
+            anyhow::anyhow!(
+                "The next \"{}\" date after {} is past the end of the calendar",
+                self,
+                anchor
+            )
+        })
+    }
+}
+
+/// Returns day `day` of the given month, clamped to the month's last day, or
+/// `None` if the year is outside the calendar
+fn month_day(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    (1..=day)
+        .rev()
+        .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly { weekdays } if weekdays.is_empty() => write!(f, "weekly"),
+            Recurrence::Weekly { weekdays } => {
+                let days: Vec<String> = weekdays
+                    .iter()
+                    .map(|day| day.to_string().to_lowercase())
+                    .collect();
+                write!(f, "weekly:{}", days.join(","))
+            }
+            Recurrence::Monthly { day: None } => write!(f, "monthly"),
+            Recurrence::Monthly { day: Some(day) } => write!(f, "monthly:{}", day),
+            Recurrence::AfterCompletion { days } => write!(f, "after:{}d", days),
+        }
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string()
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        parse_recurrence(&value)
+    }
+}
+
+/// Longest `after:` interval, so the next due date always stays on the calendar
+const MAX_AFTER_DAYS: u32 = 3650;
+
+/// Parses a recurrence rule: `daily`, `weekly[:mon,thu]`, `monthly[:15]` or `after:3d`
+pub fn parse_recurrence(recurrence_str: &str) -> Result<Recurrence> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid recurrence: {}. Valid values: daily, weekly[:mon,thu], monthly[:15], after:3d",
+            recurrence_str
+        )
+    };
+
+    let lower = recurrence_str.trim().to_lowercase();
+    let (kind, arg) = match lower.split_once(':') {
+        Some((kind, arg)) => (kind, Some(arg.trim())),
+        None => (lower.as_str(), None),
+    };
+
+    match (kind, arg) {
+        ("daily", None) => Ok(Recurrence::Daily),
+        ("weekly", None) => Ok(Recurrence::Weekly {
+            weekdays: Vec::new(),
+        }),
+        ("weekly", Some(days)) => {
+            let mut weekdays = Vec::new();
+            for day in days.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+                let weekday: Weekday = day.parse().map_err(|_| invalid())?;
+                if !weekdays.contains(&weekday) {
+                    weekdays.push(weekday);
+                }
+            }
+            weekdays.sort_by_key(|day| day.num_days_from_monday());
+            Ok(Recurrence::Weekly { weekdays })
+        }
+        ("monthly", None) => Ok(Recurrence::Monthly { day: None }),
+        ("monthly", Some(day)) => match day.parse::<u32>() {
+            Ok(day) if (1..=31).contains(&day) => Ok(Recurrence::Monthly { day: Some(day) }),
+            _ => Err(invalid()),
+        },
+        ("after", Some(days)) => match days.trim_end_matches('d').parse::<u32>() {
+            Ok(days) if days > MAX_AFTER_DAYS => Err(anyhow::anyhow!(
+                "Invalid recurrence: {}. Repeat at most every {} days",
+                recurrence_str,
+                MAX_AFTER_DAYS
+            )),
+            Ok(days) if days > 0 => Ok(Recurrence::AfterCompletion { days }),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Status, TodoList};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn rule(recurrence: &str) -> Recurrence {
+        parse_recurrence(recurrence).unwrap()
+    }
+
+    #[test]
+    fn after_completion_is_bounded() {
+        assert_eq!(
+            parse_recurrence("after:3650d").unwrap(),
+            Recurrence::AfterCompletion { days: 3650 }
+        );
+        assert!(parse_recurrence("after:3651d").is_err());
+        assert!(parse_recurrence("after:100000000d").is_err());
+        assert!(parse_recurrence("after:0d").is_err());
+
+        let completed = date(2026, 10, 16);
+        let next = Recurrence::AfterCompletion { days: 3650 }.next_due(None, completed);
+        assert_eq!(next.unwrap(), completed + Days::new(3650));
+    }
+
+    #[test]
+    fn calendar_rules_pick_the_next_matching_date() {
+        // Friday 2026-10-16, finished on the day it was due
+        let due = Some(date(2026, 10, 16));
+        let next = |recurrence: &str| rule(recurrence).next_due(due, date(2026, 10, 16)).unwrap();
+        assert_eq!(next("daily"), date(2026, 10, 17));
+        assert_eq!(next("weekly"), date(2026, 10, 23));
+        assert_eq!(next("weekly:mon,thu"), date(2026, 10, 19));
+        assert_eq!(next("monthly"), date(2026, 11, 16));
+        assert_eq!(next("monthly:20"), date(2026, 10, 20));
+        assert_eq!(next("after:3d"), date(2026, 10, 19));
+
+        // Finishing late never schedules the next instance in the past
+        let late = rule("daily").next_due(due, date(2026, 10, 20)).unwrap();
+        assert_eq!(late, date(2026, 10, 21));
+        let december = rule("monthly").next_due(Some(date(2026, 12, 5)), date(2026, 12, 5));
+        assert_eq!(december.unwrap(), date(2027, 1, 5));
+    }
+
+    #[test]
+    fn monthly_dates_clamp_to_short_months() {
+        let jan_31 = |year| rule("monthly").next_due(Some(date(year, 1, 31)), date(year, 1, 31));
+        assert_eq!(jan_31(2026).unwrap(), date(2026, 2, 28));
+        assert_eq!(jan_31(2028).unwrap(), date(2028, 2, 29));
+
+        // The rule keeps day 31 once the month is long enough again
+        let march = rule("monthly:31").next_due(Some(date(2026, 2, 28)), date(2026, 2, 28));
+        assert_eq!(march.unwrap(), date(2026, 3, 31));
+    }
+
+    #[test]
+    fn dates_past_the_end_of_the_calendar_are_an_error() {
+        for recurrence in ["daily", "weekly", "monthly", "after:3d"] {
+            let next = rule(recurrence).next_due(Some(NaiveDate::MAX), NaiveDate::MAX);
+            assert!(next.is_err(), "{}", recurrence);
+        }
+    }
+
+    #[test]
+    fn finishing_a_recurring_todo_creates_the_next_one() {
+        let mut todo_list = TodoList::new();
+        let mut todo = todo_list.create_todo("Water plants".to_string());
+        todo.due_date = Some(date(2026, 10, 16));
+        todo_list.add_todo(todo);
+        todo_list.set_recurrence(1, Some(rule("weekly"))).unwrap();
+
+        let next_id = todo_list.set_status(1, Status::Done).unwrap().unwrap();
+        let next = todo_list.get_todo(next_id).unwrap();
+        assert_eq!(next.title, "Water plants");
+        assert_eq!(next.status, Status::Todo);
+        assert!(next.due_date.unwrap() > date(2026, 10, 16));
+        assert_eq!(next.series_id, Some(1));
+        assert_eq!(todo_list.get_todo(1).unwrap().series_id, Some(1));
+
+        // The series continues from the new instance
+        let third = todo_list
+            .set_status(next_id, Status::Done)
+            .unwrap()
+            .unwrap();
+        assert_eq!(todo_list.get_todo(third).unwrap().series_id, Some(1));
+    }
+}
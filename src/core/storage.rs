@@ -1,23 +1,34 @@
-use crate::core::{Priority, Status, Todo, TodoList};
+use crate::config::load_settings;
+use crate::core::{
+    commit_file, diff_commit_message, diff_lists, diff_todo_lists, open_backend, open_repo,
+    Journal, LoadStamp, StorageBackend,
+};
+use crate::core::{DueStatus, Priority, Status, Todo, TodoList};
 use anyhow::{Context, Result};
 use dirs::home_dir;
-use std::path::PathBuf;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+/// File names checked for project-specific TODOs, in order
+const LOCAL_TODO_FILES: [&str; 8] = [
+    "TODO.yaml",
+    "TODO.yml",
+    "todo.yaml",
+    "todo.yml",
+    "TODO.json",
+    "todo.json",
+    "TODO.db",
+    "todo.db",
+];
 
 pub async fn find_todo_file() -> Result<PathBuf> {
     // 1. Check current directory for project-specific TODOs
-    let current_dir = std::env::current_dir()?;
-    let local_files = ["TODO.yaml", "TODO.yml", "todo.yaml", "todo.yml"];
-
-    for filename in &local_files {
-        let path = current_dir.join(filename);
-        if path.exists() {
-            return Ok(path);
-        }
+    if let Some(path) = find_local_todo_file(&std::env::current_dir()?) {
+        return Ok(path);
     }
 
-    // 2. Fall back to global guidebook data directory
-    let data_dir = get_data_dir()?;
-    let todo_path = data_dir.join("guidebook-todo").join("todo.yaml");
+    // 2. Fall back to global guidebook data directory, in the configured format
+    let todo_path = global_todo_file()?;
 
     // Create directory if it doesn't exist
     if let Some(parent) = todo_path.parent() {
@@ -27,52 +38,145 @@ pub async fn find_todo_file() -> Result<PathBuf> {
     Ok(todo_path)
 }
 
+/// The TODO file inside the guidebook data repository, in the configured format
+pub fn global_todo_file() -> Result<PathBuf> {
+    let settings = load_settings()?;
+    Ok(get_data_dir()?
+        .join("guidebook-todo")
+        .join(settings.storage.default_file_name()))
+}
+
+/// A project-specific TODO file in `dir`, if there is one
+pub fn find_local_todo_file(dir: &Path) -> Option<PathBuf> {
+    LOCAL_TODO_FILES
+        .iter()
+        .map(|filename| dir.join(filename))
+        .find(|path| path.exists())
+}
+
+/// The guidebook data directory: the `data_dir` setting, or `~/.local/share/guidebook`
 pub fn get_data_dir() -> Result<PathBuf> {
+    if let Some(data_dir) = load_settings()?.data_dir {
+        return Ok(data_dir);
+    }
     let home = home_dir().ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
 
     Ok(home.join(".local").join("share").join("guidebook"))
 }
 
-pub async fn load_todos() -> Result<TodoList> {
-    let path = find_todo_file().await?;
+/// With the `auto_commit` setting on, commits the global TODO file after a
+/// change. TODO files in project directories are left to their own repository.
+pub(crate) fn auto_commit(data_path: &Path, message: &str) -> Result<()> {
+    match auto_commit_repo()? {
+        Some(data_dir) => commit_data_file(&data_dir, data_path, message),
+        None => Ok(()),
+    }
+}
 
-    if !path.exists() {
-        // Create empty TODO list if file doesn't exist
-        let todo_list = TodoList::default();
-        save_todos(&todo_list, &path).await?;
-        return Ok(todo_list);
+/// The data repository, if the `auto_commit` setting is on and it exists
+fn auto_commit_repo() -> Result<Option<PathBuf>> {
+    if !load_settings()?.auto_commit {
+        return Ok(None);
     }
+    let data_dir = get_data_dir()?;
+    Ok(data_dir.join(".git").exists().then_some(data_dir))
+}
 
-    let content = std::fs::read_to_string(&path)
-        .with_context(|| format!("Failed to read TODO file: {}", path.display()))?;
+/// Commits `data_path` if it is inside the repository at `data_dir`
+fn commit_data_file(data_dir: &Path, data_path: &Path, message: &str) -> Result<()> {
+    let Ok(relative_path) = data_path.strip_prefix(data_dir) else {
+        return Ok(());
+    };
+    let repo = open_repo(data_dir)?;
+    commit_file(&repo, relative_path, message)
+        .context("Saved, but failed to commit the change to the data repository")?;
+    Ok(())
+}
 
-    if content.trim().is_empty() {
-        return Ok(TodoList::default());
-    }
+/// Opens the storage backend for the current TODO file
+pub async fn current_backend() -> Result<Box<dyn StorageBackend>> {
+    Ok(open_backend(find_todo_file().await?))
+}
 
-    serde_yaml::from_str(&content).context("Failed to parse TODO file")
+pub async fn load_todos() -> Result<LoadedList> {
+    LoadedList::load(current_backend().await?.as_ref())
 }
 
-pub async fn save_todos(todo_list: &TodoList, path: &PathBuf) -> Result<()> {
-    let content = serde_yaml::to_string(todo_list).context("Failed to serialize TODO list")?;
+pub async fn save_todos(todo_list: &TodoList, path: &Path) -> Result<()> {
+    open_backend(path.to_path_buf()).save(todo_list, &mut LoadStamp::Unloaded)
+}
 
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+/// A TODO list loaded from storage, with what is needed to save it back: the
+/// backend's [`LoadStamp`], and the list as of the last load or save, which is
+/// diffed on save to record the change in the undo journal
+pub struct LoadedList {
+    todo_list: TodoList,
+    stamp: LoadStamp,
+    baseline: TodoList,
+}
+
+impl LoadedList {
+    pub fn load(backend: &dyn StorageBackend) -> Result<Self> {
+        let (todo_list, stamp) = backend.load()?;
+        Ok(LoadedList {
+            baseline: todo_list.clone(),
+            todo_list,
+            stamp,
+        })
     }
 
-    std::fs::write(path, content)
-        .with_context(|| format!("Failed to write TODO file: {}", path.display()))?;
+    /// The list itself, for when it won't be saved
+    pub fn into_list(self) -> TodoList {
+        self.todo_list
+    }
 
-    Ok(())
+    /// Saves through the current backend and records what changed since the
+    /// list was loaded in the undo journal (and in git, with `auto_commit` on)
+    pub async fn save(&mut self) -> Result<()> {
+        let backend = current_backend().await?;
+        self.save_to(backend.as_ref(), auto_commit_repo()?.as_deref())
+    }
+
+    /// [`LoadedList::save`] through `backend`, committing to the repository
+    /// at `data_dir` when given
+    fn save_to(&mut self, backend: &dyn StorageBackend, data_dir: Option<&Path>) -> Result<()> {
+        backend.save(&self.todo_list, &mut self.stamp)?;
+
+        let before = std::mem::replace(&mut self.baseline, self.todo_list.clone());
+        if let Some(entry) = diff_lists(before.next_id, &before.todos, &self.todo_list) {
+            let diffs = diff_todo_lists(&before, &self.todo_list);
+            let message = if diffs.is_empty() {
+                entry.description.clone()
+            } else {
+                diff_commit_message(&diffs)
+            };
+
+            let mut journal = Journal::load(backend.path())?;
+            journal.record(entry);
+            journal.save(backend.path())?;
+            if let Some(data_dir) = data_dir {
+                commit_data_file(data_dir, backend.path(), &message)?;
+            }
+        }
+        Ok(())
+    }
 }
 
-impl TodoList {
-    pub async fn save(&self) -> Result<()> {
-        let path = find_todo_file().await?;
-        save_todos(self, &path).await
+impl Deref for LoadedList {
+    type Target = TodoList;
+
+    fn deref(&self) -> &TodoList {
+        &self.todo_list
+    }
+}
+
+impl DerefMut for LoadedList {
+    fn deref_mut(&mut self) -> &mut TodoList {
+        &mut self.todo_list
     }
+}
 
+impl TodoList {
     pub fn filter_todos(
         &self,
         status: Option<String>,
@@ -150,15 +254,28 @@ pub async fn default_display() -> Result<()> {
         return Ok(());
     }
 
-    // Sort by priority (P0 first) then by creation date (newest first)
-    let mut priority_todos = active_todos.clone();
-    priority_todos.sort_by(|a, b| match a.priority_value().cmp(&b.priority_value()) {
-        std::cmp::Ordering::Equal => b.created_date.cmp(&a.created_date),
-        other => other,
-    });
+    // Collect deadline sections, soonest first
+    let now = chrono::Local::now();
+    let mut due_todos: Vec<&Todo> = active_todos
+        .iter()
+        .copied()
+        .filter(|todo| todo.due_date.is_some())
+        .collect();
+    due_todos.sort_by_key(|todo| (todo.due_date, todo.due_time));
+    let due_with = |due_status: DueStatus| -> Vec<&Todo> {
+        due_todos
+            .iter()
+            .copied()
+            .filter(|todo| todo.due_status(now) == Some(due_status))
+            .collect()
+    };
+    let due_sections = [
+        ("Overdue", due_with(DueStatus::Overdue)),
+        ("Due Today", due_with(DueStatus::Today)),
+        ("Due This Week", due_with(DueStatus::ThisWeek)),
+    ];
 
-    // Take top 4 priority tasks
-    let top_priority: Vec<&Todo> = priority_todos.into_iter().take(4).collect();
+    let top_priority = priority_tasks(&todo_list, &active_todos);
 
     // Get 3 random tasks (excluding those already shown)
     let mut remaining_todos: Vec<&Todo> = active_todos
@@ -176,15 +293,20 @@ pub async fn default_display() -> Result<()> {
         };
         let random_todos: Vec<&Todo> = remaining_todos.into_iter().step_by(step).take(3).collect();
 
-        display_overview(&top_priority, &random_todos, &todo_list);
+        display_overview(&due_sections, &top_priority, &random_todos, &todo_list);
     } else {
-        display_overview(&top_priority, &[], &todo_list);
+        display_overview(&due_sections, &top_priority, &[], &todo_list);
     }
 
     Ok(())
 }
 
-fn display_overview(priority_todos: &[&Todo], random_todos: &[&Todo], todo_list: &TodoList) {
+fn display_overview(
+    due_sections: &[(&str, Vec<&Todo>)],
+    priority_todos: &[&Todo],
+    random_todos: &[&Todo],
+    todo_list: &TodoList,
+) {
     let total_active = todo_list.todos.iter().filter(|t| t.is_active()).count();
     let total_done = todo_list
         .todos
@@ -200,23 +322,31 @@ fn display_overview(priority_todos: &[&Todo], random_todos: &[&Todo], todo_list:
     println!("┌─ Your TODOs ────────────────────────────────────────────────────┐");
     println!("│                                                                 │");
 
+    for (label, todos) in due_sections {
+        if todos.is_empty() {
+            continue;
+        }
+        println!("│ {:<63} │", format!("{} ({}):", label, todos.len()));
+        for todo in todos.iter().take(5) {
+            let due = todo
+                .due_date
+                .map(|date| date.format("%b %d").to_string())
+                .unwrap_or_default();
+            print_overview_row(todo, &due);
+        }
+        if todos.len() > 5 {
+            println!("│   {:<61} │", format!("... and {} more", todos.len() - 5));
+        }
+        println!("│                                                                 │");
+    }
+
     if !priority_todos.is_empty() {
         println!(
             "│ Priority Tasks ({}):                                             │",
             priority_todos.len()
         );
         for todo in priority_todos {
-            let icon = get_status_icon(todo);
-            let category = todo.category.as_deref().unwrap_or("general");
-            let title = if todo.title.len() > 30 {
-                format!("{}...", &todo.title[..27])
-            } else {
-                todo.title.clone()
-            };
-            println!(
-                "│   [{:03}] {:2} | {:8} | {:<30} {} │",
-                todo.id, todo.priority, category, title, icon
-            );
+            print_overview_row(todo, todo.category.as_deref().unwrap_or("general"));
         }
         println!("│                                                                 │");
     }
@@ -227,17 +357,7 @@ fn display_overview(priority_todos: &[&Todo], random_todos: &[&Todo], todo_list:
             random_todos.len()
         );
         for todo in random_todos {
-            let icon = get_status_icon(todo);
-            let category = todo.category.as_deref().unwrap_or("general");
-            let title = if todo.title.len() > 30 {
-                format!("{}...", &todo.title[..27])
-            } else {
-                todo.title.clone()
-            };
-            println!(
-                "│   [{:03}] {:2} | {:8} | {:<30} {} │",
-                todo.id, todo.priority, category, title, icon
-            );
+            print_overview_row(todo, todo.category.as_deref().unwrap_or("general"));
         }
         println!("│                                                                 │");
     }
@@ -251,6 +371,35 @@ fn display_overview(priority_todos: &[&Todo], random_todos: &[&Todo], todo_list:
     println!("└─────────────────────────────────────────────────────────────────┘");
 }
 
+/// The top 4 TODOs for the Priority Tasks section: by priority (P0 first)
+/// then newest first, skipping work that is still waiting on a blocker
+fn priority_tasks<'a>(todo_list: &TodoList, active_todos: &[&'a Todo]) -> Vec<&'a Todo> {
+    let mut priority_todos: Vec<&Todo> = active_todos
+        .iter()
+        .copied()
+        .filter(|todo| !todo_list.is_blocked(todo.id))
+        .collect();
+    priority_todos.sort_by(|a, b| match a.priority_value().cmp(&b.priority_value()) {
+        std::cmp::Ordering::Equal => b.created_date.cmp(&a.created_date),
+        other => other,
+    });
+    priority_todos.into_iter().take(4).collect()
+}
+
+fn print_overview_row(todo: &Todo, label: &str) {
+    let icon = get_status_icon(todo);
+    let title = if todo.title.chars().count() > 30 {
+        let shortened: String = todo.title.chars().take(27).collect();
+        format!("{}...", shortened)
+    } else {
+        todo.title.clone()
+    };
+    println!(
+        "│   [{:03}] {:2} | {:8} | {:<30} {} │",
+        todo.id, todo.priority, label, title, icon
+    );
+}
+
 fn get_status_icon(todo: &Todo) -> &'static str {
     match (&todo.priority, &todo.status) {
         (Priority::P0, _) => "!",
@@ -260,3 +409,86 @@ fn get_status_icon(todo: &Todo) -> &'static str {
         _ => " ",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{bare_remote, working_repo};
+    use crate::core::{commit_all, head_commit};
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    #[test]
+    fn priority_tasks_skip_blocked_work() {
+        let mut todo_list = TodoList::new();
+        for (title, priority) in [
+            ("Ship release", Priority::P0),
+            ("Write changelog", Priority::P1),
+            ("Tidy desk", Priority::P4),
+            ("Fix build", Priority::P0),
+        ] {
+            let mut todo = todo_list.create_todo(title.to_string());
+            todo.priority = priority;
+            todo_list.add_todo(todo);
+        }
+        // The release waits on the build fix
+        todo_list.add_blocker(1, 4).unwrap();
+        let active: Vec<&Todo> = todo_list.todos.iter().collect();
+
+        let ids: Vec<u32> = priority_tasks(&todo_list, &active)
+            .iter()
+            .map(|todo| todo.id)
+            .collect();
+        assert_eq!(ids, vec![4, 2, 3]);
+    }
+
+    fn commit_count(repo: &Repository) -> usize {
+        let mut walk = repo.revwalk().unwrap();
+        walk.push_head().unwrap();
+        walk.count()
+    }
+
+    fn head_message(repo: &Repository) -> String {
+        let head = head_commit(repo).unwrap().unwrap();
+        repo.find_commit(head)
+            .unwrap()
+            .message()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn auto_commit_makes_one_commit_per_save() {
+        let (remote_dir, _remote) = bare_remote("main");
+        let work = TempDir::new().unwrap();
+        let repo = working_repo(work.path(), "main", remote_dir.path());
+        let backend = open_backend(work.path().join("todo.yaml"));
+        let mut todo_list = LoadedList::load(backend.as_ref()).unwrap();
+        let todo = todo_list.create_todo("Fix login".to_string());
+        todo_list.add_todo(todo);
+        todo_list.save_to(backend.as_ref(), None).unwrap();
+        commit_all(&repo, "Add login fix").unwrap();
+
+        let mut todo_list = LoadedList::load(backend.as_ref()).unwrap();
+        todo_list.set_status(1, Status::Done).unwrap();
+        todo_list
+            .save_to(backend.as_ref(), Some(work.path()))
+            .unwrap();
+        assert_eq!(commit_count(&repo), 2);
+        assert_eq!(head_message(&repo), "Mark #1 done");
+
+        todo_list.get_todo_mut(1).unwrap().priority = Priority::P1;
+        todo_list
+            .save_to(backend.as_ref(), Some(work.path()))
+            .unwrap();
+        assert_eq!(commit_count(&repo), 3);
+        assert_eq!(head_message(&repo), "Bump #1 to P1");
+
+        // Saving without a change commits nothing, and the journal stays out of git
+        todo_list
+            .save_to(backend.as_ref(), Some(work.path()))
+            .unwrap();
+        assert_eq!(commit_count(&repo), 3);
+        assert!(!crate::core::has_changes(&repo).unwrap());
+    }
+}
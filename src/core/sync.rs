@@ -0,0 +1,882 @@
+use crate::core::{
+    ahead_behind, changes_commit_message, commit_all, diff_commit_message, diff_todo_lists,
+    fast_forward, fetch, get_data_dir, global_todo_file, head_commit, is_network_error,
+    load_todos_at, open_repo, push, remote_target, serialize_todos, signature, uncommitted_changes,
+    PushError, Todo, TodoList,
+};
+use anyhow::{Context, Result};
+use git2::{Oid, Repository};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Which side's version wins a merge conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConflictKind {
+    /// Both sides changed the same field to different values
+    Field {
+        field: String,
+        local: Value,
+        remote: Value,
+    },
+    /// Deleted on this machine but edited on the remote
+    DeletedLocally { remote: Todo },
+    /// Edited on this machine but deleted on the remote
+    DeletedRemotely { local: Todo },
+}
+
+/// A change to one TODO that can't be merged automatically
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    pub id: u32,
+    pub title: String,
+    pub kind: ConflictKind,
+}
+
+/// Result of merging two versions of the list against their common ancestor.
+///
+/// Conflicts are left at the local version in `merged` until resolved with
+/// [`MergeOutcome::resolve`].
+pub struct MergeOutcome {
+    pub merged: TodoList,
+    pub conflicts: Vec<MergeConflict>,
+    /// Local TODOs moved to a new ID because the remote used theirs, as (old, new)
+    pub renumbered: Vec<(u32, u32)>,
+    /// TODOs added, changed or deleted on the remote that were applied
+    pub remote_changes: usize,
+}
+
+impl MergeOutcome {
+    /// Repairs links once conflicts are resolved: a parent or blocker may have
+    /// been deleted on the other side, and parent changes from both sides may
+    /// form a loop. Returns the TODOs made top-level to break a loop.
+    pub fn repair_links(&mut self) -> Vec<u32> {
+        self.merged.prune_dangling_links();
+        self.merged.break_parent_cycles()
+    }
+
+    /// Applies the chosen side of a conflict to the merged list
+    pub fn resolve(&mut self, conflict: &MergeConflict, resolution: Resolution) -> Result<()> {
+        if resolution == Resolution::Local {
+            return Ok(());
+        }
+
+        let todos = &mut self.merged.todos;
+        match &conflict.kind {
+            ConflictKind::Field { field, remote, .. } => {
+                let Some(todo) = todos.iter_mut().find(|todo| todo.id == conflict.id) else {
+                    return Ok(());
+                };
+                let mut fields = todo_fields(todo)?;
+                set_field(&mut fields, field, remote.clone());
+                *todo = serde_json::from_value(Value::Object(fields))
+                    .with_context(|| format!("Failed to merge TODO #{}", conflict.id))?;
+            }
+            ConflictKind::DeletedLocally { remote } => {
+                let index = todos
+                    .iter()
+                    .position(|todo| todo.id > remote.id)
+                    .unwrap_or(todos.len());
+                todos.insert(index, remote.clone());
+            }
+            ConflictKind::DeletedRemotely { .. } => {
+                todos.retain(|todo| todo.id != conflict.id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Three-way merge of the TODO list, matching TODOs by ID.
+///
+/// Changes to different TODOs, or to different fields of the same TODO, merge
+/// automatically; tags merge as a set. TODOs created on both sides with the
+/// same ID keep the remote's ID and the local one is renumbered.
+pub fn merge_todo_lists(
+    base: &TodoList,
+    local: &TodoList,
+    remote: &TodoList,
+) -> Result<MergeOutcome> {
+    let base_todos: HashMap<u32, &Todo> = base.todos.iter().map(|todo| (todo.id, todo)).collect();
+    let remote_todos: HashMap<u32, &Todo> =
+        remote.todos.iter().map(|todo| (todo.id, todo)).collect();
+
+    let (local_todos, renumbered) = renumber_collisions(&base_todos, local, remote);
+    let local_by_id: HashMap<u32, &Todo> = local_todos.iter().map(|todo| (todo.id, todo)).collect();
+
+    // Local order first, then TODOs that only exist on the remote
+    let mut ids: Vec<u32> = local_todos.iter().map(|todo| todo.id).collect();
+    let mut seen: HashSet<u32> = ids.iter().copied().collect();
+    for todo in &remote.todos {
+        if seen.insert(todo.id) {
+            ids.push(todo.id);
+        }
+    }
+    for todo in &base.todos {
+        if seen.insert(todo.id) {
+            ids.push(todo.id);
+        }
+    }
+
+    let mut merged = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut remote_changes = 0;
+
+    for id in ids {
+        let base = base_todos.get(&id).copied();
+        let local = local_by_id.get(&id).copied();
+        let remote = remote_todos.get(&id).copied();
+
+        match (base, local, remote) {
+            (_, Some(local), Some(remote)) if local == remote => merged.push(local.clone()),
+            (None, Some(local), None) => merged.push(local.clone()),
+            (None, None, Some(remote)) => {
+                merged.push(remote.clone());
+                remote_changes += 1;
+            }
+            (Some(base), Some(local), Some(remote)) if local == base => {
+                merged.push(remote.clone());
+                remote_changes += 1;
+            }
+            (Some(base), Some(local), Some(remote)) if remote == base => merged.push(local.clone()),
+            (Some(base), Some(local), Some(remote)) => {
+                let (todo, field_conflicts) = merge_fields(base, local, remote)?;
+                merged.push(todo);
+                conflicts.extend(field_conflicts);
+                remote_changes += 1;
+            }
+            (Some(base), None, Some(remote)) => {
+                if remote != base {
+                    conflicts.push(MergeConflict {
+                        id,
+                        title: remote.title.clone(),
+                        kind: ConflictKind::DeletedLocally {
+                            remote: remote.clone(),
+                        },
+                    });
+                }
+            }
+            (Some(base), Some(local), None) => {
+                if local == base {
+                    remote_changes += 1;
+                } else {
+                    merged.push(local.clone());
+                    conflicts.push(MergeConflict {
+                        id,
+                        title: local.title.clone(),
+                        kind: ConflictKind::DeletedRemotely {
+                            local: local.clone(),
+                        },
+                    });
+                }
+            }
+            // Both sides dropped it, or renumbering made the IDs disjoint
+            (_, None, None) | (None, Some(_), Some(_)) => {
+                if let Some(local) = local {
+                    merged.push(local.clone());
+                }
+            }
+        }
+    }
+
+    let max_id = merged.iter().map(|todo| todo.id).max().unwrap_or(0);
+    let next_id = local.next_id.max(remote.next_id).max(max_id + 1);
+
+    Ok(MergeOutcome {
+        merged: TodoList {
+            next_id,
+            todos: merged,
+        },
+        conflicts,
+        renumbered,
+        remote_changes,
+    })
+}
+
+/// Gives local TODOs created with an ID the remote also created a new ID,
+/// updating local references (parent, blockers, series) that were added with them
+fn renumber_collisions(
+    base: &HashMap<u32, &Todo>,
+    local: &TodoList,
+    remote: &TodoList,
+) -> (Vec<Todo>, Vec<(u32, u32)>) {
+    let remote_ids: HashMap<u32, &Todo> = remote.todos.iter().map(|todo| (todo.id, todo)).collect();
+    let mut next_id = local.next_id.max(remote.next_id).max(
+        local
+            .todos
+            .iter()
+            .chain(&remote.todos)
+            .map(|t| t.id + 1)
+            .max()
+            .unwrap_or(1),
+    );
+
+    let mut renumbered = Vec::new();
+    for todo in &local.todos {
+        let collides = !base.contains_key(&todo.id)
+            && remote_ids
+                .get(&todo.id)
+                .is_some_and(|remote| *remote != todo);
+        if collides {
+            renumbered.push((todo.id, next_id));
+            next_id += 1;
+        }
+    }
+    if renumbered.is_empty() {
+        return (local.todos.clone(), renumbered);
+    }
+
+    let new_id: HashMap<u32, u32> = renumbered.iter().copied().collect();
+    let remap = |id: u32, existed_in_base: bool| {
+        if existed_in_base {
+            id
+        } else {
+            new_id.get(&id).copied().unwrap_or(id)
+        }
+    };
+
+    let todos = local
+        .todos
+        .iter()
+        .map(|todo| {
+            let base_todo = base.get(&todo.id).copied();
+            let mut todo = todo.clone();
+            if let Some(parent_id) = todo.parent_id {
+                let unchanged = base_todo.is_some_and(|b| b.parent_id == Some(parent_id));
+                todo.parent_id = Some(remap(parent_id, unchanged));
+            }
+            if let Some(series_id) = todo.series_id {
+                let unchanged = base_todo.is_some_and(|b| b.series_id == Some(series_id));
+                todo.series_id = Some(remap(series_id, unchanged));
+            }
+            todo.blocked_by = todo
+                .blocked_by
+                .iter()
+                .map(|&id| remap(id, base_todo.is_some_and(|b| b.blocked_by.contains(&id))))
+                .collect();
+            todo.id = new_id.get(&todo.id).copied().unwrap_or(todo.id);
+            todo
+        })
+        .collect();
+
+    (todos, renumbered)
+}
+
+/// Field-by-field merge of a TODO edited on both sides
+fn merge_fields(base: &Todo, local: &Todo, remote: &Todo) -> Result<(Todo, Vec<MergeConflict>)> {
+    let base_fields = todo_fields(base)?;
+    let local_fields = todo_fields(local)?;
+    let remote_fields = todo_fields(remote)?;
+
+    let mut keys: Vec<&String> = local_fields.keys().collect();
+    for key in remote_fields.keys().chain(base_fields.keys()) {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    let mut merged = Map::new();
+    let mut conflicts = Vec::new();
+    for key in keys {
+        let base_value = base_fields.get(key).unwrap_or(&Value::Null);
+        let local_value = local_fields.get(key).unwrap_or(&Value::Null);
+        let remote_value = remote_fields.get(key).unwrap_or(&Value::Null);
+
+        let value = if local_value == remote_value || remote_value == base_value {
+            local_value.clone()
+        } else if local_value == base_value {
+            remote_value.clone()
+        } else if key == "tags" {
+            merge_tags(base_value, local_value, remote_value)
+        } else {
+            conflicts.push(MergeConflict {
+                id: local.id,
+                title: local.title.clone(),
+                kind: ConflictKind::Field {
+                    field: key.clone(),
+                    local: local_value.clone(),
+                    remote: remote_value.clone(),
+                },
+            });
+            local_value.clone()
+        };
+        set_field(&mut merged, key, value);
+    }
+
+    let todo = serde_json::from_value(Value::Object(merged))
+        .with_context(|| format!("Failed to merge TODO #{}", local.id))?;
+    Ok((todo, conflicts))
+}
+
+/// Keeps tags added on either side and drops tags removed on either side
+fn merge_tags(base: &Value, local: &Value, remote: &Value) -> Value {
+    let tags = |value: &Value| -> Vec<String> {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    };
+    let (base, local, remote) = (tags(base), tags(local), tags(remote));
+
+    let mut merged: Vec<String> = local
+        .iter()
+        .filter(|tag| remote.contains(tag) || !base.contains(tag))
+        .cloned()
+        .collect();
+    for tag in remote {
+        if !base.contains(&tag) && !merged.contains(&tag) {
+            merged.push(tag);
+        }
+    }
+    Value::from(merged)
+}
+
+fn todo_fields(todo: &Todo) -> Result<Map<String, Value>> {
+    match serde_json::to_value(todo).context("Failed to serialize TODO")? {
+        Value::Object(fields) => Ok(fields),
+        _ => anyhow::bail!("TODO #{} did not serialize to an object", todo.id),
+    }
+}
+
+/// Missing and null fields are equivalent; leaving them out lets empty lists fall back to their defaults
+fn set_field(fields: &mut Map<String, Value>, key: &str, value: Value) {
+    if value.is_null() {
+        fields.remove(key);
+    } else {
+        fields.insert(key.to_string(), value);
+    }
+}
+
+/// What `todo sync` did
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub remote: String,
+    pub branch: String,
+    /// Uncommitted local changes were committed first
+    pub committed: bool,
+    /// Commits on the remote this machine didn't have
+    pub pulled_commits: usize,
+    /// TODOs the remote changed, when both sides had new commits and were merged by ID
+    pub remote_changes: usize,
+    pub renumbered: Vec<(u32, u32)>,
+    /// TODOs made top-level because the merged parents formed a loop
+    pub detached: Vec<u32>,
+    pub conflicts_resolved: usize,
+    pub pushed: bool,
+}
+
+/// Commits local changes, fetches, merges the remote TODO list by ID and pushes.
+///
+/// `resolve` is asked to pick a side for each conflict; returning an error
+/// aborts the sync before anything is merged.
+pub fn sync_data_repo(
+    resolve: impl FnMut(&MergeConflict) -> Result<Resolution>,
+) -> Result<SyncReport> {
+    let (data_dir, data_path) = data_repo()?;
+    sync_repo(&data_dir, &data_path, resolve)
+}
+
+/// The guidebook data repository and the global TODO file in it
+pub fn data_repo() -> Result<(PathBuf, PathBuf)> {
+    let data_dir = get_data_dir()?;
+    if !data_dir.join(".git").exists() {
+        anyhow::bail!("Not a git repository. Run 'todo init' to set up GitHub integration.");
+    }
+    Ok((data_dir, global_todo_file()?))
+}
+
+/// Context attached to errors from not reaching the remote, so callers can
+/// tell being offline apart from other failures
+#[derive(Debug)]
+pub struct RemoteUnreachable;
+
+impl std::fmt::Display for RemoteUnreachable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Could not reach the remote")
+    }
+}
+
+/// Syncs the repository at `repo_path` with its remote (see [`remote_target`]);
+/// `data_path` is the TODO file in it
+pub fn sync_repo(
+    repo_path: &Path,
+    data_path: &Path,
+    resolve: impl FnMut(&MergeConflict) -> Result<Resolution>,
+) -> Result<SyncReport> {
+    fetch_remote(repo_path)?;
+    let mut report = merge_fetched(repo_path, data_path, resolve)?;
+    report.pushed = push_local_commits(repo_path)?;
+    Ok(report)
+}
+
+/// Commits everything uncommitted in the repository, describing the changes
+/// to the TODO file at `data_path`. Returns whether there was anything.
+pub fn commit_local_changes(repo_path: &Path, data_path: &Path) -> Result<bool> {
+    let repo = open_repo(repo_path)?;
+    let message = changes_commit_message(&uncommitted_changes(data_path));
+    Ok(commit_all(&repo, &message)?.is_some())
+}
+
+/// The network half of a sync: updates the remote-tracking branch
+pub fn fetch_remote(repo_path: &Path) -> Result<()> {
+    let repo = open_repo(repo_path)?;
+    let target = remote_target(&repo, None, None)?;
+    fetch(&repo, &target.remote, &target.branch).context(RemoteUnreachable)
+}
+
+/// The local half of a sync, after [`fetch_remote`]: commits local changes
+/// and merges the fetched branch. Nothing here touches the network.
+pub fn merge_fetched(
+    repo_path: &Path,
+    data_path: &Path,
+    mut resolve: impl FnMut(&MergeConflict) -> Result<Resolution>,
+) -> Result<SyncReport> {
+    let repo = open_repo(repo_path)?;
+
+    let target = remote_target(&repo, None, None)?;
+
+    let relative_path = data_path
+        .strip_prefix(repo_path)
+        .context("TODO file is outside the data repository")?
+        .to_path_buf();
+
+    let mut report = SyncReport {
+        remote: target.remote.clone(),
+        branch: target.branch.clone(),
+        ..SyncReport::default()
+    };
+
+    report.committed = commit_local_changes(repo_path, data_path)?;
+
+    let remote_ref = target.to_string();
+    let remote_head = repo
+        .find_reference(&format!("refs/remotes/{}", remote_ref))
+        .ok()
+        .and_then(|reference| reference.target());
+    let head = head_commit(&repo)?;
+
+    let (Some(remote_head), Some(head)) = (remote_head, head) else {
+        if let Some(remote_head) = remote_head {
+            fast_forward(
+                &repo,
+                &target.local_branch,
+                remote_head,
+                "sync: initial checkout",
+            )?;
+            let mut walk = repo.revwalk()?;
+            walk.push(remote_head)?;
+            report.pulled_commits = walk.count();
+        }
+        return Ok(report);
+    };
+
+    let (ahead, behind) = ahead_behind(&repo, head, remote_head)?;
+    if behind > 0 && ahead == 0 {
+        fast_forward(
+            &repo,
+            &target.local_branch,
+            remote_head,
+            "sync: fast-forward",
+        )?;
+        report.pulled_commits = behind;
+    } else if behind > 0 {
+        let merge = RemoteMerge {
+            local_branch: &target.local_branch,
+            head,
+            remote_head,
+            remote_ref: &remote_ref,
+            relative_path: &relative_path,
+        };
+        merge_remote(&repo, &merge, &mut resolve, &mut report)?;
+        report.pulled_commits = behind;
+    }
+
+    Ok(report)
+}
+
+/// The last step of a sync: pushes local commits the remote branch doesn't
+/// have, returning whether there were any
+pub fn push_local_commits(repo_path: &Path) -> Result<bool> {
+    let repo = open_repo(repo_path)?;
+    let target = remote_target(&repo, None, None)?;
+    let Some(head) = head_commit(&repo)? else {
+        return Ok(false);
+    };
+    let remote_head = repo
+        .find_reference(&format!("refs/remotes/{}", target))
+        .ok()
+        .and_then(|reference| reference.target());
+    let ahead = match remote_head {
+        Some(remote_head) => ahead_behind(&repo, head, remote_head)?.0,
+        None => 1,
+    };
+    if ahead == 0 {
+        return Ok(false);
+    }
+    push(&repo, &target).map_err(sync_push_error)?;
+    Ok(true)
+}
+
+/// The two commits being merged
+struct RemoteMerge<'a> {
+    local_branch: &'a str,
+    head: Oid,
+    remote_head: Oid,
+    remote_ref: &'a str,
+    relative_path: &'a Path,
+}
+
+/// Merges diverged histories, replacing git's textual merge of the TODO file with a merge by ID
+fn merge_remote(
+    repo: &Repository,
+    merge: &RemoteMerge,
+    resolve: &mut impl FnMut(&MergeConflict) -> Result<Resolution>,
+    report: &mut SyncReport,
+) -> Result<()> {
+    let relative_path = merge.relative_path;
+    let merge_base = repo
+        .merge_base(merge.head, merge.remote_head)
+        .context("The local and remote histories have nothing in common")?;
+    let base = load_todos_at(repo, merge_base, relative_path)?;
+    let local = load_todos_at(repo, merge.head, relative_path)?;
+    let remote = load_todos_at(repo, merge.remote_head, relative_path)?;
+
+    let mut outcome = merge_todo_lists(&base, &local, &remote)?;
+    for conflict in outcome.conflicts.clone() {
+        let resolution = resolve(&conflict)?;
+        outcome.resolve(&conflict, resolution)?;
+    }
+    report.detached = outcome.repair_links();
+
+    // Let git merge everything else in the repository; only the TODO file may conflict
+    let local_commit = repo.find_commit(merge.head)?;
+    let remote_commit = repo.find_commit(merge.remote_head)?;
+    let mut index = repo
+        .merge_commits(&local_commit, &remote_commit, None)
+        .context("Failed to merge the remote branch")?;
+
+    let relative = relative_path.to_string_lossy().replace('\\', "/");
+    let mut other_conflicts = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+        let path = entry
+            .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+            .unwrap_or_default();
+        if path != relative {
+            other_conflicts.push(path);
+        }
+    }
+    if !other_conflicts.is_empty() {
+        anyhow::bail!(
+            "Other files in the data repository conflict ({}). Resolve them with git in {}",
+            other_conflicts.join(", "),
+            repo.workdir().unwrap_or(repo.path()).display()
+        );
+    }
+
+    let content = serialize_todos(&outcome.merged, relative_path)?;
+    let blob = repo.blob(&content)?;
+    index.conflict_remove(relative_path).ok();
+    index.add(&git2::IndexEntry {
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode: 0o100644,
+        uid: 0,
+        gid: 0,
+        file_size: content.len() as u32,
+        id: blob,
+        flags: 0,
+        flags_extended: 0,
+        path: relative.into_bytes(),
+    })?;
+    let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+
+    // Describe what the merge brought in, as other commits describe their changes
+    let mut message = format!("Merge TODOs from {}", merge.remote_ref);
+    let merged_in = diff_todo_lists(&local, &outcome.merged);
+    if !merged_in.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(&diff_commit_message(&merged_in));
+    }
+    let signature = signature(repo)?;
+    let merge_commit = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &[&local_commit, &remote_commit],
+        )
+        .context("Failed to commit the merge")?;
+    // The branch only moves once the merged files are safely checked out
+    fast_forward(repo, merge.local_branch, merge_commit, "sync: merge")?;
+
+    report.remote_changes = outcome.remote_changes;
+    report.renumbered = outcome.renumbered;
+    report.conflicts_resolved = outcome.conflicts.len();
+    Ok(())
+}
+
+fn sync_push_error(error: PushError) -> anyhow::Error {
+    match error {
+        PushError::NonFastForward => anyhow::anyhow!(
+            "The remote changed while syncing. Run 'todo sync' again to merge the new changes"
+        ),
+        PushError::Other(e) if is_network_error(&e) => e.context(RemoteUnreachable),
+        PushError::Other(e) => e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{bare_remote, working_repo};
+    use crate::core::{clone_repo, has_changes, open_backend, Priority, Status};
+    use tempfile::TempDir;
+
+    fn todo(id: u32, title: &str) -> Todo {
+        let mut todo = Todo::new(title.to_string());
+        todo.id = id;
+        todo
+    }
+
+    fn list(next_id: u32, todos: Vec<Todo>) -> TodoList {
+        TodoList { next_id, todos }
+    }
+
+    fn find(list: &TodoList, id: u32) -> &Todo {
+        list.todos.iter().find(|todo| todo.id == id).unwrap()
+    }
+
+    #[test]
+    fn independent_edits_on_each_side_merge() {
+        let base = list(3, vec![todo(1, "Write docs"), todo(2, "Fix login")]);
+        let mut local = base.clone();
+        local.todos[0].title = "Write the docs".to_string();
+        local.todos[1].status = Status::Done;
+        let mut remote = base.clone();
+        remote.todos[1].priority = Priority::P0;
+
+        let outcome = merge_todo_lists(&base, &local, &remote).unwrap();
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.remote_changes, 1);
+        assert_eq!(find(&outcome.merged, 1).title, "Write the docs");
+        let login = find(&outcome.merged, 2);
+        assert_eq!(login.status, Status::Done);
+        assert_eq!(login.priority, Priority::P0);
+        assert_eq!(outcome.merged.next_id, 3);
+    }
+
+    #[test]
+    fn same_field_edited_on_both_sides_conflicts() {
+        let base = list(2, vec![todo(1, "Fix login")]);
+        let mut local = base.clone();
+        local.todos[0].title = "Fix the login page".to_string();
+        let mut remote = base.clone();
+        remote.todos[0].title = "Fix login redirect".to_string();
+
+        let mut outcome = merge_todo_lists(&base, &local, &remote).unwrap();
+        assert_eq!(outcome.conflicts.len(), 1);
+        let conflict = outcome.conflicts[0].clone();
+        match &conflict.kind {
+            ConflictKind::Field { field, .. } => assert_eq!(field, "title"),
+            other => panic!("unexpected conflict {:?}", other),
+        }
+        // Left at the local version until resolved
+        assert_eq!(find(&outcome.merged, 1).title, "Fix the login page");
+        outcome.resolve(&conflict, Resolution::Remote).unwrap();
+        assert_eq!(find(&outcome.merged, 1).title, "Fix login redirect");
+    }
+
+    #[test]
+    fn delete_on_one_side_and_edit_on_the_other_conflicts() {
+        let base = list(3, vec![todo(1, "Fix login"), todo(2, "Write docs")]);
+        let mut edited = base.clone();
+        edited.todos[0].title = "Fix login redirect".to_string();
+        let mut deleted = base.clone();
+        deleted.todos.remove(0);
+
+        // Deleted here, edited on the remote: gone until the remote side is chosen
+        let mut outcome = merge_todo_lists(&base, &deleted, &edited).unwrap();
+        assert_eq!(outcome.conflicts.len(), 1);
+        let conflict = outcome.conflicts[0].clone();
+        assert!(matches!(conflict.kind, ConflictKind::DeletedLocally { .. }));
+        assert!(outcome.merged.get_todo(1).is_none());
+        outcome.resolve(&conflict, Resolution::Remote).unwrap();
+        assert_eq!(find(&outcome.merged, 1).title, "Fix login redirect");
+
+        // Edited here, deleted on the remote: kept until the remote side is chosen
+        let mut outcome = merge_todo_lists(&base, &edited, &deleted).unwrap();
+        let conflict = outcome.conflicts[0].clone();
+        assert!(matches!(
+            conflict.kind,
+            ConflictKind::DeletedRemotely { .. }
+        ));
+        assert!(outcome.merged.get_todo(1).is_some());
+        outcome.resolve(&conflict, Resolution::Remote).unwrap();
+        assert!(outcome.merged.get_todo(1).is_none());
+
+        // A delete of an untouched TODO just applies
+        let outcome = merge_todo_lists(&base, &base, &deleted).unwrap();
+        assert!(outcome.conflicts.is_empty());
+        assert!(outcome.merged.get_todo(1).is_none());
+    }
+
+    #[test]
+    fn tags_merge_as_a_set() {
+        let mut base = list(2, vec![todo(1, "Fix login")]);
+        base.todos[0].tags = vec!["bug".to_string(), "web".to_string()];
+        let mut local = base.clone();
+        local.todos[0].tags = vec!["bug".to_string(), "urgent".to_string()];
+        let mut remote = base.clone();
+        remote.todos[0].tags = vec!["bug".to_string(), "web".to_string(), "auth".to_string()];
+
+        let outcome = merge_todo_lists(&base, &local, &remote).unwrap();
+        assert!(outcome.conflicts.is_empty());
+        // Added on either side kept, removed on either side dropped
+        assert_eq!(find(&outcome.merged, 1).tags, ["bug", "urgent", "auth"]);
+    }
+
+    #[test]
+    fn parents_set_on_both_sides_never_form_a_loop() {
+        let base = list(3, vec![todo(1, "Launch"), todo(2, "Design")]);
+        let mut local = base.clone();
+        local.todos[0].parent_id = Some(2);
+        let mut remote = base.clone();
+        remote.todos[1].parent_id = Some(1);
+
+        let mut outcome = merge_todo_lists(&base, &local, &remote).unwrap();
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.repair_links(), vec![1]);
+
+        let merged = &outcome.merged;
+        assert_eq!(find(merged, 1).parent_id, None);
+        assert_eq!(find(merged, 2).parent_id, Some(1));
+        let todos: Vec<&Todo> = merged.todos.iter().collect();
+        assert_eq!(crate::core::order_as_tree(&todos).len(), 2);
+    }
+
+    #[test]
+    fn id_collisions_renumber_local_todos_and_their_references() {
+        let base = list(2, vec![todo(1, "Release")]);
+        let mut local = base.clone();
+        let mut checklist = todo(2, "Release checklist");
+        checklist.parent_id = Some(1);
+        let mut step = todo(3, "Tag the release");
+        step.parent_id = Some(2);
+        step.blocked_by = vec![2];
+        local.todos.extend([checklist, step]);
+        local.next_id = 4;
+        let mut remote = base.clone();
+        remote.todos.push(todo(2, "Remote TODO"));
+        remote.next_id = 3;
+
+        let outcome = merge_todo_lists(&base, &local, &remote).unwrap();
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.renumbered, [(2, 4)]);
+        assert_eq!(find(&outcome.merged, 2).title, "Remote TODO");
+        let checklist = find(&outcome.merged, 4);
+        assert_eq!(checklist.title, "Release checklist");
+        assert_eq!(checklist.parent_id, Some(1));
+        let step = find(&outcome.merged, 3);
+        assert_eq!(step.parent_id, Some(4));
+        assert_eq!(step.blocked_by, [4]);
+        assert_eq!(outcome.merged.next_id, 5);
+    }
+
+    /// A clone of `remote` with the TODO file at `todo.yaml`
+    fn clone_with_todos(remote: &Path) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        clone_repo(&remote.to_string_lossy(), dir.path(), None).unwrap();
+        let data_path = dir.path().join("todo.yaml");
+        (dir, data_path)
+    }
+
+    fn edit_todos(data_path: &Path, edit: impl FnOnce(&mut TodoList)) {
+        let backend = open_backend(data_path.to_path_buf());
+        let (mut todo_list, mut stamp) = backend.load().unwrap();
+        edit(&mut todo_list);
+        backend.save(&todo_list, &mut stamp).unwrap();
+    }
+
+    fn head_message(repo: &Repository) -> String {
+        let head = head_commit(repo).unwrap().unwrap();
+        repo.find_commit(head)
+            .unwrap()
+            .message()
+            .unwrap()
+            .to_string()
+    }
+
+    fn no_conflicts(conflict: &MergeConflict) -> Result<Resolution> {
+        panic!("unexpected conflict on #{}", conflict.id)
+    }
+
+    #[test]
+    fn sync_repo_merges_two_clones_by_id() {
+        let (remote_dir, _remote) = bare_remote("main");
+        let first = TempDir::new().unwrap();
+        let repo = working_repo(first.path(), "main", remote_dir.path());
+        let data_path = first.path().join("todo.yaml");
+        edit_todos(&data_path, |todo_list| {
+            let todo = todo_list.create_todo("Fix login".to_string());
+            todo_list.add_todo(todo);
+        });
+        let report = sync_repo(first.path(), &data_path, no_conflicts).unwrap();
+        assert!(report.committed && report.pushed);
+        assert_eq!(head_message(&repo), "Add #1 \"Fix login\"");
+
+        let (second, other_path) = clone_with_todos(remote_dir.path());
+        edit_todos(&other_path, |todo_list| {
+            todo_list.todos[0].priority = Priority::P0;
+            let todo = todo_list.create_todo("Write docs".to_string());
+            todo_list.add_todo(todo);
+        });
+        sync_repo(second.path(), &other_path, no_conflicts).unwrap();
+
+        // Both machines added TODO #2 and changed #1
+        edit_todos(&data_path, |todo_list| {
+            todo_list.todos[0].status = Status::InProgress;
+            let todo = todo_list.create_todo("Plan sprint".to_string());
+            todo_list.add_todo(todo);
+        });
+        let report = sync_repo(first.path(), &data_path, no_conflicts).unwrap();
+        assert_eq!(report.pulled_commits, 1);
+        assert_eq!(report.renumbered, [(2, 3)]);
+        assert!(report.pushed);
+        assert!(!has_changes(&repo).unwrap());
+        let merge_message = head_message(&repo);
+        assert!(
+            merge_message.starts_with("Merge TODOs from origin/main\n\n"),
+            "{}",
+            merge_message
+        );
+        assert!(
+            merge_message.contains("Add #3 \"Plan sprint\""),
+            "{}",
+            merge_message
+        );
+
+        let report = sync_repo(second.path(), &other_path, no_conflicts).unwrap();
+        assert!(!report.pushed);
+        for path in [&data_path, &other_path] {
+            let (todo_list, _) = open_backend(path.to_path_buf()).load().unwrap();
+            let titles: Vec<&str> = todo_list.todos.iter().map(|t| t.title.as_str()).collect();
+            assert_eq!(titles, ["Fix login", "Plan sprint", "Write docs"]);
+            let login = find(&todo_list, 1);
+            assert_eq!(
+                (login.priority.clone(), login.status.clone()),
+                (Priority::P0, Status::InProgress)
+            );
+            assert_eq!(find(&todo_list, 2).title, "Write docs");
+            assert_eq!(todo_list.next_id, 4);
+        }
+    }
+}
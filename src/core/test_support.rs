@@ -0,0 +1,52 @@
+//! Scratch git repositories for tests: a bare repository standing in for
+//! the remote, and working repositories pushing to it.
+
+use chrono::{DateTime, Local};
+use git2::{IndexAddOption, Oid, Repository, RepositoryInitOptions, Signature, Time};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A bare repository in a temporary directory, with `branch` as its HEAD
+pub fn bare_remote(branch: &str) -> (TempDir, Repository) {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init_opts(
+        dir.path(),
+        RepositoryInitOptions::new().bare(true).initial_head(branch),
+    )
+    .unwrap();
+    (dir, repo)
+}
+
+/// A working repository at `path` on `branch`, with `origin` pointing at `remote`
+pub fn working_repo(path: &Path, branch: &str, remote: &Path) -> Repository {
+    let repo =
+        Repository::init_opts(path, RepositoryInitOptions::new().initial_head(branch)).unwrap();
+    repo.remote("origin", &remote.to_string_lossy()).unwrap();
+    repo
+}
+
+/// Writes `content` to `name` in the repository's working tree
+pub fn write_file(repo: &Repository, name: &str, content: &str) {
+    std::fs::write(repo.workdir().unwrap().join(name), content).unwrap();
+}
+
+/// Commits the whole working tree on top of HEAD, dated `time`
+pub fn commit_all_at(repo: &Repository, message: &str, time: DateTime<Local>) -> Oid {
+    let mut index = repo.index().unwrap();
+    index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let when = Time::new(time.timestamp(), time.offset().local_minus_utc() / 60);
+    let signature = Signature::new("Test", "test@example.com", &when).unwrap();
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+    .unwrap()
+}
@@ -0,0 +1,252 @@
+use crate::core::{days_before_today, parse_day_count, parse_weekday, Todo};
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// A span of time spent working on a TODO; `end` is `None` while the timer runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub start: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Local>>,
+}
+
+impl TimeEntry {
+    pub fn start_now() -> Self {
+        TimeEntry {
+            start: Local::now(),
+            end: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Length of the entry, counting a running entry up to `now`
+    pub fn duration(&self, now: DateTime<Local>) -> Duration {
+        (self.end.unwrap_or(now) - self.start).max(Duration::zero())
+    }
+
+    /// Portion of the entry that falls on or after `since`
+    pub fn duration_since(&self, since: DateTime<Local>, now: DateTime<Local>) -> Duration {
+        let end = self.end.unwrap_or(now);
+        let start = self.start.max(since);
+        (end - start).max(Duration::zero())
+    }
+}
+
+impl Todo {
+    /// The entry currently being timed, if any
+    pub fn running_entry(&self) -> Option<&TimeEntry> {
+        self.time_entries.iter().find(|entry| entry.is_running())
+    }
+
+    /// Total tracked time, optionally only counting time on or after `since`
+    pub fn tracked_time(&self, since: Option<DateTime<Local>>, now: DateTime<Local>) -> Duration {
+        self.time_entries
+            .iter()
+            .map(|entry| match since {
+                Some(since) => entry.duration_since(since, now),
+                None => entry.duration(now),
+            })
+            .fold(Duration::zero(), |total, d| total + d)
+    }
+}
+
+/// Formats a duration as `1h 05m`, or `42m` under an hour
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes == 0 && duration > Duration::zero() {
+        "<1m".to_string()
+    } else if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Formats a running timer as `H:MM:SS`
+pub fn format_timer(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
+
+/// Parses the start of a reporting window.
+///
+/// Accepts `YYYY-MM-DD`, `today`, `yesterday`, `week` (this Monday),
+/// `month` (the 1st), a weekday name (most recent, today included) or a
+/// look-back such as `7d` / `2w`.
+pub fn parse_since(since_str: &str) -> Result<NaiveDate> {
+    let today = Local::now().date_naive();
+    let lower = since_str.trim().to_lowercase();
+
+    let date = match lower.as_str() {
+        "today" => today,
+        "yesterday" => today - Duration::days(1),
+        "week" => today - Duration::days(today.weekday().num_days_from_monday() as i64),
+        "month" => today.with_day(1).unwrap_or(today),
+        _ => {
+            if let Some(weekday) = parse_weekday(&lower) {
+                let back = (7 + today.weekday().num_days_from_monday() as i64
+                    - weekday.num_days_from_monday() as i64)
+                    % 7;
+                today - Duration::days(back)
+            } else if let Some(days) = parse_day_count(&lower) {
+                days_before_today(days).ok_or_else(|| {
+                    anyhow::anyhow!("Invalid date: {} reaches too far back", since_str)
+                })?
+            } else {
+                NaiveDate::parse_from_str(&lower, "%Y-%m-%d").map_err(|_| {
+                    anyhow::anyhow!(
+                        "Invalid date: {}. Use YYYY-MM-DD, today, yesterday, week, month, a weekday, or 7d/2w",
+                        since_str
+                    )
+                })?
+            }
+        }
+    };
+
+    Ok(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Status, TodoList};
+    use chrono::TimeZone;
+
+    fn list_of(titles: &[&str]) -> TodoList {
+        let mut todo_list = TodoList::new();
+        for title in titles {
+            let todo = todo_list.create_todo(title.to_string());
+            todo_list.add_todo(todo);
+        }
+        todo_list
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 10, 1, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn running_count(todo_list: &TodoList) -> usize {
+        todo_list
+            .todos
+            .iter()
+            .flat_map(|todo| &todo.time_entries)
+            .filter(|entry| entry.is_running())
+            .count()
+    }
+
+    #[test]
+    fn start_and_stop_timer() {
+        let mut todo_list = list_of(&["Write report", "Review PR"]);
+
+        assert_eq!(todo_list.start_timer(1).unwrap(), None);
+        assert_eq!(todo_list.get_todo(1).unwrap().status, Status::InProgress);
+        assert_eq!(todo_list.running_timer().unwrap().0.id, 1);
+        // Starting it again keeps the same entry
+        assert_eq!(todo_list.start_timer(1).unwrap(), None);
+        assert_eq!(todo_list.get_todo(1).unwrap().time_entries.len(), 1);
+
+        let (stopped, _) = todo_list.stop_timer().unwrap();
+        assert_eq!(stopped, 1);
+        assert!(todo_list.running_timer().is_none());
+        assert!(todo_list.stop_timer().is_none());
+    }
+
+    #[test]
+    fn only_one_timer_runs_at_a_time() {
+        let mut todo_list = list_of(&["Write report", "Review PR"]);
+        todo_list.start_timer(1).unwrap();
+
+        assert_eq!(todo_list.start_timer(2).unwrap(), Some(1));
+        assert_eq!(running_count(&todo_list), 1);
+        assert_eq!(todo_list.running_timer().unwrap().0.id, 2);
+        assert!(todo_list.get_todo(1).unwrap().time_entries[0].end.is_some());
+    }
+
+    #[test]
+    fn set_status_closes_a_running_entry() {
+        for status in [Status::Done, Status::Todo, Status::Archived] {
+            let mut todo_list = list_of(&["Write report"]);
+            todo_list.start_timer(1).unwrap();
+            todo_list.set_status(1, status.clone()).unwrap();
+            assert_eq!(running_count(&todo_list), 0, "{:?}", status);
+        }
+
+        // Moving to in progress starts one
+        let mut todo_list = list_of(&["Write report"]);
+        todo_list.set_status(1, Status::InProgress).unwrap();
+        assert_eq!(running_count(&todo_list), 1);
+
+        todo_list.set_status(1, Status::Done).unwrap();
+        assert!(todo_list.start_timer(1).is_err());
+    }
+
+    #[test]
+    fn tracked_time_counts_the_part_inside_the_window() {
+        let mut todo_list = list_of(&["Write report"]);
+        let todo = todo_list.get_todo_mut(1).unwrap();
+        todo.time_entries = vec![
+            TimeEntry {
+                start: at(8, 0),
+                end: Some(at(9, 0)),
+            },
+            // Straddles the start of the window
+            TimeEntry {
+                start: at(9, 30),
+                end: Some(at(10, 30)),
+            },
+            // Still running
+            TimeEntry {
+                start: at(11, 0),
+                end: None,
+            },
+        ];
+        let now = at(11, 45);
+
+        assert_eq!(todo.tracked_time(None, now), Duration::minutes(165));
+        assert_eq!(
+            todo.tracked_time(Some(at(10, 0)), now),
+            Duration::minutes(75)
+        );
+        assert_eq!(todo.tracked_time(Some(at(12, 0)), now), Duration::zero());
+    }
+
+    #[test]
+    fn since_dates() {
+        let today = Local::now().date_naive();
+        assert_eq!(parse_since("today").unwrap(), today);
+        assert_eq!(parse_since("Yesterday").unwrap(), today - Duration::days(1));
+        assert_eq!(parse_since("7d").unwrap(), today - Duration::days(7));
+        assert_eq!(parse_since("2w").unwrap(), today - Duration::days(14));
+        assert_eq!(
+            parse_since("2026-09-01").unwrap(),
+            NaiveDate::from_ymd_opt(2026, 9, 1).unwrap()
+        );
+        assert_eq!(parse_since("month").unwrap().day(), 1);
+        assert_eq!(parse_since("week").unwrap().weekday(), chrono::Weekday::Mon);
+
+        for bad in ["soon", "2026-13-01", "7x", "", "99999999999d"] {
+            assert!(parse_since(bad).is_err(), "{:?} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn durations_format_as_hours_and_minutes() {
+        assert_eq!(format_duration(Duration::zero()), "0m");
+        assert_eq!(format_duration(Duration::seconds(30)), "<1m");
+        assert_eq!(format_duration(Duration::minutes(42)), "42m");
+        assert_eq!(format_duration(Duration::minutes(65)), "1h 05m");
+        assert_eq!(format_duration(Duration::hours(26)), "26h 00m");
+        assert_eq!(format_timer(Duration::seconds(3725)), "1:02:05");
+    }
+}
@@ -1,9 +1,10 @@
+use crate::core::{Recurrence, TimeEntry};
 use anyhow::Result;
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveDate, NaiveTime, Weekday};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Todo {
     pub id: u32,
     pub title: String,
@@ -15,25 +16,38 @@ pub struct Todo {
     pub project: Option<String>,
     pub created_date: DateTime<Local>,
     pub finished_date: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_time: Option<NaiveTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    /// ID of the first TODO in a recurring series, shared by every instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<u32>,
+    /// Owning TODO when this is a subtask
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<u32>,
+    /// TODOs that must be finished before this one can start
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_by: Vec<u32>,
+    /// Work sessions logged with `todo start` / `todo stop`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_entries: Vec<TimeEntry>,
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Priority {
     P0, // Urgent - work on this right now
     P1, // Must have
+    #[default]
     P2, // Should do
     P3, // Nice to have
     P4, // Wishlist
     P5, // Worth considering
 }
 
-impl Default for Priority {
-    fn default() -> Self {
-        Priority::P2
-    }
-}
-
 impl std::fmt::Display for Priority {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -47,20 +61,15 @@ impl std::fmt::Display for Priority {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Status {
+    #[default]
     Todo,
     InProgress,
     Done,
     Archived,
 }
 
-impl Default for Status {
-    fn default() -> Self {
-        Status::Todo
-    }
-}
-
 impl std::fmt::Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -72,6 +81,24 @@ impl std::fmt::Display for Status {
     }
 }
 
+/// What to do with a parent's open subtasks when the parent is completed or archived
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildAction {
+    /// Apply the parent's new status to every open subtask
+    Cascade,
+    /// Leave subtasks open
+    Leave,
+}
+
+/// Where a TODO's due date falls relative to the current time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueStatus {
+    Overdue,
+    Today,
+    ThisWeek, // Within the next seven days
+    Later,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodoList {
     pub next_id: u32,
@@ -100,6 +127,13 @@ impl Todo {
             project: None,
             created_date: Local::now(),
             finished_date: None,
+            due_date: None,
+            due_time: None,
+            recurrence: None,
+            series_id: None,
+            parent_id: None,
+            blocked_by: Vec::new(),
+            time_entries: Vec::new(),
             notes: None,
         }
     }
@@ -119,6 +153,42 @@ impl Todo {
         }
     }
 
+    /// Classifies the due date relative to `now`; finished TODOs are never due
+    pub fn due_status(&self, now: DateTime<Local>) -> Option<DueStatus> {
+        if !self.is_active() {
+            return None;
+        }
+
+        let due_date = self.due_date?;
+        let today = now.date_naive();
+
+        if due_date < today {
+            Some(DueStatus::Overdue)
+        } else if due_date == today {
+            match self.due_time {
+                Some(due_time) if due_time < now.time() => Some(DueStatus::Overdue),
+                _ => Some(DueStatus::Today),
+            }
+        } else if due_date <= today + Duration::days(7) {
+            Some(DueStatus::ThisWeek)
+        } else {
+            Some(DueStatus::Later)
+        }
+    }
+
+    /// Formats the due date (and time, if set) for display
+    pub fn format_due(&self) -> Option<String> {
+        let due_date = self.due_date?;
+        Some(match self.due_time {
+            Some(due_time) => format!(
+                "{} {}",
+                due_date.format("%Y-%m-%d"),
+                due_time.format("%H:%M")
+            ),
+            None => due_date.format("%Y-%m-%d").to_string(),
+        })
+    }
+
     /// Validates a TODO title according to SPEC (max 200 chars, non-empty)
     pub fn validate_title(title: &str) -> Result<()> {
         let trimmed = title.trim();
@@ -202,6 +272,13 @@ impl Todo {
             project: None,
             created_date: Local::now(),
             finished_date: None,
+            due_date: None,
+            due_time: None,
+            recurrence: None,
+            series_id: None,
+            parent_id: None,
+            blocked_by: Vec::new(),
+            time_entries: Vec::new(),
             notes: None,
         })
     }
@@ -284,6 +361,7 @@ impl TodoList {
         match pos {
             Some(index) => {
                 self.todos.remove(index);
+                self.prune_dangling_links();
                 Ok(())
             }
             None => anyhow::bail!("TODO with ID {} not found", id),
@@ -293,7 +371,8 @@ impl TodoList {
     pub fn delete_by_category(&mut self, category: &str) -> usize {
         let initial_len = self.todos.len();
         self.todos
-            .retain(|todo| todo.category.as_ref().map_or(true, |cat| cat != category));
+            .retain(|todo| todo.category.as_ref().is_none_or(|cat| cat != category));
+        self.prune_dangling_links();
         initial_len - self.todos.len()
     }
 
@@ -301,9 +380,308 @@ impl TodoList {
         let status = parse_status(status_str)?;
         let initial_len = self.todos.len();
         self.todos.retain(|todo| todo.status != status);
+        self.prune_dangling_links();
         Ok(initial_len - self.todos.len())
     }
 
+    /// Copies the TODOs of another list in under new IDs, keeping subtask,
+    /// blocker and series links. TODOs already here (same title and creation
+    /// time) are skipped, so importing twice adds nothing. Returns how many
+    /// were added.
+    pub fn import(&mut self, other: &TodoList) -> usize {
+        let mut new_ids: HashMap<u32, u32> = HashMap::new();
+        let mut imported = Vec::new();
+        for todo in &other.todos {
+            let existing = self.todos.iter().find(|existing| {
+                existing.title == todo.title && existing.created_date == todo.created_date
+            });
+            match existing {
+                Some(existing) => {
+                    new_ids.insert(todo.id, existing.id);
+                }
+                None => {
+                    new_ids.insert(todo.id, self.next_id);
+                    let mut todo = todo.clone();
+                    todo.id = self.next_id;
+                    self.next_id += 1;
+                    imported.push(todo);
+                }
+            }
+        }
+
+        let count = imported.len();
+        for mut todo in imported {
+            todo.parent_id = todo.parent_id.and_then(|id| new_ids.get(&id).copied());
+            todo.series_id = todo.series_id.and_then(|id| new_ids.get(&id).copied());
+            todo.blocked_by = todo
+                .blocked_by
+                .iter()
+                .filter_map(|id| new_ids.get(id).copied())
+                .collect();
+            self.todos.push(todo);
+        }
+        count
+    }
+
+    /// Clears parent links that loop back on themselves, which merging two
+    /// lists can produce (#1 under #2 on one side, #2 under #1 on the other).
+    /// The first TODO of each loop becomes top-level; their IDs are returned.
+    pub(crate) fn break_parent_cycles(&mut self) -> Vec<u32> {
+        let mut detached = Vec::new();
+        for index in 0..self.todos.len() {
+            let id = self.todos[index].id;
+            let mut seen = std::collections::HashSet::new();
+            let mut ancestor = self.todos[index].parent_id;
+            while let Some(ancestor_id) = ancestor {
+                if ancestor_id == id {
+                    self.todos[index].parent_id = None;
+                    detached.push(id);
+                    break;
+                }
+                // A loop further up, which its own first TODO will break
+                if !seen.insert(ancestor_id) {
+                    break;
+                }
+                ancestor = self.get_todo(ancestor_id).and_then(|todo| todo.parent_id);
+            }
+        }
+        detached
+    }
+
+    /// Drops links to deleted TODOs: orphaned subtasks become top-level and
+    /// blockers that no longer exist are forgotten
+    pub(crate) fn prune_dangling_links(&mut self) {
+        let ids: std::collections::HashSet<u32> = self.todos.iter().map(|todo| todo.id).collect();
+        for todo in &mut self.todos {
+            if todo
+                .parent_id
+                .is_some_and(|parent_id| !ids.contains(&parent_id))
+            {
+                todo.parent_id = None;
+            }
+            todo.blocked_by.retain(|blocker| ids.contains(blocker));
+        }
+    }
+
+    /// Direct subtasks of a TODO, in file order
+    pub fn children(&self, id: u32) -> Vec<&Todo> {
+        self.todos
+            .iter()
+            .filter(|todo| todo.parent_id == Some(id))
+            .collect()
+    }
+
+    /// IDs of all active subtasks below a TODO, at any depth
+    pub fn open_descendants(&self, id: u32) -> Vec<u32> {
+        let mut open = Vec::new();
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            for child in self.children(current) {
+                if child.is_active() {
+                    open.push(child.id);
+                }
+                stack.push(child.id);
+            }
+        }
+        open
+    }
+
+    /// Roll-up progress of direct subtasks as (done, total), ignoring archived ones
+    pub fn subtask_progress(&self, id: u32) -> Option<(usize, usize)> {
+        let children: Vec<&Todo> = self
+            .children(id)
+            .into_iter()
+            .filter(|todo| todo.status != Status::Archived)
+            .collect();
+        if children.is_empty() {
+            return None;
+        }
+        let done = children
+            .iter()
+            .filter(|todo| todo.status == Status::Done)
+            .count();
+        Some((done, children.len()))
+    }
+
+    /// Makes a TODO a subtask of `parent`, or top-level when `None`
+    pub fn set_parent(&mut self, id: u32, parent: Option<u32>) -> Result<()> {
+        if self.get_todo(id).is_none() {
+            anyhow::bail!("TODO with ID {} not found", id);
+        }
+
+        if let Some(parent_id) = parent {
+            if self.get_todo(parent_id).is_none() {
+                anyhow::bail!("Parent TODO with ID {} not found", parent_id);
+            }
+
+            // Walk up from the new parent; reaching `id` means a loop
+            let mut ancestor = Some(parent_id);
+            while let Some(ancestor_id) = ancestor {
+                if ancestor_id == id {
+                    anyhow::bail!(
+                        "Cannot make #{} a subtask of #{}: #{} is already below #{}",
+                        id,
+                        parent_id,
+                        parent_id,
+                        id
+                    );
+                }
+                ancestor = self.get_todo(ancestor_id).and_then(|todo| todo.parent_id);
+            }
+        }
+
+        if let Some(todo) = self.get_todo_mut(id) {
+            todo.parent_id = parent;
+        }
+        Ok(())
+    }
+
+    /// Blockers of a TODO that are still active
+    pub fn active_blockers(&self, id: u32) -> Vec<&Todo> {
+        self.get_todo(id)
+            .map(|todo| {
+                todo.blocked_by
+                    .iter()
+                    .filter_map(|blocker| self.get_todo(*blocker))
+                    .filter(|blocker| blocker.is_active())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// True when any of a TODO's blockers is still active
+    pub fn is_blocked(&self, id: u32) -> bool {
+        !self.active_blockers(id).is_empty()
+    }
+
+    /// Active and not waiting on a blocker, as `todo list --ready` shows
+    pub fn is_ready(&self, id: u32) -> bool {
+        self.get_todo(id).is_some_and(Todo::is_active) && !self.is_blocked(id)
+    }
+
+    /// Records that `id` cannot start until `blocker` is finished.
+    ///
+    /// Rejects links to unknown TODOs and links that would form a cycle.
+    pub fn add_blocker(&mut self, id: u32, blocker: u32) -> Result<()> {
+        if self.get_todo(id).is_none() {
+            anyhow::bail!("TODO with ID {} not found", id);
+        }
+        if self.get_todo(blocker).is_none() {
+            anyhow::bail!("Blocking TODO with ID {} not found", blocker);
+        }
+        if id == blocker {
+            anyhow::bail!("TODO #{} cannot block itself", id);
+        }
+
+        // A path from the blocker back to `id` would close a loop
+        if let Some(path) = self.blocker_path(blocker, id) {
+            let chain: Vec<String> = std::iter::once(id)
+                .chain(path)
+                .map(|step| format!("#{}", step))
+                .collect();
+            anyhow::bail!(
+                "Cannot block #{} on #{}: that would create a dependency cycle ({})",
+                id,
+                blocker,
+                chain.join(" → ")
+            );
+        }
+
+        if let Some(todo) = self.get_todo_mut(id) {
+            if !todo.blocked_by.contains(&blocker) {
+                todo.blocked_by.push(blocker);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_blocker(&mut self, id: u32, blocker: u32) -> Result<()> {
+        let todo = self
+            .get_todo_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+        todo.blocked_by.retain(|existing| *existing != blocker);
+        Ok(())
+    }
+
+    /// Applies a blocker change list: `3,4` replaces, `+5` adds, `-3` removes, `none` clears
+    pub fn update_blockers(&mut self, id: u32, blockers_str: &str) -> Result<()> {
+        let trimmed = blockers_str.trim();
+        if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("none") {
+            let todo = self
+                .get_todo_mut(id)
+                .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+            todo.blocked_by.clear();
+            return Ok(());
+        }
+
+        let parse_id = |part: &str| -> Result<u32> {
+            part.trim_start_matches('#')
+                .parse::<u32>()
+                .map_err(|_| anyhow::anyhow!("Invalid TODO ID in --blocked-by: {}", part))
+        };
+
+        let parts: Vec<&str> = trimmed
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect();
+        let is_delta = parts
+            .iter()
+            .all(|part| part.starts_with('+') || part.starts_with('-'));
+
+        if !is_delta {
+            let todo = self
+                .get_todo_mut(id)
+                .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+            todo.blocked_by.clear();
+        }
+
+        for part in parts {
+            if let Some(blocker) = part.strip_prefix('-') {
+                self.remove_blocker(id, parse_id(blocker)?)?;
+            } else {
+                let blocker = part.strip_prefix('+').unwrap_or(part);
+                self.add_blocker(id, parse_id(blocker)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Follows `blocked_by` links from `from`, returning the chain of IDs that reaches `to`
+    fn blocker_path(&self, from: u32, to: u32) -> Option<Vec<u32>> {
+        let mut visited = std::collections::HashSet::new();
+        let mut stack = vec![vec![from]];
+        while let Some(path) = stack.pop() {
+            let current = *path.last()?;
+            if current == to {
+                return Some(path);
+            }
+            if !visited.insert(current) {
+                continue;
+            }
+            if let Some(todo) = self.get_todo(current) {
+                for next in &todo.blocked_by {
+                    let mut next_path = path.clone();
+                    next_path.push(*next);
+                    stack.push(next_path);
+                }
+            }
+        }
+        None
+    }
+
+    /// Applies a parent's Done/Archived status to all of its open subtasks.
+    ///
+    /// Returns the number of subtasks changed.
+    pub fn cascade_status(&mut self, id: u32, status: Status) -> Result<usize> {
+        let open = self.open_descendants(id);
+        for child_id in &open {
+            self.set_status(*child_id, status.clone())?;
+        }
+        Ok(open.len())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn update_todo(
         &mut self,
         id: u32,
@@ -313,30 +691,21 @@ impl TodoList {
         category: Option<String>,
         project: Option<String>,
         notes: Option<String>,
-    ) -> Result<()> {
+        due: Option<String>,
+    ) -> Result<Option<u32>> {
+        if self.get_todo(id).is_none() {
+            anyhow::bail!("TODO with ID {} not found", id);
+        }
+
+        let next_occurrence = match status {
+            Some(status_str) => self.set_status(id, parse_status(&status_str)?)?,
+            None => None,
+        };
+
         let todo = self
             .get_todo_mut(id)
             .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
 
-        if let Some(status_str) = status {
-            let new_status = parse_status(&status_str)?;
-            let old_status = todo.status.clone();
-            todo.status = new_status;
-
-            // Set finished_date when marking as Done
-            if matches!(todo.status, Status::Done)
-                && matches!(old_status, Status::Todo | Status::InProgress)
-            {
-                todo.finished_date = Some(Local::now());
-            }
-            // Clear finished_date when unmarking
-            else if matches!(todo.status, Status::Todo | Status::InProgress)
-                && matches!(old_status, Status::Done)
-            {
-                todo.finished_date = None;
-            }
-        }
-
         if let Some(priority_str) = priority {
             todo.priority = parse_priority(&priority_str)?;
         }
@@ -357,9 +726,147 @@ impl TodoList {
             todo.notes = if note.is_empty() { None } else { Some(note) };
         }
 
+        if let Some(due_str) = due {
+            let trimmed = due_str.trim();
+            if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("none") {
+                todo.due_date = None;
+                todo.due_time = None;
+            } else {
+                let (due_date, due_time) = parse_due(trimmed)?;
+                todo.due_date = Some(due_date);
+                todo.due_time = due_time;
+            }
+        }
+
+        Ok(next_occurrence)
+    }
+
+    /// Changes a TODO's status, maintaining `finished_date`.
+    ///
+    /// Completing a recurring TODO creates its next instance; the new ID is returned.
+    pub fn set_status(&mut self, id: u32, new_status: Status) -> Result<Option<u32>> {
+        let todo = self
+            .get_todo_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+
+        let old_status = todo.status.clone();
+        todo.status = new_status;
+
+        // The timer runs only while a TODO is in progress
+        if todo.status != Status::InProgress {
+            close_running_entry(todo);
+        } else if old_status != Status::InProgress {
+            self.start_timer(id)?;
+        }
+        let todo = self
+            .get_todo_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+
+        // Set finished_date when marking as Done
+        if matches!(todo.status, Status::Done)
+            && matches!(old_status, Status::Todo | Status::InProgress)
+        {
+            todo.finished_date = Some(Local::now());
+            if todo.recurrence.is_some() {
+                return Ok(Some(self.spawn_next_occurrence(id)?));
+            }
+        }
+        // Clear finished_date when unmarking
+        else if matches!(todo.status, Status::Todo | Status::InProgress)
+            && matches!(old_status, Status::Done)
+        {
+            todo.finished_date = None;
+        }
+
+        Ok(None)
+    }
+
+    /// The TODO whose timer is currently running, if any
+    pub fn running_timer(&self) -> Option<(&Todo, &TimeEntry)> {
+        self.todos
+            .iter()
+            .find_map(|todo| todo.running_entry().map(|entry| (todo, entry)))
+    }
+
+    /// Starts timing a TODO and marks it in progress.
+    ///
+    /// Only one timer runs at a time; the ID of a TODO whose timer was stopped
+    /// to make way is returned.
+    pub fn start_timer(&mut self, id: u32) -> Result<Option<u32>> {
+        let todo = self
+            .get_todo(id)
+            .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+        if !todo.is_active() {
+            anyhow::bail!(
+                "TODO #{} is {}; reopen it before starting a timer",
+                id,
+                todo.status
+            );
+        }
+        if todo.running_entry().is_some() {
+            return Ok(None);
+        }
+
+        let stopped = self.stop_timer().map(|(stopped_id, _)| stopped_id);
+
+        let todo = self
+            .get_todo_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+        todo.status = Status::InProgress;
+        todo.time_entries.push(TimeEntry::start_now());
+        Ok(stopped)
+    }
+
+    /// Stops the running timer, returning the TODO's ID and the length of the session
+    pub fn stop_timer(&mut self) -> Option<(u32, Duration)> {
+        let now = Local::now();
+        self.todos.iter_mut().find_map(|todo| {
+            let entry = todo
+                .time_entries
+                .iter_mut()
+                .find(|entry| entry.is_running())?;
+            entry.end = Some(now);
+            Some((todo.id, entry.duration(now)))
+        })
+    }
+
+    /// Sets or clears the recurrence rule of a TODO
+    pub fn set_recurrence(&mut self, id: u32, recurrence: Option<Recurrence>) -> Result<()> {
+        let todo = self
+            .get_todo_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+        todo.recurrence = recurrence;
         Ok(())
     }
 
+    /// Creates the next instance of a recurring TODO and links both into one series
+    fn spawn_next_occurrence(&mut self, id: u32) -> Result<u32> {
+        let next_id = self.next_id;
+        let todo = self
+            .get_todo_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("TODO with ID {} not found", id))?;
+        let recurrence = todo
+            .recurrence
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("TODO with ID {} does not recur", id))?;
+
+        let series_id = *todo.series_id.get_or_insert(id);
+        let completed = todo.finished_date.unwrap_or_else(Local::now).date_naive();
+
+        let mut next = todo.clone();
+        next.id = next_id;
+        next.status = Status::Todo;
+        next.created_date = Local::now();
+        next.finished_date = None;
+        next.due_date = Some(recurrence.next_due(todo.due_date, completed)?);
+        next.series_id = Some(series_id);
+        next.time_entries.clear();
+
+        self.next_id += 1;
+        self.todos.push(next);
+        Ok(next_id)
+    }
+
     pub fn show_stats(&self) {
         let total = self.todos.len();
         let mut status_counts = HashMap::new();
@@ -400,6 +907,17 @@ impl TodoList {
     }
 }
 
+/// Ends the TODO's running time entry, if it has one
+fn close_running_entry(todo: &mut Todo) {
+    if let Some(entry) = todo
+        .time_entries
+        .iter_mut()
+        .find(|entry| entry.is_running())
+    {
+        entry.end = Some(Local::now());
+    }
+}
+
 pub fn parse_status(status_str: &str) -> Result<Status> {
     match status_str.to_lowercase().as_str() {
         "todo" => Ok(Status::Todo),
@@ -413,6 +931,17 @@ pub fn parse_status(status_str: &str) -> Result<Status> {
     }
 }
 
+pub fn parse_child_action(action_str: &str) -> Result<ChildAction> {
+    match action_str.to_lowercase().as_str() {
+        "complete" | "cascade" | "archive" => Ok(ChildAction::Cascade),
+        "leave" => Ok(ChildAction::Leave),
+        _ => anyhow::bail!(
+            "Invalid subtask action: {}. Valid values: complete, leave",
+            action_str
+        ),
+    }
+}
+
 pub fn parse_priority(priority_str: &str) -> Result<Priority> {
     match priority_str.to_lowercase().as_str() {
         "p0" => Ok(Priority::P0),
@@ -428,19 +957,299 @@ pub fn parse_priority(priority_str: &str) -> Result<Priority> {
     }
 }
 
+/// Parses a due date such as `2025-08-01`, `2025-08-01 17:00`, `today`,
+/// `tomorrow`, `friday`, `+3d` or `+2w`, optionally followed by an `HH:MM` time
+pub fn parse_due(due_str: &str) -> Result<(NaiveDate, Option<NaiveTime>)> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid due date: {}. Use YYYY-MM-DD [HH:MM], today, tomorrow, a weekday, +Nd or +Nw",
+            due_str
+        )
+    };
+
+    let mut parts = due_str.split_whitespace();
+    let date_part = parts.next().ok_or_else(invalid)?.to_lowercase();
+    let time_part = parts.next();
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+
+    let today = Local::now().date_naive();
+    let due_date = match date_part.as_str() {
+        "today" => today,
+        "tomorrow" => today + Duration::days(1),
+        _ => {
+            if let Some(weekday) = parse_weekday(&date_part) {
+                // Next occurrence of the weekday, never today
+                let days_ahead = (weekday.num_days_from_monday() as i64
+                    - today.weekday().num_days_from_monday() as i64)
+                    .rem_euclid(7);
+                today + Duration::days(if days_ahead == 0 { 7 } else { days_ahead })
+            } else if let Some(offset) = date_part.strip_prefix('+') {
+                parse_day_count(offset)
+                    .and_then(days_after_today)
+                    .ok_or_else(invalid)?
+            } else {
+                NaiveDate::parse_from_str(&date_part, "%Y-%m-%d").map_err(|_| invalid())?
+            }
+        }
+    };
+
+    let due_time = match time_part {
+        Some(time_str) => {
+            Some(NaiveTime::parse_from_str(time_str, "%H:%M").map_err(|_| invalid())?)
+        }
+        None => None,
+    };
+
+    Ok((due_date, due_time))
+}
+
+pub fn parse_weekday(weekday_str: &str) -> Option<Weekday> {
+    match weekday_str {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// `7d` or `2w` as a number of days; `None` if `text` is neither or the
+/// count does not fit
+pub fn parse_day_count(text: &str) -> Option<u64> {
+    if let Some(days) = text.strip_suffix('d') {
+        days.parse().ok()
+    } else {
+        text.strip_suffix('w')?
+            .parse::<u64>()
+            .ok()
+            .and_then(|weeks| weeks.checked_mul(7))
+    }
+}
+
+/// The date `days` before today, or `None` if that is before the earliest
+/// date there is
+pub fn days_before_today(days: u64) -> Option<NaiveDate> {
+    Local::now().date_naive().checked_sub_days(Days::new(days))
+}
+
+/// The date `days` after today, or `None` if that is past the latest date
+/// there is
+pub fn days_after_today(days: u64) -> Option<NaiveDate> {
+    Local::now().date_naive().checked_add_days(Days::new(days))
+}
+
 pub fn update_tags(tags: &mut Vec<String>, tags_str: &str) {
     for tag_part in tags_str.split(',') {
         let tag_part = tag_part.trim();
-        if tag_part.starts_with('+') {
+        if let Some(tag) = tag_part.strip_prefix('+') {
             // Add tag
-            let tag = tag_part[1..].to_lowercase();
+            let tag = tag.to_lowercase();
             if !tag.is_empty() && !tags.contains(&tag) {
                 tags.push(tag);
             }
-        } else if tag_part.starts_with('-') {
+        } else if let Some(tag) = tag_part.strip_prefix('-') {
             // Remove tag
-            let tag = tag_part[1..].to_lowercase();
+            let tag = tag.to_lowercase();
             tags.retain(|t| t != &tag);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn day_counts_stay_within_the_calendar() {
+        let today = Local::now().date_naive();
+        assert_eq!(parse_day_count("7d"), Some(7));
+        assert_eq!(parse_day_count("2w"), Some(14));
+        for text in ["d", "3é", "-3d", "3x", "99999999999999999999d", "3000000000000000000w"] {
+            assert_eq!(parse_day_count(text), None, "{} is not a day count", text);
+        }
+        assert_eq!(days_before_today(3), Some(today - Duration::days(3)));
+        assert_eq!(days_after_today(3), Some(today + Duration::days(3)));
+        assert_eq!(days_before_today(100_000_000), None);
+        assert_eq!(days_after_today(u64::MAX), None);
+
+        // Every parser built on them reports an error instead of panicking
+        assert_eq!(parse_due("+2w").unwrap().0, today + Duration::weeks(2));
+        assert!(parse_due("+99999999999d").is_err());
+        assert_eq!(crate::core::parse_since("7d").unwrap(), today - Duration::days(7));
+        assert!(crate::core::parse_since("100000000d").is_err());
+        assert!(crate::core::parse_date_span("100000000d").is_err());
+        assert!(crate::core::Query::parse("age:>100000000d").is_err());
+    }
+
+    #[test]
+    fn due_dates_parse_relative_to_today() {
+        let today = Local::now().date_naive();
+        assert_eq!(parse_due("today").unwrap(), (today, None));
+        assert_eq!(parse_due("Tomorrow").unwrap().0, today + Duration::days(1));
+        assert_eq!(parse_due("+3d").unwrap().0, today + Duration::days(3));
+        assert_eq!(
+            parse_due("2026-11-02 09:30").unwrap(),
+            (
+                NaiveDate::from_ymd_opt(2026, 11, 2).unwrap(),
+                NaiveTime::from_hms_opt(9, 30, 0)
+            )
+        );
+
+        // A weekday is its next occurrence within a week, never today
+        for name in ["mon", "tuesday", "Fri", "sun"] {
+            let due = parse_due(name).unwrap().0;
+            assert_eq!(Some(due.weekday()), parse_weekday(&name.to_lowercase()));
+            assert!(due > today && due <= today + Duration::days(7), "{}", name);
+        }
+        let same_day = today.weekday().to_string().to_lowercase();
+        assert_eq!(parse_due(&same_day).unwrap().0, today + Duration::days(7));
+
+        for invalid in ["", "someday", "+3", "+d", "2026-13-01", "today 25:00", "today 9:30 pm"] {
+            assert!(parse_due(invalid).is_err(), "{} should not parse", invalid);
+        }
+    }
+
+    #[test]
+    fn due_status_classifies_against_now() {
+        let now = Local.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let status = |days: i64, time: Option<(u32, u32)>| {
+            let mut todo = Todo::new("Pay rent".to_string());
+            todo.due_date = Some(now.date_naive() + Duration::days(days));
+            todo.due_time = time.and_then(|(h, m)| NaiveTime::from_hms_opt(h, m, 0));
+            todo.due_status(now)
+        };
+        assert_eq!(status(-1, None), Some(DueStatus::Overdue));
+        assert_eq!(status(0, Some((9, 0))), Some(DueStatus::Overdue));
+        assert_eq!(status(0, None), Some(DueStatus::Today));
+        assert_eq!(status(0, Some((17, 0))), Some(DueStatus::Today));
+        assert_eq!(status(1, None), Some(DueStatus::ThisWeek));
+        assert_eq!(status(7, None), Some(DueStatus::ThisWeek));
+        assert_eq!(status(8, None), Some(DueStatus::Later));
+
+        // Finished and undated TODOs are never due
+        let mut done = Todo::new("Pay rent".to_string());
+        done.due_date = Some(now.date_naive() - Duration::days(3));
+        done.status = Status::Done;
+        assert_eq!(done.due_status(now), None);
+        assert_eq!(Todo::new("Someday".to_string()).due_status(now), None);
+    }
+
+    /// A list of TODOs with IDs 1..=count
+    fn list_with(count: u32) -> TodoList {
+        let mut todo_list = TodoList::new();
+        for n in 1..=count {
+            let todo = todo_list.create_todo(format!("Task {}", n));
+            todo_list.add_todo(todo);
+        }
+        todo_list
+    }
+
+    #[test]
+    fn blocker_cycles_are_rejected_with_their_path() {
+        let mut todo_list = list_with(4);
+        let error = todo_list.add_blocker(1, 1).unwrap_err();
+        assert!(error.to_string().contains("cannot block itself"), "{}", error);
+
+        todo_list.add_blocker(1, 2).unwrap();
+        let error = todo_list.add_blocker(2, 1).unwrap_err();
+        assert!(error.to_string().contains("(#2 → #1 → #2)"), "{}", error);
+
+        todo_list.add_blocker(2, 3).unwrap();
+        todo_list.add_blocker(3, 4).unwrap();
+        let error = todo_list.add_blocker(4, 1).unwrap_err();
+        assert!(
+            error.to_string().contains("(#4 → #1 → #2 → #3 → #4)"),
+            "{}",
+            error
+        );
+        assert!(todo_list.get_todo(4).unwrap().blocked_by.is_empty());
+
+        // Shared blockers are not cycles, and adding one twice keeps one link
+        todo_list.add_blocker(1, 4).unwrap();
+        todo_list.add_blocker(1, 4).unwrap();
+        assert_eq!(todo_list.get_todo(1).unwrap().blocked_by, vec![2, 4]);
+        assert!(todo_list.add_blocker(1, 9).is_err());
+    }
+
+    #[test]
+    fn finishing_the_blocker_makes_a_todo_ready() {
+        let mut todo_list = list_with(3);
+        todo_list.add_blocker(1, 2).unwrap();
+        todo_list.add_blocker(1, 3).unwrap();
+        assert!(todo_list.is_blocked(1));
+        assert!(!todo_list.is_ready(1));
+        assert!(todo_list.is_ready(2));
+
+        todo_list.set_status(2, Status::Done).unwrap();
+        assert!(todo_list.is_blocked(1), "#3 still blocks it");
+        todo_list.set_status(3, Status::Archived).unwrap();
+        assert!(!todo_list.is_blocked(1));
+        assert!(todo_list.is_ready(1));
+
+        // Finished TODOs are never ready
+        assert!(!todo_list.is_ready(2));
+    }
+
+    #[test]
+    fn parent_loops_are_rejected() {
+        let mut todo_list = list_with(3);
+        todo_list.set_parent(2, Some(1)).unwrap();
+        todo_list.set_parent(3, Some(2)).unwrap();
+
+        assert!(todo_list.set_parent(1, Some(1)).is_err());
+        let error = todo_list.set_parent(1, Some(3)).unwrap_err();
+        assert!(error.to_string().contains("#3 is already below #1"), "{}", error);
+        assert_eq!(todo_list.get_todo(1).unwrap().parent_id, None);
+        assert!(todo_list.set_parent(1, Some(9)).is_err());
+
+        // Moving a subtree elsewhere, or back to the top, is fine
+        todo_list.set_parent(3, Some(1)).unwrap();
+        todo_list.set_parent(2, None).unwrap();
+        assert_eq!(todo_list.get_todo(2).unwrap().parent_id, None);
+    }
+
+    /// A parent (#1) with subtasks #2 and #3, #3 having its own subtask #4,
+    /// and an archived subtask #5
+    fn family() -> TodoList {
+        let mut todo_list = list_with(5);
+        for (child, parent) in [(2, 1), (3, 1), (4, 3), (5, 1)] {
+            todo_list.set_parent(child, Some(parent)).unwrap();
+        }
+        todo_list.set_status(5, Status::Archived).unwrap();
+        todo_list
+    }
+
+    #[test]
+    fn completing_a_parent_cascades_or_leaves_subtasks() {
+        let mut cascaded = family();
+        cascaded.set_status(1, Status::Done).unwrap();
+        assert_eq!(cascaded.cascade_status(1, Status::Done).unwrap(), 3);
+        for id in 2..=4 {
+            assert_eq!(cascaded.get_todo(id).unwrap().status, Status::Done);
+        }
+        assert_eq!(cascaded.get_todo(5).unwrap().status, Status::Archived);
+
+        let mut left = family();
+        left.set_status(1, Status::Done).unwrap();
+        assert_eq!(left.open_descendants(1), vec![2, 3, 4]);
+        for id in 2..=4 {
+            assert_eq!(left.get_todo(id).unwrap().status, Status::Todo);
+        }
+    }
+
+    #[test]
+    fn progress_counts_direct_subtasks_except_archived() {
+        let mut todo_list = family();
+        assert_eq!(todo_list.subtask_progress(1), Some((0, 2)));
+        todo_list.set_status(2, Status::Done).unwrap();
+        assert_eq!(todo_list.subtask_progress(1), Some((1, 2)));
+        assert_eq!(todo_list.subtask_progress(3), Some((0, 1)));
+        assert_eq!(todo_list.subtask_progress(4), None);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::core::{auto_commit, get_data_dir, parse_sort, write_atomically, Query};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const VIEWS_FILE: &str = "views.yaml";
+
+/// A query saved under a name with `todo view save`, with an optional sort order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+}
+
+/// Saved views by name, kept next to the global TODO file so they sync with
+/// the data repository
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Views {
+    pub views: BTreeMap<String, SavedView>,
+}
+
+impl Views {
+    pub fn path() -> Result<PathBuf> {
+        Ok(get_data_dir()?.join("guidebook-todo").join(VIEWS_FILE))
+    }
+
+    pub fn load() -> Result<Views> {
+        Self::read(&Self::path()?)
+    }
+
+    /// Writes the views, committing them with `message` when `auto_commit` is on
+    pub fn save(&self, message: &str) -> Result<()> {
+        let path = Self::path()?;
+        self.write(&path)?;
+        auto_commit(&path, message)
+    }
+
+    fn read(path: &Path) -> Result<Views> {
+        if !path.exists() {
+            return Ok(Views::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read views: {}", path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse views: {}", path.display()))
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_yaml::to_string(self).context("Failed to serialize views")?;
+        write_atomically(path, content.as_bytes())
+    }
+
+    pub fn get(&self, name: &str) -> Result<&SavedView> {
+        self.views.get(name).ok_or_else(|| self.not_found(name))
+    }
+
+    /// Deletes the view named `name`, returning it
+    pub fn remove(&mut self, name: &str) -> Result<SavedView> {
+        let error = self.not_found(name);
+        self.views.remove(name).ok_or(error)
+    }
+
+    fn not_found(&self, name: &str) -> anyhow::Error {
+        let names: Vec<&str> = self.views.keys().map(String::as_str).collect();
+        if names.is_empty() {
+            anyhow::anyhow!(
+                "No view named '{}'. Save one with 'todo view save {} \"<query>\"'",
+                name,
+                name
+            )
+        } else {
+            anyhow::anyhow!("No view named '{}'. Views: {}", name, names.join(", "))
+        }
+    }
+
+    /// Saves `view` as `name`, after checking its query and sort order parse.
+    /// Returns whether it replaced an existing view.
+    pub fn insert(&mut self, name: &str, view: SavedView) -> Result<bool> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!(
+                "Invalid view name '{}'. Use letters, digits, '-' and '_'",
+                name
+            );
+        }
+        Query::parse(&view.query).with_context(|| format!("Invalid query: {}", view.query))?;
+        if let Some(sort) = &view.sort {
+            parse_sort(sort).map_err(|error| anyhow::anyhow!("Invalid sort order: {}", error))?;
+        }
+        Ok(self.views.insert(name.to_string(), view).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn view(query: &str, sort: Option<&str>) -> SavedView {
+        SavedView {
+            query: query.to_string(),
+            sort: sort.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn views_round_trip_through_the_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("guidebook-todo").join(VIEWS_FILE);
+        assert!(Views::read(&path).unwrap().views.is_empty());
+
+        let mut views = Views::default();
+        views.insert("urgent", view("priority:p0", None)).unwrap();
+        views
+            .insert(
+                "work-week",
+                view("tag:work due:this-week", Some("due,-priority")),
+            )
+            .unwrap();
+        views.write(&path).unwrap();
+
+        let saved = Views::read(&path).unwrap();
+        assert_eq!(saved.views, views.views);
+        assert_eq!(saved.get("urgent").unwrap().query, "priority:p0");
+        assert_eq!(
+            saved.get("work-week").unwrap().sort.as_deref(),
+            Some("due,-priority")
+        );
+    }
+
+    #[test]
+    fn bad_names_queries_and_sorts_are_rejected() {
+        let mut views = Views::default();
+        for name in ["", "my view", "a/b", "../x", "work.week"] {
+            let error = views.insert(name, view("tag:work", None)).unwrap_err();
+            assert!(error.to_string().contains("Invalid view name"), "{}", name);
+        }
+        assert!(views.insert("work", view("status:nope", None)).is_err());
+        assert!(views
+            .insert("work", view("tag:work", Some("colour")))
+            .is_err());
+        assert!(views.views.is_empty());
+
+        assert!(views.insert("Work_2", view("tag:work", None)).is_ok());
+    }
+
+    #[test]
+    fn saving_over_a_view_replaces_it() {
+        let mut views = Views::default();
+        assert!(!views.insert("work", view("tag:work", None)).unwrap());
+        assert!(views
+            .insert("work", view("tag:work status:todo", Some("due")))
+            .unwrap());
+        assert_eq!(views.views.len(), 1);
+        assert_eq!(
+            views.get("work").unwrap(),
+            &view("tag:work status:todo", Some("due"))
+        );
+    }
+
+    #[test]
+    fn deleting_a_view() {
+        let mut views = Views::default();
+        views.insert("work", view("tag:work", None)).unwrap();
+        views.insert("home", view("tag:home", None)).unwrap();
+
+        assert_eq!(views.remove("work").unwrap().query, "tag:work");
+        assert!(views.get("work").is_err());
+        let error = views.remove("work").unwrap_err();
+        assert!(error.to_string().contains("Views: home"), "{}", error);
+
+        views.remove("home").unwrap();
+        assert!(views.views.is_empty());
+        assert!(views.remove("home").is_err());
+    }
+}
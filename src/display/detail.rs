@@ -1,12 +1,50 @@
-use crate::core::Todo;
+use crate::core::{format_duration, DueStatus, HistoryEvent, Todo, TodoDiff, TodoList};
 use colored::*;
 
-pub fn format_detail(todo: &Todo) {
+pub fn format_detail(todo: &Todo, todo_list: &TodoList) {
     println!("{}", format!("TODO #{}", todo.id).bold().blue());
     println!("{}: {}", "Title".bold(), todo.title);
     println!("{}: {}", "Status".bold(), format_status(&todo.status));
     println!("{}: {}", "Priority".bold(), format_priority(&todo.priority));
 
+    if let Some(parent) = todo.parent_id.and_then(|id| todo_list.get_todo(id)) {
+        println!("{}: #{} {}", "Parent".bold(), parent.id, parent.title);
+    }
+
+    if !todo.blocked_by.is_empty() {
+        let blockers: Vec<String> = todo
+            .blocked_by
+            .iter()
+            .map(|id| match todo_list.get_todo(*id) {
+                Some(blocker) if blocker.is_active() => {
+                    format!("#{} {}", blocker.id, blocker.title)
+                        .yellow()
+                        .to_string()
+                }
+                Some(blocker) => format!(
+                    "#{} {} ({})",
+                    blocker.id,
+                    blocker.title,
+                    blocker.status.to_string().to_lowercase()
+                )
+                .dimmed()
+                .to_string(),
+                None => format!("#{}", id),
+            })
+            .collect();
+        println!("{}: {}", "Blocked by".bold(), blockers.join(", "));
+    }
+
+    let blocks: Vec<String> = todo_list
+        .todos
+        .iter()
+        .filter(|other| other.blocked_by.contains(&todo.id))
+        .map(|other| format!("#{}", other.id))
+        .collect();
+    if !blocks.is_empty() {
+        println!("{}: {}", "Blocks".bold(), blocks.join(", "));
+    }
+
     if let Some(ref category) = todo.category {
         println!("{}: {}", "Category".bold(), category);
     }
@@ -19,6 +57,23 @@ pub fn format_detail(todo: &Todo) {
         println!("{}: {}", "Tags".bold(), todo.tags.join(", "));
     }
 
+    if let Some(due) = todo.format_due() {
+        let due_str = match todo.due_status(chrono::Local::now()) {
+            Some(DueStatus::Overdue) => format!("{} (overdue)", due).red().bold(),
+            Some(DueStatus::Today) => format!("{} (today)", due).yellow(),
+            _ => due.normal(),
+        };
+        println!("{}: {}", "Due".bold(), due_str);
+    }
+
+    if let Some(ref recurrence) = todo.recurrence {
+        println!("{}: {}", "Repeats".bold(), recurrence);
+    }
+
+    if let Some(series_id) = todo.series_id {
+        println!("{}: #{}", "Series".bold(), series_id);
+    }
+
     println!(
         "{}: {}",
         "Created".bold(),
@@ -35,6 +90,39 @@ pub fn format_detail(todo: &Todo) {
         println!("{}: -", "Finished".bold());
     }
 
+    if !todo.time_entries.is_empty() {
+        let tracked = format_duration(todo.tracked_time(None, chrono::Local::now()));
+        match todo.running_entry() {
+            Some(entry) => println!(
+                "{}: {} {}",
+                "Time".bold(),
+                tracked,
+                format!("(running since {})", entry.start.format("%H:%M")).yellow()
+            ),
+            None => println!(
+                "{}: {} over {} sessions",
+                "Time".bold(),
+                tracked,
+                todo.time_entries.len()
+            ),
+        }
+    }
+
+    if let Some((done, total)) = todo_list.subtask_progress(todo.id) {
+        println!("{}: {}/{} done", "Subtasks".bold(), done, total);
+        for child in todo_list.children(todo.id) {
+            if child.status == crate::core::Status::Archived {
+                continue;
+            }
+            let check = if child.status == crate::core::Status::Done {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            println!("  {} #{} {}", check, child.id, child.title);
+        }
+    }
+
     if let Some(ref notes) = todo.notes {
         println!("{}: ", "Notes".bold());
         for line in notes.lines() {
@@ -43,6 +131,46 @@ pub fn format_detail(todo: &Todo) {
     }
 }
 
+/// Prints a TODO's change history, oldest first, one line per change
+pub fn format_history(events: &[HistoryEvent]) {
+    for event in events {
+        let commit = event.commit.as_deref().unwrap_or("working");
+        println!(
+            "{}  {}  {}",
+            event
+                .timestamp
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .dimmed(),
+            format!("{:<7}", commit).yellow(),
+            event.message
+        );
+        for change in &event.changes {
+            println!("    {}", change);
+        }
+    }
+}
+
+/// Prints per-TODO differences: `+` added, `-` removed, `~` changed
+pub fn format_todo_diff(diffs: &[TodoDiff]) {
+    for diff in diffs {
+        match diff {
+            TodoDiff::Added(todo) => {
+                println!("{}", format!("+ #{} {}", todo.id, todo.title).green())
+            }
+            TodoDiff::Removed(todo) => {
+                println!("{}", format!("- #{} {}", todo.id, todo.title).red())
+            }
+            TodoDiff::Changed { todo, changes, .. } => {
+                println!("{}", format!("~ #{} {}", todo.id, todo.title).yellow());
+                for change in changes {
+                    println!("    {}", change);
+                }
+            }
+        }
+    }
+}
+
 fn format_priority(priority: &crate::core::Priority) -> colored::ColoredString {
     match priority {
         crate::core::Priority::P0 => "P0 (Urgent)".red().bold(),
@@ -1,5 +1,7 @@
 pub mod table;
 pub mod detail;
+pub mod report;
 
 pub use table::*;
 pub use detail::*;
+pub use report::*;
@@ -0,0 +1,107 @@
+use crate::core::{format_duration, Todo, TodoList};
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use colored::*;
+use std::collections::BTreeMap;
+
+/// TODOs and their tracked time, keyed by project then category
+type TimeGroups<'a> = BTreeMap<String, BTreeMap<String, Vec<(&'a Todo, Duration)>>>;
+
+/// Prints tracked time grouped by project, then category, then TODO
+pub fn format_time_report(todo_list: &TodoList, since: Option<NaiveDate>) {
+    let now = Local::now();
+    let since_time = since.and_then(|date| {
+        Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest()
+    });
+
+    let mut groups: TimeGroups = BTreeMap::new();
+    for todo in &todo_list.todos {
+        let tracked = todo.tracked_time(since_time, now);
+        if tracked <= Duration::zero() {
+            continue;
+        }
+        let project = todo
+            .project
+            .clone()
+            .unwrap_or_else(|| "(no project)".to_string());
+        let category = todo
+            .category
+            .clone()
+            .unwrap_or_else(|| "general".to_string());
+        groups
+            .entry(project)
+            .or_default()
+            .entry(category)
+            .or_default()
+            .push((todo, tracked));
+    }
+
+    let heading = match since {
+        Some(date) => format!("Time tracked since {}", date.format("%Y-%m-%d")),
+        None => "Time tracked".to_string(),
+    };
+    println!("{}", heading.bold().blue());
+
+    if groups.is_empty() {
+        println!("No time tracked.");
+        return;
+    }
+
+    let sum = |items: &[(&Todo, Duration)]| {
+        items
+            .iter()
+            .fold(Duration::zero(), |total, (_, tracked)| total + *tracked)
+    };
+
+    let mut grand_total = Duration::zero();
+    for (project, categories) in &groups {
+        let project_total = categories
+            .values()
+            .fold(Duration::zero(), |total, items| total + sum(items));
+        grand_total += project_total;
+
+        println!();
+        println!(
+            "{:<50} {:>10}",
+            project.bold(),
+            format_duration(project_total).bold()
+        );
+
+        for (category, items) in categories {
+            println!(
+                "  {:<48} {:>10}",
+                category.cyan(),
+                format_duration(sum(items))
+            );
+
+            let mut items = items.clone();
+            items.sort_by_key(|(_, tracked)| std::cmp::Reverse(*tracked));
+            for (todo, tracked) in items {
+                let running = if todo.running_entry().is_some() {
+                    " ⏱"
+                } else {
+                    ""
+                };
+                let label = format!("#{} {}{}", todo.id, todo.title, running);
+                let label = if label.chars().count() > 44 {
+                    format!("{}...", label.chars().take(41).collect::<String>())
+                } else {
+                    label
+                };
+                println!(
+                    "    {:<46} {:>10}",
+                    label.dimmed(),
+                    format_duration(tracked).dimmed()
+                );
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{:<50} {:>10}",
+        "Total".bold(),
+        format_duration(grand_total).bold()
+    );
+}
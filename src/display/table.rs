@@ -1,19 +1,68 @@
 use crate::config::colors::ApolloRgb;
-use crate::core::Todo;
+use crate::core::{order_as_tree, DueStatus, Todo, TodoList};
 use colored::*;
 
-pub fn format_list(todos: &[&Todo]) {
+pub fn format_list(todos: &[&Todo], todo_list: &TodoList) {
     if todos.is_empty() {
         println!("No TODOs found.");
         return;
     }
 
+    print_header();
+    print_rows(todos, todo_list);
+
+    println!();
+    print_total(&format!("Showing {} TODOs", todos.len()));
+}
+
+/// Prints TODOs in sections, each headed by its name and count. `total` is
+/// the number of distinct TODOs, which can be less than the sum of the
+/// sections when grouping by tag.
+pub fn format_grouped_list(groups: &[(String, Vec<&Todo>)], total: usize, todo_list: &TodoList) {
+    if groups.is_empty() {
+        println!("No TODOs found.");
+        return;
+    }
+
+    print_header();
+    for (index, (name, todos)) in groups.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!(
+            "{} {}",
+            name.truecolor(
+                ApolloRgb::SKY_BLUE.0,
+                ApolloRgb::SKY_BLUE.1,
+                ApolloRgb::SKY_BLUE.2
+            )
+            .bold(),
+            format!("({})", todos.len()).truecolor(
+                ApolloRgb::MED_GRAY.0,
+                ApolloRgb::MED_GRAY.1,
+                ApolloRgb::MED_GRAY.2
+            )
+        );
+        print_rows(todos, todo_list);
+    }
+
+    println!();
+    let groups_label = if groups.len() == 1 { "group" } else { "groups" };
+    print_total(&format!(
+        "Showing {} TODOs in {} {}",
+        total,
+        groups.len(),
+        groups_label
+    ));
+}
+
+fn print_header() {
     // Print header with Apollo color styling
     println!(
         "{}",
         format!(
-            "{:<4} │ {:<3} │ {:<1} │ {:<10} │ {:<40} │ {:<15}",
-            "ID", "PRI", "S", "Category", "Title", "Tags"
+            "{:<4} │ {:<3} │ {:<1} │ {:<10} │ {:<40} │ {:<10} │ {:<15}",
+            "ID", "PRI", "S", "Category", "Title", "Due", "Tags"
         )
         .truecolor(
             ApolloRgb::LIGHT_CREAM.0,
@@ -23,7 +72,7 @@ pub fn format_list(todos: &[&Todo]) {
         .bold()
     );
 
-    let separator = "─".repeat(80);
+    let separator = "─".repeat(93);
     println!(
         "{}",
         separator.truecolor(
@@ -32,9 +81,13 @@ pub fn format_list(todos: &[&Todo]) {
             ApolloRgb::MED_GRAY.2
         )
     );
+}
 
-    // Print todos
-    for todo in todos {
+fn print_rows(todos: &[&Todo], todo_list: &TodoList) {
+    let now = chrono::Local::now();
+
+    // Print todos, each subtask indented beneath its parent
+    for (todo, depth) in order_as_tree(todos) {
         let category = todo.category.as_deref().unwrap_or("-");
         let tags = if todo.tags.is_empty() {
             "-".to_string()
@@ -42,10 +95,24 @@ pub fn format_list(todos: &[&Todo]) {
             todo.tags.join(",")
         };
 
-        let title = if todo.title.len() > 40 {
-            format!("{}...", &todo.title[..37])
+        // Parents show roll-up progress of their subtasks
+        let progress = todo_list
+            .subtask_progress(todo.id)
+            .map(|(done, total)| format!(" [{}/{}]", done, total))
+            .unwrap_or_default();
+        let indent = if depth > 0 {
+            format!("{}└ ", "  ".repeat((depth - 1).min(8)))
+        } else {
+            String::new()
+        };
+        let title_width = 40usize
+            .saturating_sub(progress.len() + indent.chars().count())
+            .max(4);
+        let title = if todo.title.chars().count() > title_width {
+            let shortened: String = todo.title.chars().take(title_width - 3).collect();
+            format!("{}{}...{}", indent, shortened, progress)
         } else {
-            todo.title.clone()
+            format!("{}{}{}", indent, todo.title, progress)
         };
 
         // Format ID with dimmed style
@@ -62,12 +129,20 @@ pub fn format_list(todos: &[&Todo]) {
             get_priority_color(&todo.priority).2,
         );
 
-        // Format status icon with color
-        let status_icon = get_status_icon(&todo.status).truecolor(
-            get_status_color(&todo.status).0,
-            get_status_color(&todo.status).1,
-            get_status_color(&todo.status).2,
-        );
+        // Format status icon with color; active TODOs waiting on a blocker show "B"
+        let status_icon = if todo.is_active() && todo_list.is_blocked(todo.id) {
+            "B".truecolor(
+                ApolloRgb::BRIGHT_ORANGE.0,
+                ApolloRgb::BRIGHT_ORANGE.1,
+                ApolloRgb::BRIGHT_ORANGE.2,
+            )
+        } else {
+            get_status_icon(&todo.status).truecolor(
+                get_status_color(&todo.status).0,
+                get_status_color(&todo.status).1,
+                get_status_color(&todo.status).2,
+            )
+        };
 
         // Format other fields
         let category_str = format!("{:<10}", category).truecolor(
@@ -82,6 +157,13 @@ pub fn format_list(todos: &[&Todo]) {
             ApolloRgb::LIGHT_CREAM.2,
         );
 
+        let due = todo
+            .due_date
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let due_color = get_due_color(todo.due_status(now));
+        let due_str = format!("{:<10}", due).truecolor(due_color.0, due_color.1, due_color.2);
+
         let tags_str = format!("{:<15}", tags).truecolor(
             ApolloRgb::CREAM.0,
             ApolloRgb::CREAM.1,
@@ -96,7 +178,7 @@ pub fn format_list(todos: &[&Todo]) {
         );
 
         println!(
-            "{} {} {} {} {} {} {} {} {} {} {}",
+            "{} {} {} {} {} {} {} {} {} {} {} {} {}",
             id_str,
             sep,
             priority_str,
@@ -107,18 +189,17 @@ pub fn format_list(todos: &[&Todo]) {
             sep,
             title_str,
             sep,
+            due_str,
+            sep,
             tags_str
         );
     }
+}
 
-    println!();
+fn print_total(text: &str) {
     println!(
         "{}",
-        format!("Showing {} TODOs", todos.len()).truecolor(
-            ApolloRgb::CREAM.0,
-            ApolloRgb::CREAM.1,
-            ApolloRgb::CREAM.2
-        )
+        text.truecolor(ApolloRgb::CREAM.0, ApolloRgb::CREAM.1, ApolloRgb::CREAM.2)
     );
 }
 
@@ -160,3 +241,13 @@ fn get_status_color(status: &crate::core::Status) -> (u8, u8, u8) {
         crate::core::Status::Archived => ApolloRgb::MED_GRAY, // Archived
     }
 }
+
+fn get_due_color(due_status: Option<DueStatus>) -> (u8, u8, u8) {
+    match due_status {
+        Some(DueStatus::Overdue) => ApolloRgb::BRIGHT_MAGENTA,
+        Some(DueStatus::Today) => ApolloRgb::BRIGHT_ORANGE,
+        Some(DueStatus::ThisWeek) => ApolloRgb::YELLOW,
+        Some(DueStatus::Later) => ApolloRgb::CREAM,
+        None => ApolloRgb::MED_GRAY,
+    }
+}
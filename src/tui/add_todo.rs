@@ -9,20 +9,25 @@ use crate::tui::forms::{FieldManager, FormRenderer, TerminalRunner, TodoFormData
 pub struct TodoCreator {
     fields: TodoFormFields,
     field_manager: FieldManager,
+    parent: Option<(u32, String)>,
 }
 
 impl TodoCreator {
-    pub fn new(title: Option<String>) -> Self {
+    pub fn new(title: Option<String>, due: Option<String>, parent: Option<(u32, String)>) -> Self {
         let mut fields = TodoFormFields::new();
 
-        // Pre-fill title if provided
+        // Pre-fill title and due date if provided
         if let Some(title) = title {
             fields = fields.with_title(title);
         }
+        if let Some(due) = due {
+            fields = fields.with_due(due);
+        }
 
         let mut creator = Self {
             fields,
-            field_manager: FieldManager::new(7), // 7 form fields
+            field_manager: FieldManager::new(8), // 8 form fields
+            parent,
         };
 
         creator.update_focus();
@@ -104,6 +109,7 @@ impl TodoCreator {
 
         // Extract and validate form data
         let form_data = TodoFormData::from_fields(&self.fields)?;
+        let parent_id = self.parent.as_ref().map(|(id, _)| *id);
 
         let handle = thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().unwrap();
@@ -116,9 +122,15 @@ impl TodoCreator {
                 todo.category = form_data.category;
                 todo.project = form_data.project;
                 todo.tags = form_data.tags;
+                todo.due_date = form_data.due_date;
+                todo.due_time = form_data.due_time;
                 todo.notes = form_data.notes;
 
+                let id = todo.id;
                 todo_list.add_todo(todo);
+                if parent_id.is_some() {
+                    todo_list.set_parent(id, parent_id)?;
+                }
                 todo_list.save().await?;
                 Ok::<(), anyhow::Error>(())
             });
@@ -143,14 +155,18 @@ impl TodoCreator {
                 Constraint::Length(3), // Title (increased for underline)
                 Constraint::Length(3), // Priority + Status (increased for underline)
                 Constraint::Length(3), // Category + Project (increased for underline)
-                Constraint::Length(3), // Tags (increased for underline)
+                Constraint::Length(3), // Tags + Due (increased for underline)
                 Constraint::Min(3),    // Notes
                 Constraint::Length(1), // Help (minimal)
             ])
             .split(size);
 
         // Simple header
-        let header_paragraph = ratatui::widgets::Paragraph::new("Add New TODO").style(
+        let header = match &self.parent {
+            Some((id, title)) => format!("Add Subtask of #{} {}", id, title),
+            None => "Add New TODO".to_string(),
+        };
+        let header_paragraph = ratatui::widgets::Paragraph::new(header).style(
             Style::default()
                 .fg(Theme::primary())
                 .add_modifier(Modifier::BOLD),
@@ -198,8 +214,14 @@ impl TodoCreator {
             "Project",
         );
 
-        // Tags field
-        FormRenderer::draw_minimal_input(f, main_chunks[4], &self.fields.tags_input, "Tags");
+        // Tags and Due on same line
+        let tags_due_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(main_chunks[4]);
+
+        FormRenderer::draw_minimal_input(f, tags_due_chunks[0], &self.fields.tags_input, "Tags");
+        FormRenderer::draw_minimal_input(f, tags_due_chunks[1], &self.fields.due_input, "Due");
 
         // Notes with minimal styling
         FormRenderer::draw_minimal_textarea(f, main_chunks[5], &self.fields.notes_textarea);
@@ -212,10 +234,26 @@ impl TodoCreator {
     }
 }
 
-pub async fn run_add_todo(title: Option<String>) -> Result<()> {
+pub async fn run_add_todo(
+    title: Option<String>,
+    due: Option<String>,
+    parent: Option<u32>,
+) -> Result<()> {
+    // Resolve the parent before taking over the terminal so a bad ID fails cleanly
+    let parent = match parent {
+        Some(parent_id) => {
+            let todo_list = load_todos().await?;
+            let parent = todo_list
+                .get_todo(parent_id)
+                .ok_or_else(|| anyhow::anyhow!("Parent TODO with ID {} not found", parent_id))?;
+            Some((parent.id, parent.title.clone()))
+        }
+        None => None,
+    };
+
     let mut terminal = TerminalRunner::init()?;
 
-    let mut creator = TodoCreator::new(title);
+    let mut creator = TodoCreator::new(title, due, parent);
     let mut should_quit = false;
 
     while !should_quit {
@@ -0,0 +1,190 @@
+use crate::config::{load_settings, AUTO_SYNC_RETRY_SECS, DEFAULT_AUTO_SYNC_INTERVAL_SECS};
+use crate::core::{
+    commit_local_changes, data_repo, fetch_remote, merge_fetched, push_local_commits,
+    RemoteUnreachable,
+};
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+/// Quiet period after a change before syncing, so a burst of edits syncs once
+const CHANGE_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// State of the background sync, shown in the search TUI header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncState {
+    /// Started, first sync not run yet
+    Idle,
+    Syncing,
+    Synced(DateTime<Local>),
+    /// The remote couldn't be reached; local commits stay queued until a retry succeeds
+    Offline,
+    /// Both machines changed the same TODO; `todo sync` asks how to resolve it
+    Conflict,
+    Failed(String),
+}
+
+/// A state change reported by the sync thread
+pub struct SyncUpdate {
+    pub state: SyncState,
+    /// Remote commits merged in, so the TODO list on screen is out of date
+    pub pulled_commits: usize,
+}
+
+/// Syncs the data repository on a background thread while the search TUI is open
+pub struct AutoSync {
+    requests: Sender<()>,
+    updates: Receiver<SyncUpdate>,
+    repo_lock: Arc<Mutex<()>>,
+    pub state: SyncState,
+}
+
+impl AutoSync {
+    /// Starts the sync thread if the `auto_sync` setting is on and the data
+    /// directory is a git repository
+    pub fn start() -> Option<AutoSync> {
+        let settings = load_settings().ok()?;
+        if !settings.auto_sync {
+            return None;
+        }
+        let (repo_path, data_path) = data_repo().ok()?;
+        let interval = Duration::from_secs(
+            settings
+                .auto_sync_interval
+                .unwrap_or(DEFAULT_AUTO_SYNC_INTERVAL_SECS)
+                .max(1),
+        );
+
+        let (request_tx, request_rx) = mpsc::channel();
+        let (update_tx, update_rx) = mpsc::channel();
+        let repo_lock = Arc::new(Mutex::new(()));
+        let worker = Worker {
+            repo_path,
+            data_path,
+            interval,
+            repo_lock: repo_lock.clone(),
+        };
+        thread::spawn(move || worker.run(request_rx, update_tx));
+
+        Some(AutoSync {
+            requests: request_tx,
+            updates: update_rx,
+            repo_lock,
+            state: SyncState::Idle,
+        })
+    }
+
+    /// Asks for a sync soon, e.g. after a change
+    pub fn request(&self) {
+        let _ = self.requests.send(());
+    }
+
+    /// Held while changing the TODO file so a sync never merges over a save in progress
+    pub fn repo_lock(&self) -> Arc<Mutex<()>> {
+        self.repo_lock.clone()
+    }
+
+    /// The latest update from the sync thread, if any arrived since the last call
+    pub fn poll(&mut self) -> Option<SyncUpdate> {
+        let mut latest = None;
+        let mut pulled_commits = 0;
+        while let Ok(update) = self.updates.try_recv() {
+            pulled_commits += update.pulled_commits;
+            latest = Some(update.state);
+        }
+        let state = latest?;
+        self.state = state.clone();
+        Some(SyncUpdate {
+            state,
+            pulled_commits,
+        })
+    }
+}
+
+pub fn lock_repo(repo_lock: &Mutex<()>) -> MutexGuard<'_, ()> {
+    repo_lock.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Worker {
+    repo_path: PathBuf,
+    data_path: PathBuf,
+    interval: Duration,
+    repo_lock: Arc<Mutex<()>>,
+}
+
+impl Worker {
+    /// Syncs right away, then on every request and after each interval; while
+    /// offline it retries sooner. Stops when the TUI drops its `AutoSync`.
+    fn run(self, requests: Receiver<()>, updates: Sender<SyncUpdate>) {
+        let mut wait = Duration::ZERO;
+        loop {
+            match requests.recv_timeout(wait) {
+                Ok(()) => {
+                    thread::sleep(CHANGE_DEBOUNCE);
+                    while requests.try_recv().is_ok() {}
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            let syncing = SyncUpdate {
+                state: SyncState::Syncing,
+                pulled_commits: 0,
+            };
+            if updates.send(syncing).is_err() {
+                return;
+            }
+            let update = self.sync_once();
+            wait = if update.state == SyncState::Offline {
+                Duration::from_secs(AUTO_SYNC_RETRY_SECS).min(self.interval)
+            } else {
+                self.interval
+            };
+            if updates.send(update).is_err() {
+                return;
+            }
+        }
+    }
+
+    fn sync_once(&self) -> SyncUpdate {
+        let repo_path: &Path = &self.repo_path;
+        let mut conflicted = false;
+
+        // Commit first so changes made while offline are queued as commits.
+        // Only local work holds the lock; fetch and push run without it so
+        // the UI never waits on the network. The lock only covers this process:
+        // a save from another terminal mid-sync makes the checkout fail
+        // instead of being overwritten, and the next sync commits it
+        let result = {
+            let _guard = lock_repo(&self.repo_lock);
+            commit_local_changes(repo_path, &self.data_path)
+        }
+        .and_then(|_| fetch_remote(repo_path))
+        .and_then(|_| {
+            let _guard = lock_repo(&self.repo_lock);
+            // Conflicts need a person to choose a side, so leave them for `todo sync`
+            merge_fetched(repo_path, &self.data_path, |_| {
+                conflicted = true;
+                anyhow::bail!("Conflicting changes need 'todo sync'")
+            })
+        })
+        .and_then(|mut report| {
+            report.pushed = push_local_commits(repo_path)?;
+            Ok(report)
+        });
+
+        let (state, pulled_commits) = match result {
+            Ok(report) => (SyncState::Synced(Local::now()), report.pulled_commits),
+            Err(e) if e.downcast_ref::<RemoteUnreachable>().is_some() => (SyncState::Offline, 0),
+            Err(_) if conflicted => (SyncState::Conflict, 0),
+            Err(e) => (SyncState::Failed(e.to_string()), 0),
+        };
+        SyncUpdate {
+            state,
+            pulled_commits,
+        }
+    }
+}
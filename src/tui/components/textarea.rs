@@ -51,13 +51,11 @@ impl TextArea {
 
         if let Event::Key(KeyEvent { code, modifiers, .. }) = event {
             match code {
-                KeyCode::Char(c) => {
-                    // Allow character input unless it's a control sequence we want to handle elsewhere
-                    if !modifiers.contains(KeyModifiers::CONTROL) {
-                        self.content.insert(self.cursor_position, *c);
-                        self.cursor_position += 1;
-                        return true;
-                    }
+                // Allow character input unless it's a control sequence we want to handle elsewhere
+                KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.content.insert(self.cursor_position, *c);
+                    self.cursor_position += 1;
+                    return true;
                 }
                 KeyCode::Backspace if *modifiers == KeyModifiers::NONE => {
                     if self.cursor_position > 0 {
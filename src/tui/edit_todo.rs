@@ -3,21 +3,29 @@ use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use ratatui::prelude::*;
 
 use crate::config::{Theme, ColorTheme};
-use crate::core::{load_todos, Status};
+use crate::core::{load_todos, ChildAction, Status};
 use crate::tui::forms::{FieldManager, FormRenderer, TerminalRunner, TodoFormData, TodoFormFields};
 
 pub struct TodoEditor {
     fields: TodoFormFields,
     field_manager: FieldManager,
     todo_id: u32,
+    original_status: Status,
+    open_subtasks: usize,
+    confirming_subtasks: bool, // asking what to do with open subtasks before saving
+    child_action: Option<ChildAction>,
 }
 
 impl TodoEditor {
     pub fn new(todo_id: u32) -> Result<Self> {
         let mut editor = Self {
             fields: TodoFormFields::new(),
-            field_manager: FieldManager::new(7), // 7 form fields
+            field_manager: FieldManager::new(8), // 8 form fields
             todo_id,
+            original_status: Status::Todo,
+            open_subtasks: 0,
+            confirming_subtasks: false,
+            child_action: None,
         };
 
         editor.load_todo_data()?;
@@ -45,6 +53,8 @@ impl TodoEditor {
 
         if let Some(todo) = todo_list.get_todo(self.todo_id) {
             self.fields.load_from_todo(todo);
+            self.original_status = todo.status.clone();
+            self.open_subtasks = todo_list.open_descendants(self.todo_id).len();
         } else {
             anyhow::bail!("TODO with ID {} not found", self.todo_id);
         }
@@ -57,11 +67,50 @@ impl TodoEditor {
         self.fields.set_field_focus(focused_field);
     }
 
+    /// True when the form completes or archives a TODO whose subtasks are still open
+    fn needs_subtask_decision(&self) -> bool {
+        let closing = matches!(
+            self.fields.status_select.selected_value(),
+            Some(Status::Done) | Some(Status::Archived)
+        );
+        closing
+            && self.open_subtasks > 0
+            && self.child_action.is_none()
+            && self.fields.status_select.selected_value() != Some(self.original_status.clone())
+    }
+
     fn handle_event(&mut self, event: &Event) -> bool {
+        // Resolve the open subtasks prompt before anything else
+        if self.confirming_subtasks {
+            if let Event::Key(key) = event {
+                let action = match key.code {
+                    KeyCode::Char('c') => ChildAction::Cascade,
+                    KeyCode::Char('l') => ChildAction::Leave,
+                    KeyCode::Esc => {
+                        self.confirming_subtasks = false;
+                        return false;
+                    }
+                    _ => return false,
+                };
+                self.confirming_subtasks = false;
+                self.child_action = Some(action);
+                if let Err(e) = self.save_todo() {
+                    eprintln!("Failed to save: {}", e);
+                    return false;
+                }
+                return true;
+            }
+            return false;
+        }
+
         // Check for Enter in title field first (before field manager navigation)
         if let Event::Key(key) = event {
             if key.code == KeyCode::Enter && self.field_manager.focused_field() == 0 {
                 // Enter pressed in title field (field index 0) - save and exit
+                if self.needs_subtask_decision() {
+                    self.confirming_subtasks = true;
+                    return false;
+                }
                 if let Err(e) = self.save_todo() {
                     eprintln!("Failed to save TODO: {}", e);
                 } else {
@@ -90,6 +139,10 @@ impl TodoEditor {
             match (key.modifiers, key.code) {
                 (KeyModifiers::CONTROL, KeyCode::Char('x'))
                 | (KeyModifiers::NONE, KeyCode::Esc) => {
+                    if self.needs_subtask_decision() {
+                        self.confirming_subtasks = true;
+                        return false;
+                    }
                     // Auto-save on exit
                     if let Err(e) = self.save_todo() {
                         // TODO: Show error to user
@@ -98,6 +151,10 @@ impl TodoEditor {
                     return true;
                 }
                 (KeyModifiers::CONTROL, KeyCode::Char('s')) => {
+                    if self.needs_subtask_decision() {
+                        self.confirming_subtasks = true;
+                        return false;
+                    }
                     if let Err(e) = self.save_todo() {
                         // TODO: Show error to user
                         eprintln!("Failed to save: {}", e);
@@ -127,6 +184,7 @@ impl TodoEditor {
         // Extract and validate form data
         let form_data = TodoFormData::from_fields(&self.fields)?;
         let todo_id = self.todo_id;
+        let child_action = self.child_action;
 
         let handle = thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().unwrap();
@@ -148,11 +206,18 @@ impl TodoEditor {
                     form_data.category,
                     form_data.project,
                     form_data.notes,
+                    None,
                 )?;
 
-                // Update title separately since update_todo doesn't handle it
+                if child_action == Some(ChildAction::Cascade) {
+                    todo_list.cascade_status(todo_id, form_data.status.clone())?;
+                }
+
+                // Update title and due date separately since update_todo takes them as strings
                 if let Some(todo) = todo_list.get_todo_mut(todo_id) {
                     todo.title = form_data.title;
+                    todo.due_date = form_data.due_date;
+                    todo.due_time = form_data.due_time;
                 }
 
                 todo_list.save().await?;
@@ -179,7 +244,7 @@ impl TodoEditor {
                 Constraint::Length(3), // Title (increased for underline)
                 Constraint::Length(3), // Priority + Status (increased for underline)
                 Constraint::Length(3), // Category + Project (increased for underline)
-                Constraint::Length(3), // Tags (increased for underline)
+                Constraint::Length(3), // Tags + Due (increased for underline)
                 Constraint::Min(3),    // Notes
                 Constraint::Length(1), // Help (minimal)
             ])
@@ -230,16 +295,35 @@ impl TodoEditor {
             "Project",
         );
 
-        // Tags field
-        FormRenderer::draw_minimal_input(f, chunks[4], &self.fields.tags_input, "Tags");
+        // Tags and Due on same line
+        let tags_due_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[4]);
+
+        FormRenderer::draw_minimal_input(f, tags_due_chunks[0], &self.fields.tags_input, "Tags");
+        FormRenderer::draw_minimal_input(f, tags_due_chunks[1], &self.fields.due_input, "Due");
 
         // Notes with minimal styling
         FormRenderer::draw_minimal_textarea(f, chunks[5], &self.fields.notes_textarea);
 
-        // Minimal help text
-        let help_text = "⏎ Save & Exit (in title) • ⌃R Archive • ⌃S Save • ⌃X Exit (auto-saves) • ⇥ Next • ⇧⇥ Previous";
+        // Minimal help text, replaced by the subtask prompt while it is open
+        let (help_text, help_color) = if self.confirming_subtasks {
+            (
+                format!(
+                    "{} open subtasks: c Update them too • l Leave them open • Esc Back to form",
+                    self.open_subtasks
+                ),
+                Theme::warning(),
+            )
+        } else {
+            (
+                "⏎ Save & Exit (in title) • ⌃R Archive • ⌃S Save • ⌃X Exit (auto-saves) • ⇥ Next • ⇧⇥ Previous".to_string(),
+                Theme::text_muted(),
+            )
+        };
         let help_paragraph =
-            ratatui::widgets::Paragraph::new(help_text).style(Style::default().fg(Theme::text_muted()));
+            ratatui::widgets::Paragraph::new(help_text).style(Style::default().fg(help_color));
         f.render_widget(help_paragraph, chunks[6]);
     }
 }
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime};
 use crate::core::{Priority, Status};
 use crate::tui::components::{Input, Select, TextArea};
 
@@ -10,9 +11,16 @@ pub struct TodoFormFields {
     pub category_input: Input,
     pub project_input: Input,
     pub tags_input: Input,
+    pub due_input: Input,
     pub notes_textarea: TextArea,
 }
 
+impl Default for TodoFormFields {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TodoFormFields {
     pub fn new() -> Self {
         let mut fields = Self {
@@ -40,6 +48,7 @@ impl TodoFormFields {
             category_input: Input::new("Category"),
             project_input: Input::new("Project"),
             tags_input: Input::new("Tags (comma-separated)"),
+            due_input: Input::new("YYYY-MM-DD [HH:MM], tomorrow, +3d"),
             notes_textarea: TextArea::new("Notes".to_string()),
         };
 
@@ -55,6 +64,11 @@ impl TodoFormFields {
         self
     }
 
+    pub fn with_due(mut self, due: String) -> Self {
+        self.due_input = self.due_input.with_value(due);
+        self
+    }
+
     pub fn load_from_todo(&mut self, todo: &crate::core::Todo) {
         self.title_input = self.title_input.clone().with_value(todo.title.clone());
         self.priority_select.set_selected(&todo.priority);
@@ -72,6 +86,10 @@ impl TodoFormFields {
             self.tags_input = self.tags_input.clone().with_value(todo.tags.join(","));
         }
 
+        if let Some(due) = todo.format_due() {
+            self.due_input = self.due_input.clone().with_value(due);
+        }
+
         if let Some(notes) = &todo.notes {
             self.notes_textarea.set_content(notes.clone());
         }
@@ -85,6 +103,7 @@ impl TodoFormFields {
         self.category_input.set_focused(false);
         self.project_input.set_focused(false);
         self.tags_input.set_focused(false);
+        self.due_input.set_focused(false);
         self.notes_textarea.set_focused(false);
     }
 
@@ -98,7 +117,8 @@ impl TodoFormFields {
             3 => self.category_input.set_focused(true),
             4 => self.project_input.set_focused(true),
             5 => self.tags_input.set_focused(true),
-            6 => self.notes_textarea.set_focused(true),
+            6 => self.due_input.set_focused(true),
+            7 => self.notes_textarea.set_focused(true),
             _ => {} // Invalid index, keep all unfocused
         }
     }
@@ -112,7 +132,8 @@ impl TodoFormFields {
             3 => self.category_input.handle_event(event),
             4 => self.project_input.handle_event(event),
             5 => self.tags_input.handle_event(event),
-            6 => self.notes_textarea.handle_event(event),
+            6 => self.due_input.handle_event(event),
+            7 => self.notes_textarea.handle_event(event),
             _ => false,
         }
     }
@@ -126,6 +147,8 @@ pub struct TodoFormData {
     pub category: Option<String>,
     pub project: Option<String>,
     pub tags: Vec<String>,
+    pub due_date: Option<NaiveDate>,
+    pub due_time: Option<NaiveTime>,
     pub notes: Option<String>,
 }
 
@@ -167,6 +190,13 @@ impl TodoFormData {
             crate::core::Todo::validate_and_normalize_tags(&fields.tags_input.value)?
         };
 
+        let (due_date, due_time) = if fields.due_input.value.trim().is_empty() {
+            (None, None)
+        } else {
+            let (date, time) = crate::core::parse_due(fields.due_input.value.trim())?;
+            (Some(date), time)
+        };
+
         let notes = if fields.notes_textarea.content().trim().is_empty() {
             None
         } else {
@@ -182,6 +212,8 @@ impl TodoFormData {
             category,
             project,
             tags,
+            due_date,
+            due_time,
             notes,
         })
     }
@@ -1,5 +1,6 @@
 pub mod add_todo;
 pub mod app;
+pub mod auto_sync;
 pub mod components;
 pub mod edit_todo;
 pub mod events;
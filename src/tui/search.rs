@@ -6,56 +6,87 @@ use ratatui::{
 };
 
 use crate::config::{ColorTheme, Theme};
-use crate::core::{get_git_status, load_todos, GitStatus, Priority, Status, Todo};
+use crate::core::{
+    format_timer, get_git_status, load_todos, order_as_tree, parse_sort, rank_by_query, redo_next,
+    sort_todos, undo_last, ChildAction, DueStatus, GitStatus, Priority, Query, SavedView, Status,
+    Todo, TodoList, Views,
+};
+use crate::tui::auto_sync::{lock_repo, AutoSync, SyncState};
 use crate::tui::components::Input;
 use crate::tui::forms::{FormRenderer, TerminalRunner};
 
 pub struct TodoSearcher {
     search_input: Input,
     filtered_todos: Vec<Todo>,
-    all_todos: Vec<Todo>,
+    filtered_depths: Vec<usize>, // subtask nesting depth, parallel to filtered_todos
+    todo_list: TodoList,
     selected_index: usize,
     list_state: ListState,
     show_help: bool,
     focus_on_search: bool, // true = search input, false = results list
     git_status: GitStatus,
+    pending_close: Option<PendingClose>, // waiting for the user to decide on open subtasks
+    child_action: Option<ChildAction>,
+    message: Option<String>, // result of the last undo/redo, shown in the help line
+    query_error: Option<String>, // why the search input doesn't parse, shown in the help line
+    query: Query,            // last query that parsed, for highlighting matches
+    views: Views,            // saved views, shown as tabs and cycled with Tab
+    auto_sync: Option<AutoSync>, // background sync, when the auto_sync setting is on
+}
+
+/// What `handle_event` asks `run_search_todo` to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchAction {
+    Exit,
+    Add,
+    Undo,
+    Redo,
+    Edit(u32),
+    RaisePriority(u32),
+    LowerPriority(u32),
+    Done(u32),
+    Archive(u32),
+}
+
+/// A done/archive request on a TODO that still has open subtasks
+struct PendingClose {
+    id: u32,
+    status: Status,
+    open: usize,
 }
 
 impl TodoSearcher {
     pub async fn new(query: Option<String>) -> Result<Self> {
-        let todo_list = load_todos().await?;
-        let all_todos = todo_list.todos.clone();
+        let todo_list = load_todos().await?.into_list();
 
         // Get git status
-        let git_status = get_git_status().unwrap_or_else(|_| GitStatus {
-            has_changes: false,
-            pretty_path: "unknown".to_string(),
-            status_message: "unavailable".to_string(),
-        });
-
-        // Apply initial filtering (archived and old done todos)
-        let initial_filtered = all_todos
-            .iter()
-            .filter(|todo| Self::should_include_todo(todo))
-            .cloned()
-            .collect();
+        let git_status = Self::load_git_status();
 
         let mut searcher = Self {
             search_input: Input::new("Search"),
-            filtered_todos: initial_filtered,
-            all_todos,
+            filtered_todos: Vec::new(),
+            filtered_depths: Vec::new(),
+            todo_list,
             selected_index: 0,
             list_state: ListState::default(),
             show_help: false,
             focus_on_search: true, // Start with search input focused
             git_status,
+            pending_close: None,
+            child_action: None,
+            message: None,
+            query_error: None,
+            query: Query::All,
+            views: Views::load()?,
+            auto_sync: AutoSync::start(),
         };
 
         // Pre-fill search if provided
         if let Some(query) = query {
             searcher.search_input = searcher.search_input.clone().with_value(query);
-            searcher.filter_todos();
         }
+        // Apply initial filtering (archived and old done todos)
+        searcher.filter_todos();
 
         // Set initial focus state
         searcher.search_input.set_focused(searcher.focus_on_search);
@@ -63,81 +94,147 @@ impl TodoSearcher {
         Ok(searcher)
     }
 
+    fn load_git_status() -> GitStatus {
+        get_git_status().unwrap_or_else(|_| GitStatus {
+            has_changes: false,
+            pretty_path: "unknown".to_string(),
+            status_message: "unavailable".to_string(),
+            branch: None,
+            ahead: 0,
+            behind: 0,
+            remote_url: None,
+        })
+    }
+
+    /// Refreshes the header after a change and lets the background sync pick it up
+    fn after_change(&mut self) {
+        self.git_status = Self::load_git_status();
+        if let Some(auto_sync) = &self.auto_sync {
+            auto_sync.request();
+        }
+    }
+
+    /// Applies news from the background sync, reloading the list if it pulled changes
+    async fn poll_sync(&mut self) -> Result<()> {
+        let Some(update) = self.auto_sync.as_mut().and_then(AutoSync::poll) else {
+            return Ok(());
+        };
+        if update.state == SyncState::Syncing {
+            return Ok(());
+        }
+        self.git_status = Self::load_git_status();
+        if update.pulled_commits > 0 {
+            match self.get_selected_todo().map(|todo| todo.id) {
+                Some(id) => self.reload_todos_preserving_selection(id).await?,
+                None => self.reload_todos().await?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The sync part of the header, e.g. `sync: offline, 2 queued`
+    fn sync_status_text(&self) -> Option<(String, Color)> {
+        let auto_sync = self.auto_sync.as_ref()?;
+        let ahead = self.git_status.ahead;
+        let (text, color) = match &auto_sync.state {
+            SyncState::Idle => ("starting".to_string(), Theme::text_muted()),
+            SyncState::Syncing => ("syncing…".to_string(), Theme::accent()),
+            SyncState::Synced(_) if ahead > 0 => (format!("{} ahead", ahead), Theme::warning()),
+            SyncState::Synced(at) => (
+                format!("synced {}", at.format("%H:%M")),
+                Theme::text_muted(),
+            ),
+            SyncState::Offline if ahead > 0 => {
+                (format!("offline, {} queued", ahead), Theme::warning())
+            }
+            SyncState::Offline => ("offline".to_string(), Theme::warning()),
+            SyncState::Conflict => ("conflict - run todo sync".to_string(), Theme::error()),
+            SyncState::Failed(error) => (format!("failed: {}", error), Theme::error()),
+        };
+        Some((format!("sync: {}", text), color))
+    }
+
     fn filter_todos(&mut self) {
-        let query = self.search_input.value.to_lowercase();
-
-        if query.is_empty() {
-            self.filtered_todos = self
-                .all_todos
-                .iter()
-                .filter(|todo| Self::should_include_todo(todo))
-                .cloned()
-                .collect();
-        } else {
-            self.filtered_todos = self
-                .all_todos
-                .iter()
-                .filter(|todo| {
-                    // Apply base filtering first
-                    if !Self::should_include_todo(todo) {
-                        return false;
-                    }
+        // While a query is half typed, keep showing the last results along with the error
+        let query = match Query::parse(&self.search_input.value) {
+            Ok(query) => {
+                self.query_error = None;
+                query
+            }
+            Err(error) => {
+                self.query_error = Some(error.to_string());
+                return;
+            }
+        };
 
-                    // Advanced search syntax
-                    if query.starts_with("#") {
-                        // Tag filtering: #tag
-                        let tag = &query[1..];
-                        return todo.tags.iter().any(|t| t.to_lowercase().contains(tag));
-                    } else if query.starts_with("@") {
-                        // Category filtering: @category
-                        let category = &query[1..];
-                        return todo
-                            .category
-                            .as_ref()
-                            .map_or(false, |c| c.to_lowercase().contains(category));
-                    } else if query.starts_with("!") {
-                        // Status filtering: !status
-                        let status = &query[1..];
-                        return format!("{:?}", todo.status).to_lowercase().contains(status);
-                    } else if query.starts_with("p") && query.len() == 2 {
-                        // Priority filtering: p0, p1, etc.
-                        if let Some(priority_char) = query.chars().nth(1) {
-                            if priority_char.is_ascii_digit() {
-                                let expected_priority = format!("p{}", priority_char);
-                                return format!("{}", todo.priority).to_lowercase()
-                                    == expected_priority;
-                            }
-                        }
-                    }
+        // Asking for a status shows done and archived TODOs too
+        let show_closed = query.mentions_status();
+        self.filtered_todos = self
+            .todo_list
+            .todos
+            .iter()
+            .filter(|todo| show_closed || Self::should_include_todo(todo))
+            .filter(|todo| query.matches(todo))
+            .cloned()
+            .collect();
 
-                    // Default fuzzy search across all fields
-                    todo.title.to_lowercase().contains(&query)
-                        || todo
-                            .notes
-                            .as_ref()
-                            .map_or(false, |n| n.to_lowercase().contains(&query))
-                        || todo
-                            .tags
-                            .iter()
-                            .any(|tag| tag.to_lowercase().contains(&query))
-                        || todo
-                            .category
-                            .as_ref()
-                            .map_or(false, |c| c.to_lowercase().contains(&query))
-                        || todo
-                            .project
-                            .as_ref()
-                            .map_or(false, |p| p.to_lowercase().contains(&query))
-                })
-                .cloned()
-                .collect();
+        // Best matches first, with subtasks directly beneath their parents
+        let mut matches: Vec<&Todo> = self.filtered_todos.iter().collect();
+        rank_by_query(&mut matches, &query);
+        if let Some(sort) = self
+            .active_view()
+            .and_then(|(_, view)| view.sort.as_deref())
+        {
+            sort_todos(&mut matches, &parse_sort(sort).unwrap_or_default());
         }
+        let (ordered, depths): (Vec<Todo>, Vec<usize>) = order_as_tree(&matches)
+            .into_iter()
+            .map(|(todo, depth)| (todo.clone(), depth))
+            .unzip();
+        self.filtered_todos = ordered;
+        self.filtered_depths = depths;
+        self.query = query;
 
         // Reset selection
         self.selected_index = 0;
         self.update_selection();
     }
 
+    /// The saved view whose query is in the search input, if any
+    fn active_view(&self) -> Option<(usize, &SavedView)> {
+        let value = self.search_input.value.trim();
+        self.views
+            .views
+            .values()
+            .enumerate()
+            .find(|(_, view)| view.query == value)
+    }
+
+    /// Fills the search input with the next (or previous) saved view; past
+    /// the last one comes back to an empty search
+    fn cycle_view(&mut self, forward: bool) {
+        let count = self.views.views.len();
+        if count == 0 {
+            return;
+        }
+        // Position count stands for the empty search, before the first view
+        let current = self.active_view().map_or(count, |(index, _)| index);
+        let next = if forward {
+            (current + 1) % (count + 1)
+        } else {
+            (current + count) % (count + 1)
+        };
+        let query = self
+            .views
+            .views
+            .values()
+            .nth(next)
+            .map(|view| view.query.clone())
+            .unwrap_or_default();
+        self.search_input = self.search_input.clone().with_value(query);
+        self.filter_todos();
+    }
+
     fn update_selection(&mut self) {
         if self.filtered_todos.is_empty() || self.focus_on_search {
             self.list_state.select(None);
@@ -166,6 +263,9 @@ impl TodoSearcher {
     }
 
     fn get_status_icon(&self, todo: &Todo) -> &'static str {
+        if todo.is_active() && self.todo_list.is_blocked(todo.id) {
+            return "B"; // Waiting on a blocker
+        }
         match todo.status {
             crate::core::Status::Todo => "T",
             crate::core::Status::InProgress => "W", // Work in progress
@@ -174,17 +274,65 @@ impl TodoSearcher {
         }
     }
 
-    fn handle_event(&mut self, event: &Event) -> Result<Option<u32>> {
+    /// Asks to mark a TODO done or archived, first asking about open subtasks
+    fn request_close(&mut self, id: u32, status: Status) -> Option<SearchAction> {
+        let open = self.todo_list.open_descendants(id).len();
+        if open > 0 {
+            self.pending_close = Some(PendingClose { id, status, open });
+            return None;
+        }
+        self.child_action = None;
+        Some(Self::close_action(id, &status))
+    }
+
+    fn close_action(id: u32, status: &Status) -> SearchAction {
+        match status {
+            Status::Archived => SearchAction::Archive(id),
+            _ => SearchAction::Done(id),
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) -> Result<Option<SearchAction>> {
+        // Resolve a pending subtask prompt before anything else
+        if let Some(pending) = self.pending_close.take() {
+            if let Event::Key(key) = event {
+                let action = match key.code {
+                    KeyCode::Char('c') => Some(ChildAction::Cascade),
+                    KeyCode::Char('l') => Some(ChildAction::Leave),
+                    KeyCode::Esc => return Ok(None), // Cancel
+                    _ => {
+                        self.pending_close = Some(pending);
+                        return Ok(None);
+                    }
+                };
+                self.child_action = action;
+                return Ok(Some(Self::close_action(pending.id, &pending.status)));
+            }
+            self.pending_close = Some(pending);
+            return Ok(None);
+        }
+
         // Handle global navigation first
         if let Event::Key(key) = event {
+            self.message = None;
             match (key.modifiers, key.code) {
-                (KeyModifiers::CONTROL, KeyCode::Char('x')) => return Ok(Some(0)), // Signal exit
-                (KeyModifiers::NONE, KeyCode::Esc) => return Ok(Some(0)),          // Signal exit
-                (KeyModifiers::CONTROL, KeyCode::Char('a')) => return Ok(Some(5000)), // Signal add todo
+                (KeyModifiers::CONTROL, KeyCode::Char('x'))
+                | (KeyModifiers::NONE, KeyCode::Esc) => return Ok(Some(SearchAction::Exit)),
+                (KeyModifiers::CONTROL, KeyCode::Char('a')) => return Ok(Some(SearchAction::Add)),
+                (KeyModifiers::CONTROL, KeyCode::Char('z')) => return Ok(Some(SearchAction::Undo)),
+                (KeyModifiers::CONTROL, KeyCode::Char('y')) => return Ok(Some(SearchAction::Redo)),
                 (KeyModifiers::NONE, KeyCode::F(1)) => {
                     self.show_help = !self.show_help;
                     return Ok(None);
                 }
+                (KeyModifiers::NONE, KeyCode::Tab) => {
+                    self.cycle_view(true);
+                    return Ok(None);
+                }
+                (_, KeyCode::BackTab) => {
+                    self.cycle_view(false);
+                    return Ok(None);
+                }
                 (KeyModifiers::NONE, KeyCode::Char('/')) => {
                     // Always focus search input
                     self.focus_on_search = true;
@@ -251,7 +399,7 @@ impl TodoSearcher {
                     }
                     (KeyModifiers::NONE, KeyCode::Enter) => {
                         if let Some(todo) = self.get_selected_todo() {
-                            return Ok(Some(todo.id)); // Signal edit todo
+                            return Ok(Some(SearchAction::Edit(todo.id)));
                         }
                         return Ok(None);
                     }
@@ -259,28 +407,30 @@ impl TodoSearcher {
                     | (KeyModifiers::NONE, KeyCode::Char('+')) => {
                         // Increase priority (make it higher) - only when results have focus
                         if let Some(todo) = self.get_selected_todo() {
-                            return Ok(Some(1000 + todo.id)); // Signal priority increase
+                            return Ok(Some(SearchAction::RaisePriority(todo.id)));
                         }
                         return Ok(None);
                     }
                     (KeyModifiers::NONE, KeyCode::Char('-')) => {
                         // Decrease priority (make it lower) - only when results have focus
                         if let Some(todo) = self.get_selected_todo() {
-                            return Ok(Some(2000 + todo.id)); // Signal priority decrease
+                            return Ok(Some(SearchAction::LowerPriority(todo.id)));
                         }
                         return Ok(None);
                     }
                     (KeyModifiers::CONTROL, KeyCode::Char('r')) => {
                         // Archive todo - only when results have focus
                         if let Some(todo) = self.get_selected_todo() {
-                            return Ok(Some(3000 + todo.id)); // Signal archive todo
+                            let id = todo.id;
+                            return Ok(self.request_close(id, Status::Archived));
                         }
                         return Ok(None);
                     }
                     (KeyModifiers::CONTROL, KeyCode::Char('d')) => {
                         // Mark todo as done - only when results have focus
                         if let Some(todo) = self.get_selected_todo() {
-                            return Ok(Some(4000 + todo.id)); // Signal mark done
+                            let id = todo.id;
+                            return Ok(self.request_close(id, Status::Done));
                         }
                         return Ok(None);
                     }
@@ -292,16 +442,31 @@ impl TodoSearcher {
         Ok(None)
     }
 
+    /// Undoes (or with `redo`, re-applies) the last change and reports it in the help line
+    async fn undo(&mut self, redo: bool) -> Result<()> {
+        let result = if redo {
+            redo_next().await
+        } else {
+            undo_last().await
+        };
+        self.message = Some(match result {
+            Ok(Some(description)) if redo => format!("✓ Redid: {}", description),
+            Ok(Some(description)) => format!("✓ Undid: {}", description),
+            Ok(None) if redo => "Nothing to redo".to_string(),
+            Ok(None) => "Nothing to undo".to_string(),
+            Err(e) => e.to_string(),
+        });
+        self.reload_todos().await
+    }
+
     async fn reload_todos(&mut self) -> Result<()> {
-        let todo_list = load_todos().await?;
-        self.all_todos = todo_list.todos.clone();
+        self.todo_list = load_todos().await?.into_list();
         self.filter_todos();
         Ok(())
     }
 
     async fn reload_todos_preserving_selection(&mut self, preserve_todo_id: u32) -> Result<()> {
-        let todo_list = load_todos().await?;
-        self.all_todos = todo_list.todos.clone();
+        self.todo_list = load_todos().await?.into_list();
         self.filter_todos();
 
         // Find the TODO with the preserved ID and update selection to it
@@ -356,8 +521,12 @@ impl TodoSearcher {
     async fn archive_todo(&mut self, todo_id: u32) -> Result<()> {
         let mut todo_list = load_todos().await?;
 
-        if let Some(todo) = todo_list.todos.iter_mut().find(|t| t.id == todo_id) {
-            todo.status = Status::Archived;
+        if todo_list.get_todo(todo_id).is_some() {
+            // Through set_status, so a running timer is stopped too
+            todo_list.set_status(todo_id, Status::Archived)?;
+            if self.child_action.take() == Some(ChildAction::Cascade) {
+                todo_list.cascade_status(todo_id, Status::Archived)?;
+            }
             todo_list.save().await?;
 
             // Reload todos and remove focus from archived item (since it won't be visible)
@@ -370,10 +539,11 @@ impl TodoSearcher {
     async fn mark_todo_done(&mut self, todo_id: u32) -> Result<()> {
         let mut todo_list = load_todos().await?;
 
-        if let Some(todo) = todo_list.todos.iter_mut().find(|t| t.id == todo_id) {
-            todo.status = Status::Done;
-            if todo.finished_date.is_none() {
-                todo.finished_date = Some(chrono::Local::now());
+        if todo_list.get_todo(todo_id).is_some() {
+            // Completing a recurring TODO also creates its next instance
+            todo_list.set_status(todo_id, Status::Done)?;
+            if self.child_action.take() == Some(ChildAction::Cascade) {
+                todo_list.cascade_status(todo_id, Status::Done)?;
             }
             todo_list.save().await?;
 
@@ -423,17 +593,39 @@ impl TodoSearcher {
         // Results list with clean styling
         self.draw_minimal_results_list(f, chunks[2]);
 
-        // Minimal help text
-        let help_text = if self.show_help {
-            "Search: #tag @category !status p0-p5 • ↑↓ Navigate • ⏎ Edit • +/= Higher Priority • - Lower Priority • ⌃R Archive • ⌃D Done • ⌃A Add TODO • F1 Toggle Help"
+        // Minimal help text, replaced by the subtask prompt while one is pending
+        let prompt;
+        let help_text = if let Some(pending) = &self.pending_close {
+            let verb = match pending.status {
+                Status::Archived => "Archive",
+                _ => "Complete",
+            };
+            prompt = format!(
+                "{} open subtasks: c {} them too • l Leave them open • Esc Cancel",
+                pending.open, verb
+            );
+            prompt.as_str()
+        } else if let Some(error) = &self.query_error {
+            error.as_str()
+        } else if let Some(message) = &self.message {
+            message.as_str()
+        } else if self.show_help {
+            "Search: #tag @category !status p0..p2 title: notes: project: created: done: due: age: \"phrase\" AND OR NOT ( ) • Tab/⇧Tab Saved views • ↑↓ Navigate • ⏎ Edit • +/= Higher Priority • - Lower Priority • ⌃R Archive • ⌃D Done • ⌃A Add TODO • ⌃Z Undo • ⌃Y Redo • F1 Toggle Help"
         } else if self.focus_on_search {
             "Type to search • ↓/⏎ Navigate to results • / Refocus • ⌃A Add TODO • F1 Help • ⌃X Exit"
         } else {
             "↑↓ Navigate • ⏎ Edit • +/= Higher Priority • - Lower Priority • ⌃R Archive • ⌃D Done • ⌃A Add TODO • F1 Help • ⌃X Exit"
         };
 
-        let help_paragraph = ratatui::widgets::Paragraph::new(help_text)
-            .style(Style::default().fg(Theme::text_muted()));
+        let help_color = if self.pending_close.is_some() {
+            Theme::warning()
+        } else if self.query_error.is_some() {
+            Theme::error()
+        } else {
+            Theme::text_muted()
+        };
+        let help_paragraph =
+            ratatui::widgets::Paragraph::new(help_text).style(Style::default().fg(help_color));
         f.render_widget(help_paragraph, chunks[3]);
     }
 
@@ -448,12 +640,42 @@ impl TodoSearcher {
             ])
             .split(area);
 
-        // Title
-        let title_paragraph = ratatui::widgets::Paragraph::new("Search TODOs").style(
+        // Title, followed by the saved views as tabs
+        let mut title_spans = vec![Span::styled(
+            "Search TODOs",
             Style::default()
                 .fg(Theme::primary())
                 .add_modifier(Modifier::BOLD),
-        );
+        )];
+        if !self.views.views.is_empty() {
+            let active = self.active_view().map(|(index, _)| index);
+            let tab_style = |selected: bool| {
+                if selected {
+                    Style::default()
+                        .fg(Theme::accent())
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                } else {
+                    Style::default().fg(Theme::text_muted())
+                }
+            };
+            title_spans.push(Span::raw("   "));
+            title_spans.push(Span::styled(
+                "all",
+                tab_style(self.search_input.value.trim().is_empty()),
+            ));
+            for (index, name) in self.views.views.keys().enumerate() {
+                title_spans.push(Span::styled(
+                    " │ ",
+                    Style::default().fg(Theme::text_muted()),
+                ));
+                title_spans.push(Span::styled(name.clone(), tab_style(active == Some(index))));
+            }
+            title_spans.push(Span::styled(
+                "   Tab next view",
+                Style::default().fg(Theme::text_muted()),
+            ));
+        }
+        let title_paragraph = ratatui::widgets::Paragraph::new(Line::from(title_spans));
         f.render_widget(title_paragraph, header_chunks[0]);
 
         // Git status
@@ -463,13 +685,38 @@ impl TodoSearcher {
             Theme::text_muted()
         };
 
-        let git_status_text = format!(
+        let mut git_status_text = format!(
             "data dir: {} • git status: {}",
             self.git_status.pretty_path, self.git_status.status_message
         );
+        if let Some(branch) = &self.git_status.branch {
+            git_status_text.push_str(&format!(" • branch: {}", branch));
+        }
 
-        let git_status_paragraph = ratatui::widgets::Paragraph::new(git_status_text)
-            .style(Style::default().fg(git_status_color));
+        let mut status_spans = vec![Span::styled(
+            git_status_text,
+            Style::default().fg(git_status_color),
+        )];
+        if let Some((sync_text, sync_color)) = self.sync_status_text() {
+            status_spans.push(Span::styled(
+                " • ",
+                Style::default().fg(Theme::text_muted()),
+            ));
+            status_spans.push(Span::styled(sync_text, Style::default().fg(sync_color)));
+        }
+        if let Some((todo, entry)) = self.todo_list.running_timer() {
+            let elapsed = entry.duration(chrono::Local::now());
+            status_spans.push(Span::styled(
+                " • ",
+                Style::default().fg(Theme::text_muted()),
+            ));
+            status_spans.push(Span::styled(
+                format!("⏱ {} #{} {}", format_timer(elapsed), todo.id, todo.title),
+                Style::default().fg(Theme::accent()),
+            ));
+        }
+
+        let git_status_paragraph = ratatui::widgets::Paragraph::new(Line::from(status_spans));
         f.render_widget(git_status_paragraph, header_chunks[1]);
 
         // Separator line
@@ -493,6 +740,7 @@ impl TodoSearcher {
         // Results list without borders
         let list_area = Rect::new(area.x, area.y + 1, area.width, area.height - 1);
 
+        let now = chrono::Local::now();
         let items: Vec<ListItem> = self
             .filtered_todos
             .iter()
@@ -500,6 +748,16 @@ impl TodoSearcher {
             .map(|(index, todo)| {
                 let icon = self.get_status_icon(todo);
                 let category = todo.category.as_deref().unwrap_or("general");
+                let depth = self.filtered_depths.get(index).copied().unwrap_or(0);
+                let indent = if depth > 0 {
+                    format!("{}└ ", "  ".repeat(depth - 1))
+                } else {
+                    String::new()
+                };
+                let progress = self
+                    .todo_list
+                    .subtask_progress(todo.id)
+                    .map(|(done, total)| format!(" [{}/{}]", done, total));
 
                 // Calculate available width for title
                 // Format: "001 P1 S │ general  │ title"
@@ -507,12 +765,30 @@ impl TodoSearcher {
                 let fixed_width = 22;
                 let available_width = (area.width as usize).saturating_sub(fixed_width);
 
-                let title = if todo.title.len() > available_width && available_width > 3 {
-                    format!("{}...", &todo.title[..available_width.saturating_sub(3)])
-                } else if available_width <= 3 {
-                    "...".to_string()
+                // Due marker shown after the title for scheduled active TODOs
+                let due_marker = match todo.due_status(now) {
+                    Some(DueStatus::Overdue) => Some(("overdue".to_string(), Theme::error())),
+                    Some(DueStatus::Today) => Some(("due today".to_string(), Theme::warning())),
+                    Some(DueStatus::ThisWeek) | Some(DueStatus::Later) => todo
+                        .format_due()
+                        .map(|due| (format!("due {}", due), Theme::text_muted())),
+                    None => None,
+                };
+                let available_width = match &due_marker {
+                    Some((text, _)) => available_width.saturating_sub(text.len() + 1),
+                    None => available_width,
+                };
+                let available_width = available_width
+                    .saturating_sub(indent.chars().count())
+                    .saturating_sub(progress.as_ref().map_or(0, |p| p.len()));
+
+                let title_length = todo.title.chars().count();
+                let (shown, ellipsis) = if available_width <= 3 {
+                    (0, "...")
+                } else if title_length > available_width {
+                    (available_width - 3, "...")
                 } else {
-                    todo.title.clone()
+                    (title_length, "")
                 };
 
                 // Get priority color
@@ -549,20 +825,36 @@ impl TodoSearcher {
                     Style::default().fg(Theme::text_secondary()),
                 );
                 let separator2_span = Span::styled("│ ", Style::default().fg(Theme::text_muted()));
-                let title_span = Span::styled(
-                    format!("{}", title),
-                    Style::default().fg(Theme::text_primary()),
-                );
+                let indent_span = Span::styled(indent, Style::default().fg(Theme::text_muted()));
+                let title_spans = self.highlighted_title(&todo.title, shown);
+                let ellipsis_span =
+                    Span::styled(ellipsis, Style::default().fg(Theme::text_primary()));
 
-                let line = Line::from(vec![
+                let mut spans = vec![
                     id_span,
                     priority_span,
                     status_span,
                     separator1_span,
                     category_span,
                     separator2_span,
-                    title_span,
-                ]);
+                    indent_span,
+                ];
+                spans.extend(title_spans);
+                spans.push(ellipsis_span);
+                if let Some(progress) = progress {
+                    spans.push(Span::styled(
+                        progress,
+                        Style::default().fg(Theme::text_secondary()),
+                    ));
+                }
+                if let Some((text, color)) = due_marker {
+                    spans.push(Span::styled(
+                        format!(" {}", text),
+                        Style::default().fg(color),
+                    ));
+                }
+
+                let line = Line::from(spans);
 
                 let item_style = if Some(index) == self.list_state.selected() {
                     Style::default()
@@ -581,6 +873,35 @@ impl TodoSearcher {
         f.render_stateful_widget(list, list_area, &mut self.list_state);
     }
 
+    /// The first `length` characters of a title, with the characters the query
+    /// matched highlighted
+    fn highlighted_title(&self, title: &str, length: usize) -> Vec<Span<'static>> {
+        let highlights = self.query.title_highlights(title);
+        let normal = Style::default().fg(Theme::text_primary());
+        let highlighted = Style::default()
+            .fg(Theme::accent())
+            .add_modifier(Modifier::BOLD);
+
+        // Group runs of characters with the same style into one span
+        let mut spans: Vec<Span> = Vec::new();
+        let mut run = String::new();
+        let mut run_highlighted = false;
+        for (index, c) in title.chars().take(length).enumerate() {
+            let is_highlighted = highlights.binary_search(&index).is_ok();
+            if is_highlighted != run_highlighted && !run.is_empty() {
+                let style = if run_highlighted { highlighted } else { normal };
+                spans.push(Span::styled(std::mem::take(&mut run), style));
+            }
+            run_highlighted = is_highlighted;
+            run.push(c);
+        }
+        if !run.is_empty() {
+            let style = if run_highlighted { highlighted } else { normal };
+            spans.push(Span::styled(run, style));
+        }
+        spans
+    }
+
     /// Helper method to determine if a TODO should be included in the filtered results
     /// Excludes archived TODOs and Done TODOs older than 3 seconds
     fn should_include_todo(todo: &Todo) -> bool {
@@ -608,22 +929,38 @@ pub async fn run_search_todo(query: Option<String>) -> Result<()> {
     let mut terminal = TerminalRunner::init()?;
 
     let mut searcher = TodoSearcher::new(query).await?;
+    let repo_lock = searcher.auto_sync.as_ref().map(AutoSync::repo_lock);
     let mut should_quit = false;
 
     while !should_quit {
+        searcher.poll_sync().await?;
         terminal.draw(|f| searcher.draw(f))?;
 
         if event::poll(std::time::Duration::from_millis(100))? {
             let event = event::read()?;
-            match searcher.handle_event(&event)? {
-                Some(0) => should_quit = true, // Exit signal
-                Some(5000) => {
-                    // Add TODO signal - exit search, run add, then return
+            let action = searcher.handle_event(&event)?;
+            let changes_todos = action.is_some_and(|action| action != SearchAction::Exit);
+            // Keep the background sync out of the TODO file while it changes.
+            // The add and edit forms run for as long as the user types, so
+            // they go without: they reload the list just before saving, and a
+            // save over a merge that landed in between is rejected
+            let in_place = !matches!(
+                action,
+                Some(SearchAction::Add) | Some(SearchAction::Edit(_))
+            );
+            let guard = match &repo_lock {
+                Some(repo_lock) if changes_todos && in_place => Some(lock_repo(repo_lock)),
+                _ => None,
+            };
+            match action {
+                Some(SearchAction::Exit) => should_quit = true,
+                Some(SearchAction::Add) => {
+                    // Exit search, run add, then return
                     TerminalRunner::leave_tui_mode(terminal)?;
 
                     // Run add TODO in a separate async context
                     use crate::tui::add_todo::run_add_todo;
-                    if let Err(e) = run_add_todo(None).await {
+                    if let Err(e) = run_add_todo(None, None, None).await {
                         eprintln!("Failed to add TODO: {}", e);
                     }
 
@@ -633,35 +970,29 @@ pub async fn run_search_todo(query: Option<String>) -> Result<()> {
                     // Reload todos
                     searcher.reload_todos().await?;
                 }
-                Some(todo_id) if todo_id >= 4000 => {
-                    // Mark done signal (4000 + todo_id)
-                    let actual_id = todo_id - 4000;
-                    if let Err(e) = searcher.mark_todo_done(actual_id).await {
+                Some(SearchAction::Undo) => searcher.undo(false).await?,
+                Some(SearchAction::Redo) => searcher.undo(true).await?,
+                Some(SearchAction::Done(todo_id)) => {
+                    if let Err(e) = searcher.mark_todo_done(todo_id).await {
                         eprintln!("Failed to mark TODO as done: {}", e);
                     }
                 }
-                Some(todo_id) if todo_id >= 3000 => {
-                    // Archive signal (3000 + todo_id)
-                    let actual_id = todo_id - 3000;
-                    if let Err(e) = searcher.archive_todo(actual_id).await {
+                Some(SearchAction::Archive(todo_id)) => {
+                    if let Err(e) = searcher.archive_todo(todo_id).await {
                         eprintln!("Failed to archive TODO: {}", e);
                     }
                 }
-                Some(todo_id) if todo_id >= 2000 => {
-                    // Priority decrease signal (2000 + todo_id)
-                    let actual_id = todo_id - 2000;
-                    if let Err(e) = searcher.change_todo_priority(actual_id, false).await {
+                Some(SearchAction::LowerPriority(todo_id)) => {
+                    if let Err(e) = searcher.change_todo_priority(todo_id, false).await {
                         eprintln!("Failed to decrease priority: {}", e);
                     }
                 }
-                Some(todo_id) if todo_id >= 1000 => {
-                    // Priority increase signal (1000 + todo_id)
-                    let actual_id = todo_id - 1000;
-                    if let Err(e) = searcher.change_todo_priority(actual_id, true).await {
+                Some(SearchAction::RaisePriority(todo_id)) => {
+                    if let Err(e) = searcher.change_todo_priority(todo_id, true).await {
                         eprintln!("Failed to increase priority: {}", e);
                     }
                 }
-                Some(todo_id) => {
+                Some(SearchAction::Edit(todo_id)) => {
                     // Edit todo - exit search, run edit, then return
                     TerminalRunner::leave_tui_mode(terminal)?;
 
@@ -679,6 +1010,10 @@ pub async fn run_search_todo(query: Option<String>) -> Result<()> {
                 }
                 None => {} // Continue
             }
+            drop(guard);
+            if changes_todos {
+                searcher.after_change();
+            }
         }
     }
 